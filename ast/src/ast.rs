@@ -1,6 +1,10 @@
 use output_manager::warn_write;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
+/// Type of the nodes standing in for erroneous parts of the program
+pub const ERROR_NODE: &str = "error";
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Node {
     node_type: String,
@@ -10,6 +14,8 @@ pub struct Node {
     data_type: Option<String>,
     dimensions: Option<usize>,
     label: Option<String>,
+    /// Free form annotations set by the grammar's semantic actions
+    attributes: BTreeMap<String, String>,
     // Consider adding type
     // consider adding variable name
 }
@@ -33,9 +39,19 @@ impl Node {
             label: None,
             data_type: None,
             dimensions: None,
+            attributes: BTreeMap::new(),
         }
     }
 
+    /// Placeholder for a part of the program lost to a syntax error
+    pub fn error(line: usize, column: usize) -> Self {
+        Node::new(ERROR_NODE, Data::Epsilon, line, column)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.node_type == ERROR_NODE
+    }
+
     pub fn data(&self) -> &Data {
         return &self.data;
     }
@@ -84,6 +100,14 @@ impl Node {
         self.label = Some(label.to_owned())
     }
 
+    pub fn attribute(&self, key: &str) -> Option<&String> {
+        self.attributes.get(key)
+    }
+
+    pub fn set_attribute(&mut self, key: &str, value: &str) {
+        self.attributes.insert(key.to_owned(), value.to_owned());
+    }
+
     pub fn dft(&self) -> DepthFirstIterator {
         DepthFirstIterator {
             to_visit: vec![self],
@@ -144,7 +168,7 @@ impl Node {
                 ));
             }
             Data::String(s) => {
-                let a = s.replace("\"", r#"\""#);
+                let a = s.escape_default();
                 label.push_str(&format!(
                     r#" [shape=box label="{}\n{}"]"#,
                     self.node_type, a
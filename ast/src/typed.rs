@@ -0,0 +1,859 @@
+//! Strongly typed view of the AST built by the grammar's semantic actions
+//!
+//! Every node type the grammar creates has its own struct or enum variant, so code
+//! walking the typed tree can't ask for a child that isn't there. While consumers
+//! migrate away from `Node`, the typed tree is converted from the parsed one with
+//! `TryFrom`, a node without the shape the grammar gives it is reported as a
+//! `MalformedNode` instead of failing deep inside semantic analysis. Converted
+//! once the type check has annotated the nodes, the variables and operations
+//! carry what it resolved, so code generation works from the typed tree.
+use crate::ast::{Data, Node};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+    pub name: String,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub classes: Vec<ClassDecl>,
+    pub functions: Vec<FuncDef>,
+    pub main: FuncBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassDecl {
+    pub name: Ident,
+    pub inherits: Vec<Ident>,
+    pub members: Vec<ClassMember>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassMember {
+    /// None when the visibility isn't written
+    pub visibility: Option<Visibility>,
+    pub declaration: MemberDecl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Visibility {
+    Public,
+    Private,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberDecl {
+    Function(FuncDecl),
+    Variable(VarDecl),
+}
+
+/// A member function declared in a class
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncDecl {
+    pub name: Ident,
+    pub params: Vec<Param>,
+    /// None for void
+    pub return_type: Option<Type>,
+}
+
+/// A free function or the definition of a member function of the class in `scope`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncDef {
+    pub name: Ident,
+    pub scope: Option<Ident>,
+    pub params: Vec<Param>,
+    /// None for void
+    pub return_type: Option<Type>,
+    pub body: FuncBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub param_type: Type,
+    pub name: Ident,
+    /// The size of each dimension, None where it is left out
+    pub dimensions: Vec<Option<i64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarDecl {
+    pub var_type: Type,
+    pub name: Ident,
+    /// The size of each dimension, None where it is left out
+    pub dimensions: Vec<Option<i64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Integer,
+    Float,
+    String,
+    Class(Ident),
+}
+
+impl Type {
+    /// The name of the type as written in the source
+    pub fn name(&self) -> &str {
+        match self {
+            Type::Integer => "integer",
+            Type::Float => "float",
+            Type::String => "string",
+            Type::Class(class) => &class.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncBody {
+    pub variables: Vec<VarDecl>,
+    pub statements: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Assign {
+        target: Variable,
+        value: Expr,
+        position: Position,
+    },
+    /// A variable whose last access is a function call
+    Call(Variable),
+    If {
+        condition: Expr,
+        then_block: Vec<Stmt>,
+        else_block: Vec<Stmt>,
+        position: Position,
+    },
+    While {
+        condition: Expr,
+        body: Vec<Stmt>,
+        position: Position,
+    },
+    Read {
+        target: Variable,
+        position: Position,
+    },
+    Write {
+        value: Expr,
+        position: Position,
+    },
+    Return {
+        value: Expr,
+        position: Position,
+    },
+    Break(Position),
+    Continue(Position),
+}
+
+impl Stmt {
+    pub fn position(&self) -> Position {
+        match self {
+            Stmt::Assign { position, .. }
+            | Stmt::If { position, .. }
+            | Stmt::While { position, .. }
+            | Stmt::Read { position, .. }
+            | Stmt::Write { position, .. }
+            | Stmt::Return { position, .. }
+            | Stmt::Break(position)
+            | Stmt::Continue(position) => *position,
+            Stmt::Call(variable) => variable.position,
+        }
+    }
+}
+
+/// A chain of data member accesses and function calls separated by dots
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub accesses: Vec<Access>,
+    pub position: Position,
+}
+
+impl Variable {
+    /// The type of the value of the variable, that of its last access
+    pub fn data_type(&self) -> Option<&str> {
+        self.accesses
+            .last()
+            .and_then(|access| access.resolution().data_type.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Access {
+    DataMember {
+        name: Ident,
+        indices: Vec<Expr>,
+        resolution: Resolution,
+    },
+    Call {
+        name: Ident,
+        args: Vec<Expr>,
+        resolution: Resolution,
+    },
+}
+
+impl Access {
+    pub fn name(&self) -> &Ident {
+        match self {
+            Access::DataMember { name, .. } | Access::Call { name, .. } => name,
+        }
+    }
+
+    pub fn resolution(&self) -> &Resolution {
+        match self {
+            Access::DataMember { resolution, .. } | Access::Call { resolution, .. } => resolution,
+        }
+    }
+}
+
+/// What the type check resolved an access to, nothing is known before it runs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resolution {
+    /// The type of the value accessed, None for a void function
+    pub data_type: Option<String>,
+    /// The class declaring the member accessed, when it's a member of an object or of the
+    /// object of the member function being defined
+    pub member: Option<String>,
+    /// The slot of the virtual table through which a member function is called
+    pub slot: Option<usize>,
+    /// The entry label of a function called directly
+    pub function: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Variable(Variable),
+    Integer(i64, Position),
+    Float(f64, Position),
+    String(String, Position),
+    Not(Box<Expr>, Position),
+    Sign(Sign, Box<Expr>, Position),
+    Ternary {
+        condition: Box<Expr>,
+        then_value: Box<Expr>,
+        else_value: Box<Expr>,
+        position: Position,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+        /// The type of the result resolved by the type check
+        data_type: Option<String>,
+        position: Position,
+    },
+}
+
+impl Expr {
+    pub fn position(&self) -> Position {
+        match self {
+            Expr::Variable(variable) => variable.position,
+            Expr::Integer(_, position)
+            | Expr::Float(_, position)
+            | Expr::String(_, position)
+            | Expr::Not(_, position)
+            | Expr::Sign(_, _, position)
+            | Expr::Ternary { position, .. }
+            | Expr::Binary { position, .. } => *position,
+        }
+    }
+
+    /// The type of the value of the expression, if it's known
+    pub fn data_type(&self) -> Option<&str> {
+        match self {
+            Expr::Variable(variable) => variable.data_type(),
+            Expr::Integer(..) => Some("integer"),
+            Expr::Float(..) => Some("float"),
+            Expr::String(..) => Some("string"),
+            Expr::Not(operand, _) | Expr::Sign(_, operand, _) => operand.data_type(),
+            Expr::Ternary { then_value, .. } => then_value.data_type(),
+            Expr::Binary { data_type, .. } => data_type.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sign {
+    Plus,
+    Minus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Or,
+    Mul,
+    Div,
+    And,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Leq,
+    Geq,
+}
+
+/// A node which doesn't have the shape the grammar gives the expected node
+#[derive(Debug, Clone, PartialEq)]
+pub struct MalformedNode {
+    pub expected: String,
+    pub found: String,
+    pub position: Position,
+}
+
+impl fmt::Display for MalformedNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected {} at line {}, col {}, found {}",
+            self.expected, self.position.line, self.position.column, self.found
+        )
+    }
+}
+
+impl std::error::Error for MalformedNode {}
+
+impl MalformedNode {
+    /// The node found where a node of the description supplied was expected
+    pub fn new(node: &Node, expected: &str) -> Self {
+        let found = match node.data() {
+            Data::Children(children) => {
+                format!("{} with {} children", node.name(), children.len())
+            }
+            Data::Integer(value) => format!("{} {}", node.name(), value),
+            Data::Float(value) => format!("{} {}", node.name(), value),
+            Data::String(value) => format!("{} \"{}\"", node.name(), value),
+            Data::Epsilon => format!("empty {}", node.name()),
+        };
+        MalformedNode {
+            expected: expected.to_string(),
+            found,
+            position: position(node),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, MalformedNode>;
+
+fn position(node: &Node) -> Position {
+    Position {
+        line: *node.line(),
+        column: *node.column(),
+    }
+}
+
+fn malformed<T>(node: &Node, expected: &str) -> Result<T> {
+    Err(MalformedNode::new(node, expected))
+}
+
+/// The children of a node made by `makefamily~N~name`
+fn family<'a, const N: usize>(node: &'a Node, name: &str) -> Result<&'a [Node; N]> {
+    match node.data() {
+        Data::Children(children) if node.name() == name => match children.as_slice().try_into() {
+            Ok(children) => Ok(children),
+            Err(_) => malformed(node, &format!("{} with {} children", name, N)),
+        },
+        _ => malformed(node, &format!("{} with {} children", name, N)),
+    }
+}
+
+/// The converted elements of a node made by `makenode~list~name`, lists emptied by
+/// semantic analysis are epsilon nodes
+fn list<'a, T>(
+    node: &'a Node,
+    name: &str,
+    element: impl Fn(&'a Node) -> Result<T>,
+) -> Result<Vec<T>> {
+    match node.data() {
+        Data::Children(children) if node.name() == name => children.iter().map(element).collect(),
+        Data::Epsilon if node.name() == name => Ok(Vec::new()),
+        _ => malformed(node, &format!("{} list", name)),
+    }
+}
+
+/// The string of a node made by `makenode~string~name`
+fn string<'a>(node: &'a Node, name: &str) -> Result<&'a str> {
+    match node.data() {
+        Data::String(value) if node.name() == name => Ok(value),
+        _ => malformed(node, &format!("{} string", name)),
+    }
+}
+
+fn ident(node: &Node) -> Result<Ident> {
+    Ok(Ident {
+        name: string(node, "id")?.to_string(),
+        position: position(node),
+    })
+}
+
+fn dimensions(node: &Node) -> Result<Vec<Option<i64>>> {
+    list(node, "dimlist", |num| match num.data() {
+        Data::Integer(size) if num.name() == "num" => Ok(Some(*size)),
+        Data::Epsilon if num.name() == "num" => Ok(None),
+        _ => malformed(num, "num"),
+    })
+}
+
+/// The type of a declaration, None for void
+fn optional_type(node: &Node) -> Result<Option<Type>> {
+    match node.data() {
+        Data::Epsilon if node.name() == "type" => Ok(None),
+        _ => Type::try_from(node).map(Some),
+    }
+}
+
+fn statements(node: &Node) -> Result<Vec<Stmt>> {
+    list(node, "statBlock", Stmt::try_from)
+}
+
+fn boxed(node: &Node) -> Result<Box<Expr>> {
+    Expr::try_from(node).map(Box::new)
+}
+
+impl TryFrom<&Node> for Program {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [classes, functions, main] = family(node, "prog")?;
+        Ok(Program {
+            classes: list(classes, "classDeclList", ClassDecl::try_from)?,
+            functions: list(functions, "funcDefList", FuncDef::try_from)?,
+            main: FuncBody::try_from(main)?,
+        })
+    }
+}
+
+impl TryFrom<&Node> for ClassDecl {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [name, inherits, members] = family(node, "classDecl")?;
+        Ok(ClassDecl {
+            name: ident(name)?,
+            inherits: list(inherits, "inherList", ident)?,
+            members: list(members, "membList", ClassMember::try_from)?,
+        })
+    }
+}
+
+impl TryFrom<&Node> for ClassMember {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [visibility, declaration] = family(node, "classDeclBody")?;
+        let visibility = match visibility.data() {
+            Data::Epsilon if visibility.name() == "visibility" => None,
+            _ => match string(visibility, "visibility")? {
+                "public" => Some(Visibility::Public),
+                "private" => Some(Visibility::Private),
+                _ => return malformed(visibility, "public or private visibility"),
+            },
+        };
+        let declaration = match declaration.name().as_str() {
+            "funcDecl" => MemberDecl::Function(FuncDecl::try_from(declaration)?),
+            _ => MemberDecl::Variable(VarDecl::try_from(declaration)?),
+        };
+        Ok(ClassMember {
+            visibility,
+            declaration,
+        })
+    }
+}
+
+impl TryFrom<&Node> for FuncDecl {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [name, params, return_type] = family(node, "funcDecl")?;
+        Ok(FuncDecl {
+            name: ident(name)?,
+            params: list(params, "fparamList", Param::try_from)?,
+            return_type: optional_type(return_type)?,
+        })
+    }
+}
+
+impl TryFrom<&Node> for FuncDef {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [first, member, params, return_type, body] = family(node, "funcDef")?;
+        // The identifier of a member function follows the class and the scope operator
+        let (name, scope) = match member.data() {
+            Data::Epsilon if member.name() == "scopeSpec" => (ident(first)?, None),
+            _ => (
+                Ident {
+                    name: string(member, "scopeSpec")?.to_string(),
+                    position: position(member),
+                },
+                Some(ident(first)?),
+            ),
+        };
+        Ok(FuncDef {
+            name,
+            scope,
+            params: list(params, "fparamList", Param::try_from)?,
+            return_type: optional_type(return_type)?,
+            body: FuncBody::try_from(body)?,
+        })
+    }
+}
+
+impl TryFrom<&Node> for Param {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [param_type, name, dimension_list] = family(node, "fparam")?;
+        Ok(Param {
+            param_type: Type::try_from(param_type)?,
+            name: ident(name)?,
+            dimensions: dimensions(dimension_list)?,
+        })
+    }
+}
+
+impl TryFrom<&Node> for VarDecl {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [var_type, name, dimension_list] = family(node, "varDecl")?;
+        Ok(VarDecl {
+            var_type: Type::try_from(var_type)?,
+            name: ident(name)?,
+            dimensions: dimensions(dimension_list)?,
+        })
+    }
+}
+
+impl TryFrom<&Node> for Type {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        Ok(match string(node, "type")? {
+            "integer" => Type::Integer,
+            "float" => Type::Float,
+            "string" => Type::String,
+            class => Type::Class(Ident {
+                name: class.to_string(),
+                position: position(node),
+            }),
+        })
+    }
+}
+
+impl TryFrom<&Node> for FuncBody {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let [variables, statement_list] = family(node, "funcBody")?;
+        Ok(FuncBody {
+            variables: list(variables, "varList", VarDecl::try_from)?,
+            statements: statements(statement_list)?,
+        })
+    }
+}
+
+impl TryFrom<&Node> for Stmt {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let position = position(node);
+        Ok(match node.name().as_str() {
+            "assignOp" => {
+                let [target, value] = family(node, "assignOp")?;
+                let target = Variable::try_from(target)?;
+                // The statement starts where its target does
+                let position = target.position;
+                Stmt::Assign {
+                    target,
+                    value: Expr::try_from(value)?,
+                    position,
+                }
+            }
+            "var" => Stmt::Call(Variable::try_from(node)?),
+            "ifStat" => {
+                let [condition, then_block, else_block] = family(node, "ifStat")?;
+                Stmt::If {
+                    condition: Expr::try_from(condition)?,
+                    then_block: statements(then_block)?,
+                    else_block: statements(else_block)?,
+                    position,
+                }
+            }
+            "whileStat" => {
+                let [condition, body] = family(node, "whileStat")?;
+                Stmt::While {
+                    condition: Expr::try_from(condition)?,
+                    body: statements(body)?,
+                    position,
+                }
+            }
+            "readStat" => {
+                let [target] = family(node, "readStat")?;
+                Stmt::Read {
+                    target: Variable::try_from(target)?,
+                    position,
+                }
+            }
+            "writeStat" => {
+                let [value] = family(node, "writeStat")?;
+                Stmt::Write {
+                    value: Expr::try_from(value)?,
+                    position,
+                }
+            }
+            "returnStat" => {
+                let [value] = family(node, "returnStat")?;
+                Stmt::Return {
+                    value: Expr::try_from(value)?,
+                    position,
+                }
+            }
+            "breakStat" => {
+                let [keyword] = family(node, "breakStat")?;
+                string(keyword, "break")?;
+                Stmt::Break(position)
+            }
+            "continueStat" => {
+                let [keyword] = family(node, "continueStat")?;
+                string(keyword, "continue")?;
+                Stmt::Continue(position)
+            }
+            _ => return malformed(node, "statement"),
+        })
+    }
+}
+
+impl TryFrom<&Node> for Variable {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let accesses = list(node, "var", Access::try_from)?;
+        if accesses.is_empty() {
+            return malformed(node, "var with at least one access");
+        }
+        // The list of accesses is placed at the token preceding it by the parser
+        let position = accesses[0].name().position;
+        Ok(Variable { accesses, position })
+    }
+}
+
+impl TryFrom<&Node> for Access {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        if node.name() == "fCall" {
+            let [name, args] = family(node, "fCall")?;
+            Ok(Access::Call {
+                name: ident(name)?,
+                args: list(args, "aParams", Expr::try_from)?,
+                resolution: Resolution::try_from(node)?,
+            })
+        } else {
+            let [name, indices] = family(node, "dataMember")?;
+            Ok(Access::DataMember {
+                name: ident(name)?,
+                indices: list(indices, "indexList", Expr::try_from)?,
+                resolution: Resolution::try_from(node)?,
+            })
+        }
+    }
+}
+
+impl TryFrom<&Node> for Resolution {
+    type Error = MalformedNode;
+
+    /// Read the attributes the type check gives a dataMember or fCall
+    fn try_from(node: &Node) -> Result<Self> {
+        let slot = match node.attribute("slot") {
+            Some(slot) => match slot.parse() {
+                Ok(slot) => Some(slot),
+                Err(_) => return malformed(node, "virtual table slot"),
+            },
+            None => None,
+        };
+        Ok(Resolution {
+            data_type: node.data_type(),
+            member: node.attribute("member").cloned(),
+            slot,
+            function: node.attribute("function").cloned(),
+        })
+    }
+}
+
+impl TryFrom<&Node> for Expr {
+    type Error = MalformedNode;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        let position = position(node);
+        Ok(match (node.name().as_str(), node.data()) {
+            ("var", _) => Expr::Variable(Variable::try_from(node)?),
+            ("intfactor", Data::Integer(value)) => Expr::Integer(*value, position),
+            ("floatfactor", Data::Float(value)) => Expr::Float(*value, position),
+            ("stringfactor", Data::String(value)) => Expr::String(value.clone(), position),
+            ("not", _) => {
+                let [operand] = family(node, "not")?;
+                Expr::Not(boxed(operand)?, position)
+            }
+            ("sign", _) => {
+                let [sign, operand] = family(node, "sign")?;
+                let sign = match sign.name().as_str() {
+                    "pos" => Sign::Plus,
+                    "neg" => Sign::Minus,
+                    _ => return malformed(sign, "pos or neg sign"),
+                };
+                Expr::Sign(sign, boxed(operand)?, position)
+            }
+            ("ternary", _) => {
+                let [condition, then_value, else_value] = family(node, "ternary")?;
+                Expr::Ternary {
+                    condition: boxed(condition)?,
+                    then_value: boxed(then_value)?,
+                    else_value: boxed(else_value)?,
+                    position,
+                }
+            }
+            (name @ "addOp", _) | (name @ "mulOp", _) | (name @ "relOp", _) => {
+                let [lhs, op, rhs] = family(node, name)?;
+                let op = match (name, op.name().as_str()) {
+                    ("addOp", "+") => BinaryOp::Add,
+                    ("addOp", "-") => BinaryOp::Sub,
+                    ("addOp", "or") => BinaryOp::Or,
+                    ("mulOp", "*") => BinaryOp::Mul,
+                    ("mulOp", "/") => BinaryOp::Div,
+                    ("mulOp", "and") => BinaryOp::And,
+                    ("relOp", "releq") => BinaryOp::Eq,
+                    ("relOp", "relneq") => BinaryOp::Neq,
+                    ("relOp", "lt") => BinaryOp::Lt,
+                    ("relOp", "gt") => BinaryOp::Gt,
+                    ("relOp", "leq") => BinaryOp::Leq,
+                    ("relOp", "geq") => BinaryOp::Geq,
+                    _ => return malformed(op, &format!("operator of {}", name)),
+                };
+                Expr::Binary {
+                    op,
+                    lhs: boxed(lhs)?,
+                    rhs: boxed(rhs)?,
+                    data_type: node.data_type(),
+                    position,
+                }
+            }
+            _ => return malformed(node, "expression"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(name: &str, data: Data) -> Node {
+        Node::new(name, data, 1, 1)
+    }
+
+    fn parent(name: &str, children: Vec<Node>) -> Node {
+        Node::new(name, Data::Children(children), 1, 1)
+    }
+
+    fn variable(name: &str) -> Node {
+        parent(
+            "var",
+            vec![parent(
+                "dataMember",
+                vec![
+                    leaf("id", Data::String(name.to_string())),
+                    leaf("indexList", Data::Children(vec![])),
+                ],
+            )],
+        )
+    }
+
+    #[test]
+    fn test_convert_statements() {
+        let node = parent(
+            "assignOp",
+            vec![
+                variable("x"),
+                parent(
+                    "addOp",
+                    vec![
+                        variable("y"),
+                        leaf("+", Data::String("+".to_string())),
+                        leaf("intfactor", Data::Integer(1)),
+                    ],
+                ),
+            ],
+        );
+        let position = Position { line: 1, column: 1 };
+        let access = |name: &str| Variable {
+            accesses: vec![Access::DataMember {
+                name: Ident {
+                    name: name.to_string(),
+                    position,
+                },
+                indices: vec![],
+                resolution: Resolution::default(),
+            }],
+            position,
+        };
+        assert_eq!(
+            Stmt::try_from(&node),
+            Ok(Stmt::Assign {
+                target: access("x"),
+                value: Expr::Binary {
+                    op: BinaryOp::Add,
+                    lhs: Box::new(Expr::Variable(access("y"))),
+                    rhs: Box::new(Expr::Integer(1, position)),
+                    data_type: None,
+                    position,
+                },
+                position,
+            })
+        );
+    }
+
+    #[test]
+    fn test_convert_member_function_definition() {
+        let node = parent(
+            "funcDef",
+            vec![
+                leaf("id", Data::String("LINEAR".to_string())),
+                leaf("scopeSpec", Data::String("evaluate".to_string())),
+                leaf("fparamList", Data::Epsilon),
+                leaf("type", Data::Epsilon),
+                parent(
+                    "funcBody",
+                    vec![
+                        leaf("varList", Data::Epsilon),
+                        leaf("statBlock", Data::Epsilon),
+                    ],
+                ),
+            ],
+        );
+        let definition = FuncDef::try_from(&node).unwrap();
+        assert_eq!(definition.name.name, "evaluate");
+        assert_eq!(definition.scope.unwrap().name, "LINEAR");
+    }
+
+    #[test]
+    fn test_malformed_node() {
+        let node = parent(
+            "ifStat",
+            vec![variable("x"), leaf("statBlock", Data::Epsilon)],
+        );
+        let error = Stmt::try_from(&node).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected ifStat with 3 children at line 1, col 1, found ifStat with 2 children"
+        );
+    }
+}
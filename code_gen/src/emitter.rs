@@ -0,0 +1,862 @@
+//! Emit the MOON code of the three-address code
+//!
+//! The temporaries used only in the block computing them, with no call in between, are kept in
+//! the registers r4 to r12, the others are stored at labels of the function. The registers r1 to
+//! r3 hold the operands read from memory.
+
+use crate::ir::{self, Instruction, Operand, OperandType, Operator, Program, Terminator};
+use crate::macros as mm; // for moon-macros
+use crate::moon_instructions as moon;
+use crate::preamble;
+use crate::register::{Register, R0, R13, R14, R15};
+use crate::CodeGenError;
+use output_manager::OutputConfig;
+use semantic_analyzer::{SymbolTable, SymbolTableEntry};
+use std::collections::HashMap;
+
+const OUTPUT_BUFFER_SIZE: usize = 20;
+
+// The words holding the return address, the address of the object of a member function,
+// the addresses of the objects or the values passed as arguments and the temporaries
+const WORD_SIZE: usize = 4;
+
+// Following the library conventions the arguments start at -8(r14), preceded by the object
+// for member functions. They are copied to the labels of the parameters on entry so
+// the callee can call other functions in turn, although not recursively.
+const FIRST_ARGUMENT: usize = 8;
+
+/// The registers available to the temporaries
+const FIRST_ALLOCATED: usize = 4;
+const LAST_ALLOCATED: usize = 12;
+
+/// The range of the immediate operands of the MOON instructions
+const IMMEDIATE_MIN: i64 = -32768;
+const IMMEDIATE_MAX: i64 = 32767;
+
+pub fn emit(
+    program: &Program,
+    global_table: &SymbolTable,
+    output: &mut OutputConfig,
+) -> Result<(), CodeGenError> {
+    // The operations on floats would need a library computing on their bits, so no code is
+    // emitted rather than computing on them as integers
+    for function in &program.functions {
+        let float_operation = function
+            .blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .find(|instruction| {
+                matches!(
+                    instruction,
+                    Instruction::Binary {
+                        operand_type: OperandType::Float,
+                        ..
+                    }
+                )
+            });
+        if let Some(instruction) = float_operation {
+            return Err(CodeGenError::Unemitted(
+                instruction.to_string(),
+                function.name.clone(),
+            ));
+        }
+    }
+
+    // Add the contents of the lib file
+    output_manager::warn_write(&mut output.code_file, &output.code_path, preamble::PREAMBLE);
+
+    // This is taken from the slides
+    // Add the buffer for output
+    output.add_data(&moon::cmt_line(" Buffer space used for console output"));
+    mm::res(OUTPUT_BUFFER_SIZE, "buf", output);
+
+    reserve_space(global_table, output);
+    for function in program.functions.iter() {
+        emit_function(function, output);
+    }
+    Ok(())
+}
+
+fn reserve_space(table: &SymbolTable, output: &mut OutputConfig) {
+    // Iterate over the functions and create labeled reserve statements for the various functions
+    for element in &table.values {
+        match element {
+            SymbolTableEntry::Function(function) => {
+                output.add_data(&moon::cmt_line(&format!(
+                    "Reserved memory for function {}",
+                    function.id()
+                )));
+                let function_table = function.symbol_table();
+                // main halts instead of returning to a caller
+                if function.id() != "main" {
+                    mm::res(WORD_SIZE, &function_table.link_label(), output);
+                }
+                if function.is_class_member() {
+                    mm::res(WORD_SIZE, &function_table.this_label(), output);
+                }
+                reserve_space(function_table, output);
+            }
+            SymbolTableEntry::Class(class) => {
+                // The addresses of the member functions, an object finds the ones of its class through its header
+                output.add_data(&moon::cmt_line(&format!(
+                    "Virtual table of class {}",
+                    class.id()
+                )));
+                let mut slots: Vec<&str> = class
+                    .virtual_table()
+                    .iter()
+                    .map(|slot| slot.label().as_str())
+                    .collect();
+                if slots.is_empty() {
+                    slots.push("0");
+                }
+                output.add_data(&moon::labeled_line(
+                    &class.virtual_table_label(),
+                    &moon::mem_store_w(slots.as_slice()),
+                ));
+                reserve_space(class.symbol_table(), output);
+            }
+            SymbolTableEntry::Local(local) => {
+                mm::res(*local.bytes(), &table.mangle(local.id()), output)
+            }
+            SymbolTableEntry::Param(param) => {
+                mm::res(*param.bytes(), &table.mangle(param.id()), output)
+            }
+            // The literals and the intermediate values are operands of the three-address code
+            _ => (),
+        }
+    }
+}
+
+/// Where the value of a temporary is kept
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Storage {
+    Register(Register),
+    Memory,
+    /// The value is never read, only the calls computing it are made
+    Discarded,
+}
+
+/// The position of an instruction in its block, the terminator comes after the instructions
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Position {
+    block: usize,
+    index: usize,
+}
+
+/// Choose where each temporary of the function is kept
+fn allocate(function: &ir::Function) -> Vec<Storage> {
+    let mut definitions = vec![Vec::new(); function.temporaries];
+    let mut uses = vec![Vec::new(); function.temporaries];
+    let mut calls = Vec::new();
+
+    for (block_index, block) in function.blocks.iter().enumerate() {
+        for (index, instruction) in block.instructions.iter().enumerate() {
+            let position = Position {
+                block: block_index,
+                index,
+            };
+            if let Some(Operand::Temporary(temporary)) = instruction.destination() {
+                definitions[*temporary].push(position);
+            }
+            for operand in instruction.uses() {
+                if let Operand::Temporary(temporary) = operand {
+                    uses[*temporary].push(position);
+                }
+            }
+            if instruction.is_call() {
+                calls.push(position);
+            }
+        }
+        for operand in block.terminator.uses() {
+            if let Operand::Temporary(temporary) = operand {
+                uses[*temporary].push(Position {
+                    block: block_index,
+                    index: block.instructions.len(),
+                });
+            }
+        }
+    }
+
+    // The interval of the temporaries that may be kept in a register, by block
+    let mut intervals: Vec<Vec<(usize, usize, usize)>> = vec![Vec::new(); function.blocks.len()];
+    let mut storage = vec![Storage::Memory; function.temporaries];
+    for temporary in 0..function.temporaries {
+        let definition = match definitions[temporary].as_slice() {
+            [definition] => *definition,
+            _ => continue,
+        };
+        let last = match uses[temporary].iter().map(|use_| use_.index).max() {
+            Some(last) => last,
+            None => {
+                storage[temporary] = Storage::Discarded;
+                continue;
+            }
+        };
+        let local = uses[temporary]
+            .iter()
+            .all(|use_| use_.block == definition.block && use_.index > definition.index);
+        // The called functions don't preserve the registers
+        let preserved = !calls.iter().any(|call| {
+            call.block == definition.block && call.index > definition.index && call.index < last
+        });
+        if local && preserved {
+            intervals[definition.block].push((definition.index, last, temporary));
+        }
+    }
+
+    // A register is free again once the last instruction reading it has read it
+    for mut block_intervals in intervals {
+        block_intervals.sort_unstable();
+        let mut free: Vec<usize> = (FIRST_ALLOCATED..=LAST_ALLOCATED).rev().collect();
+        let mut active: Vec<(usize, usize)> = Vec::new();
+        for (start, end, temporary) in block_intervals {
+            active.retain(|&(active_end, register)| {
+                if active_end <= start {
+                    free.push(register);
+                    false
+                } else {
+                    true
+                }
+            });
+            free.sort_unstable_by(|a, b| b.cmp(a));
+            if let Some(register) = free.pop() {
+                active.push((end, register));
+                storage[temporary] = Storage::Register(Register::general(register));
+            }
+        }
+    }
+    storage
+}
+
+struct Emitter<'a> {
+    function: &'a ir::Function,
+    storage: Vec<Storage>,
+    /// The label of the next instruction
+    pending_label: Option<String>,
+    /// The labels of the constants too large for an immediate operand
+    constants: HashMap<i64, String>,
+}
+
+fn emit_function(function: &ir::Function, output: &mut OutputConfig) {
+    let mut emitter = Emitter {
+        function,
+        storage: allocate(function),
+        pending_label: None,
+        constants: HashMap::new(),
+    };
+
+    if function.is_main {
+        output.add_exec(&moon::cmt_line("Begin main ==================================================================================================="));
+        output.add_exec(&moon::instr_line(&moon::entry()));
+        output.add_exec(&moon::instr_line(&moon::add_i(&R14, &R0, "topaddr")));
+    } else {
+        mm::cmt_exec(&format!("Begin function {} =================================================================================", function.entry), output);
+        emitter.pending_label = Some(function.entry.clone());
+        emitter.line(&moon::store_w(&function.link, &R0, &R15), output);
+    }
+
+    // The blocks after a return that no jump leads to aren't emitted
+    let reachable = reachable(function);
+    let layout: Vec<usize> = (0..function.blocks.len())
+        .filter(|block| reachable[*block])
+        .collect();
+    let targets: Vec<usize> = layout
+        .iter()
+        .enumerate()
+        .flat_map(|(position, block)| jumps(function, *block, layout.get(position + 1).copied()))
+        .collect();
+
+    for (position, block) in layout.iter().enumerate() {
+        if targets.contains(block) {
+            emitter.label(function.block_label(*block), output);
+        }
+        for instruction in &function.blocks[*block].instructions {
+            mm::cmt_exec(&instruction.to_string(), output);
+            emitter.instruction(instruction, output);
+        }
+        let terminator = &function.blocks[*block].terminator;
+        let next = layout.get(position + 1).copied();
+        // Neither falling through to the next block nor halting at the end of main are commented
+        let falls_through =
+            matches!(terminator, Terminator::Jump(_)) && jumps(function, *block, next).is_empty();
+        let halts = function.is_main && matches!(terminator, Terminator::Return(_));
+        if !falls_through && !halts {
+            mm::cmt_exec(&terminator.to_string(), output);
+        }
+        emitter.terminator(terminator, next, output);
+    }
+    if emitter.pending_label.is_some() {
+        emitter.line(&moon::noop(), output);
+    }
+
+    if function.is_main {
+        mm::cmt_exec("==================================================================================================================", output);
+        mm::cmt_exec("   END OF PROGRAM/BEGINNING OF DATA", output);
+        mm::cmt_exec("==================================================================================================================", output);
+    }
+
+    let in_memory: Vec<usize> = (0..emitter.storage.len())
+        .filter(|temporary| emitter.storage[*temporary] == Storage::Memory)
+        .collect();
+    if !in_memory.is_empty() || !emitter.constants.is_empty() {
+        output.add_data(&moon::cmt_line(&format!(
+            "Temporaries of function {}",
+            function.name
+        )));
+    }
+    for temporary in in_memory {
+        mm::res(WORD_SIZE, &function.temporary_label(temporary), output);
+    }
+    let mut constants: Vec<(&i64, &String)> = emitter.constants.iter().collect();
+    constants.sort_unstable_by_key(|(_, label)| label.as_str());
+    for (value, label) in constants {
+        output.add_data(&moon::labeled_line(
+            label,
+            &moon::mem_store_w(&[&value.to_string()]),
+        ));
+    }
+}
+
+/// Whether each block can be reached from the first
+fn reachable(function: &ir::Function) -> Vec<bool> {
+    let mut reachable = vec![false; function.blocks.len()];
+    let mut pending = vec![0];
+    while let Some(index) = pending.pop() {
+        if !reachable[index] {
+            reachable[index] = true;
+            pending.extend(function.blocks[index].terminator.successors());
+        }
+    }
+    reachable
+}
+
+/// The blocks a block jumps to, the block laid out after it is reached without a jump
+fn jumps(function: &ir::Function, block: usize, next: Option<usize>) -> Vec<usize> {
+    match &function.blocks[block].terminator {
+        Terminator::Jump(target) if Some(*target) == next => Vec::new(),
+        Terminator::Jump(target) => vec![*target],
+        Terminator::Branch {
+            then, otherwise, ..
+        } => [*then, *otherwise]
+            .iter()
+            .copied()
+            .filter(|target| Some(*target) != next)
+            .collect(),
+        Terminator::Return(_) => Vec::new(),
+    }
+}
+
+fn is_immediate(value: i64) -> bool {
+    (IMMEDIATE_MIN..=IMMEDIATE_MAX).contains(&value)
+}
+
+impl<'a> Emitter<'a> {
+    /// Emit an instruction, with the pending label if there is one
+    fn line(&mut self, instruction: &str, output: &mut OutputConfig) {
+        match self.pending_label.take() {
+            Some(label) => output.add_exec(&moon::labeled_line(&label, instruction)),
+            None => output.add_exec(&moon::instr_line(instruction)),
+        }
+    }
+
+    /// Label the next instruction, a line only holds one label
+    fn label(&mut self, label: String, output: &mut OutputConfig) {
+        if self.pending_label.is_some() {
+            self.line(&moon::noop(), output);
+        }
+        self.pending_label = Some(label);
+    }
+
+    fn constant_label(&mut self, value: i64) -> String {
+        let next = self.constants.len();
+        let prefix = &self.function.prefix;
+        self.constants
+            .entry(value)
+            .or_insert_with(|| format!("{}_k{}", prefix, next))
+            .clone()
+    }
+
+    /// The register holding the value of an operand, loaded in the scratch register if needed
+    fn read(
+        &mut self,
+        operand: &Operand,
+        scratch: Register,
+        output: &mut OutputConfig,
+    ) -> Register {
+        match operand {
+            Operand::Temporary(temporary) => match self.storage[*temporary] {
+                Storage::Register(register) => register,
+                _ => {
+                    let label = self.function.temporary_label(*temporary);
+                    self.line(&moon::load_w(&scratch, &label, &R0), output);
+                    scratch
+                }
+            },
+            Operand::Constant(value) if is_immediate(*value) => {
+                self.line(&moon::add_i(&scratch, &R0, &value.to_string()), output);
+                scratch
+            }
+            Operand::Constant(value) => {
+                let label = self.constant_label(*value);
+                self.line(&moon::load_w(&scratch, &label, &R0), output);
+                scratch
+            }
+            Operand::Label(label) => {
+                self.line(&moon::load_w(&scratch, label, &R0), output);
+                scratch
+            }
+        }
+    }
+
+    /// The register the value of a destination is computed in
+    fn target(&self, destination: &Operand) -> Register {
+        match destination {
+            Operand::Temporary(temporary) => match self.storage[*temporary] {
+                Storage::Register(register) => register,
+                _ => Register::general(1),
+            },
+            _ => Register::general(1),
+        }
+    }
+
+    /// Store the value computed in a register to its destination, unless it's kept there
+    fn write(&mut self, destination: &Operand, value: Register, output: &mut OutputConfig) {
+        let label = match destination {
+            Operand::Temporary(temporary) => match self.storage[*temporary] {
+                Storage::Register(register) => {
+                    if register != value {
+                        self.line(&moon::add(&register, &value, &R0), output);
+                    }
+                    return;
+                }
+                Storage::Memory => self.function.temporary_label(*temporary),
+                Storage::Discarded => return,
+            },
+            Operand::Label(label) => label.clone(),
+            Operand::Constant(_) => panic!(),
+        };
+        self.line(&moon::store_w(&label, &R0, &value), output);
+    }
+
+    fn is_discarded(&self, destination: &Operand) -> bool {
+        matches!(destination, Operand::Temporary(temporary) if self.storage[*temporary] == Storage::Discarded)
+    }
+
+    fn instruction(&mut self, instruction: &Instruction, output: &mut OutputConfig) {
+        match instruction {
+            Instruction::Copy {
+                destination,
+                source,
+            } => {
+                let target = self.target(destination);
+                let value = self.read(source, target, output);
+                self.write(destination, value, output);
+            }
+            Instruction::Binary {
+                destination,
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                if !self.is_discarded(destination) {
+                    self.binary(destination, *operator, left, right, output);
+                }
+            }
+            Instruction::Address { destination, label } => {
+                if !self.is_discarded(destination) {
+                    let target = self.target(destination);
+                    self.line(&moon::add_i(&target, &R0, label), output);
+                    self.write(destination, target, output);
+                }
+            }
+            Instruction::Load {
+                destination,
+                address,
+                offset,
+            } => {
+                if !self.is_discarded(destination) {
+                    let address = self.read(address, Register::general(2), output);
+                    let target = self.target(destination);
+                    self.line(
+                        &moon::load_w(&target, &offset.to_string(), &address),
+                        output,
+                    );
+                    self.write(destination, target, output);
+                }
+            }
+            Instruction::Store {
+                address,
+                offset,
+                source,
+            } => {
+                let address = self.read(address, Register::general(2), output);
+                let value = self.read(source, Register::general(3), output);
+                self.line(
+                    &moon::store_w(&offset.to_string(), &address, &value),
+                    output,
+                );
+            }
+            Instruction::Parameter { destination, index } => {
+                let target = self.target(destination);
+                let offset = format!("-{}", FIRST_ARGUMENT + index * WORD_SIZE);
+                self.line(&moon::load_w(&target, &offset, &R14), output);
+                self.write(destination, target, output);
+            }
+            Instruction::Argument { index, source } => {
+                let value = self.read(source, Register::general(1), output);
+                let offset = format!("-{}", FIRST_ARGUMENT + index * WORD_SIZE);
+                self.line(&moon::store_w(&offset, &R14, &value), output);
+            }
+            Instruction::Call {
+                destination,
+                function,
+            } => {
+                self.line(&moon::jmp_lnk(&R15, function), output);
+                if let Some(destination) = destination {
+                    self.write(destination, R13, output);
+                }
+            }
+            Instruction::VirtualCall {
+                destination,
+                object,
+                slot,
+            } => {
+                // The header of the object holds the address of the virtual table of its class
+                let word = Register::general(1);
+                let object = self.read(object, word, output);
+                self.line(&moon::load_w(&word, "0", &object), output);
+                let slot_offset = slot * WORD_SIZE;
+                self.line(
+                    &moon::load_w(&word, &slot_offset.to_string(), &word),
+                    output,
+                );
+                self.line(&moon::jmp_lnk_reg(&R15, &word), output);
+                if let Some(destination) = destination {
+                    self.write(destination, R13, output);
+                }
+            }
+            Instruction::Write { source } => {
+                // Taken from slides
+                let local = Register::general(1);
+                let value = self.read(source, local, output);
+                // put value on stack
+                self.line(&moon::store_w("-8", &R14, &value), output);
+                // link buffer to stack
+                self.line(&moon::add_i(&local, &R0, "buf"), output);
+                self.line(&moon::store_w("-12", &R14, &local), output);
+                // convert int to string for output
+                self.line(&moon::jmp_lnk(&R15, "intstr"), output);
+                self.line(&moon::store_w("-8", &R14, &R13), output);
+                // output to console
+                self.line(&moon::jmp_lnk(&R15, "putstr"), output);
+            }
+            Instruction::Read { destination } => {
+                // read a line in the buffer
+                let local = Register::general(1);
+                self.line(&moon::add_i(&local, &R0, "buf"), output);
+                self.line(&moon::store_w("-8", &R14, &local), output);
+                self.line(&moon::jmp_lnk(&R15, "getstr"), output);
+                // convert the string read, still on stack, to an int
+                self.line(&moon::jmp_lnk(&R15, "strint"), output);
+                self.write(destination, R13, output);
+            }
+        }
+    }
+
+    fn binary(
+        &mut self,
+        destination: &Operand,
+        operator: Operator,
+        left: &Operand,
+        right: &Operand,
+        output: &mut OutputConfig,
+    ) {
+        let target = self.target(destination);
+
+        // The operands of and and or are any integer, only their being zero matters
+        if let Operator::And | Operator::Or = operator {
+            let left = self.read(left, Register::general(2), output);
+            self.line(&moon::cmp_neq_i(&Register::general(2), &left, "0"), output);
+            let right = self.read(right, Register::general(3), output);
+            self.line(&moon::cmp_neq_i(&Register::general(3), &right, "0"), output);
+            let (left, right) = (Register::general(2), Register::general(3));
+            let line = match operator {
+                Operator::And => moon::and(&target, &left, &right),
+                _ => moon::or(&target, &left, &right),
+            };
+            self.line(&line, output);
+            self.write(destination, target, output);
+            return;
+        }
+
+        // A constant left operand is moved to the right when the operator allows it
+        let (operator, left, right) = match (left, operator.mirrored()) {
+            (Operand::Constant(value), Some(mirrored))
+                if is_immediate(*value) && !matches!(right, Operand::Constant(_)) =>
+            {
+                (mirrored, right, left)
+            }
+            _ => (operator, left, right),
+        };
+
+        let left = self.read(left, Register::general(2), output);
+        let line = match right {
+            Operand::Constant(value) if is_immediate(*value) => {
+                let value = value.to_string();
+                match operator {
+                    Operator::Add => moon::add_i(&target, &left, &value),
+                    Operator::Subtract => moon::sub_i(&target, &left, &value),
+                    Operator::Multiply => moon::mul_i(&target, &left, &value),
+                    Operator::Divide => moon::div_i(&target, &left, &value),
+                    Operator::Equal => moon::cmp_eq_i(&target, &left, &value),
+                    Operator::NotEqual => moon::cmp_neq_i(&target, &left, &value),
+                    Operator::Less => moon::cmp_lt_i(&target, &left, &value),
+                    Operator::LessOrEqual => moon::cmp_lte_i(&target, &left, &value),
+                    Operator::Greater => moon::cmp_gt_i(&target, &left, &value),
+                    Operator::GreaterOrEqual => moon::cmp_gte_i(&target, &left, &value),
+                    Operator::And | Operator::Or => panic!(),
+                }
+            }
+            _ => {
+                let right = self.read(right, Register::general(3), output);
+                match operator {
+                    Operator::Add => moon::add(&target, &left, &right),
+                    Operator::Subtract => moon::sub(&target, &left, &right),
+                    Operator::Multiply => moon::mul(&target, &left, &right),
+                    Operator::Divide => moon::div(&target, &left, &right),
+                    Operator::Equal => moon::cmp_eq(&target, &left, &right),
+                    Operator::NotEqual => moon::cmp_neq(&target, &left, &right),
+                    Operator::Less => moon::cmp_lt(&target, &left, &right),
+                    Operator::LessOrEqual => moon::cmp_lte(&target, &left, &right),
+                    Operator::Greater => moon::cmp_gt(&target, &left, &right),
+                    Operator::GreaterOrEqual => moon::cmp_gte(&target, &left, &right),
+                    Operator::And | Operator::Or => panic!(),
+                }
+            }
+        };
+        self.line(&line, output);
+        self.write(destination, target, output);
+    }
+
+    /// End a block, the jumps to the block laid out next are left out
+    fn terminator(
+        &mut self,
+        terminator: &Terminator,
+        next: Option<usize>,
+        output: &mut OutputConfig,
+    ) {
+        match terminator {
+            Terminator::Jump(target) => {
+                if Some(*target) != next {
+                    let label = self.function.block_label(*target);
+                    self.line(&moon::jmp(&label), output);
+                }
+            }
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let condition = self.read(condition, Register::general(1), output);
+                let then_label = self.function.block_label(*then);
+                let otherwise_label = self.function.block_label(*otherwise);
+                if Some(*then) == next {
+                    self.line(&moon::jmp_zero(&condition, &otherwise_label), output);
+                } else {
+                    self.line(&moon::jmp_nzero(&condition, &then_label), output);
+                    if Some(*otherwise) != next {
+                        self.line(&moon::jmp(&otherwise_label), output);
+                    }
+                }
+            }
+            Terminator::Return(_) if self.function.is_main => {
+                self.line(&moon::halt(), output);
+            }
+            Terminator::Return(value) => {
+                if let Some(value) = value {
+                    let result = self.read(value, R13, output);
+                    if result != R13 {
+                        self.line(&moon::add(&R13, &result, &R0), output);
+                    }
+                }
+                self.line(&moon::load_w(&R15, &self.function.link, &R0), output);
+                self.line(&moon::jmp_reg(&R15), output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::analyze_source;
+    use crate::visitor;
+    use std::convert::TryFrom;
+
+    /// The code generated for a source file, with the fields of each line separated by one space
+    fn emit_source(name: &str, contents: &str) -> Vec<String> {
+        let (ast, results, mut output) = analyze_source(name, contents);
+        let program = ast::typed::Program::try_from(&ast).unwrap();
+        let program = visitor::build(&program, &results.symbol_table).unwrap();
+        emit(&program, &results.symbol_table, &mut output).unwrap();
+        output
+            .code_exec
+            .iter()
+            .chain(&output.code_data)
+            .flat_map(|code| code.lines())
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn test_emit_empty_main() {
+        // main halts without saving its link
+        assert_eq!(
+            emit_source("empty_main.src", "main\n{\n}\n"),
+            vec![
+                "% Begin main ===================================================================================================",
+                "entry",
+                "addi r14, r0, topaddr",
+                "hlt",
+                "% ==================================================================================================================",
+                "% END OF PROGRAM/BEGINNING OF DATA",
+                "% ==================================================================================================================",
+                "% Buffer space used for console output",
+                "buf res 20",
+                "align",
+                "% Reserved memory for function main",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_emit_call() {
+        let code = emit_source(
+            "call.src",
+            r#"
+func twice(integer n) : integer
+{
+  return (n * 2);
+}
+
+main
+{
+  write(twice(3));
+}
+"#,
+        );
+        assert_eq!(
+            code,
+            vec![
+                "% Begin function twice__entry =================================================================================",
+                "twice__entry sw twice__link(r0), r15",
+                "% twice__n := parameter 0",
+                "lw r1, -8(r14)",
+                "sw twice__n(r0), r1",
+                "% t0 := twice__n * 2",
+                "lw r2, twice__n(r0)",
+                "muli r4, r2, 2",
+                "% return t0",
+                "add r13, r4, r0",
+                "lw r15, twice__link(r0)",
+                "jr r15",
+                "% Begin main ===================================================================================================",
+                "entry",
+                "addi r14, r0, topaddr",
+                "% argument 0 := 3",
+                "addi r1, r0, 3",
+                "sw -8(r14), r1",
+                "% t0 := call twice__entry",
+                "jl r15, twice__entry",
+                "add r4, r13, r0",
+                "% write t0",
+                "sw -8(r14), r4",
+                "addi r1, r0, buf",
+                "sw -12(r14), r1",
+                "jl r15, intstr",
+                "sw -8(r14), r13",
+                "jl r15, putstr",
+                "hlt",
+                "% ==================================================================================================================",
+                "% END OF PROGRAM/BEGINNING OF DATA",
+                "% ==================================================================================================================",
+                "% Buffer space used for console output",
+                "buf res 20",
+                "align",
+                "% Reserved memory for function main",
+                "% Reserved memory for function twice",
+                "twice__link res 4",
+                "align",
+                "twice__n res 4",
+                "align",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_emit_read() {
+        let code = emit_source(
+            "emit_read.src",
+            r#"
+main
+{
+  var
+  {
+    integer i;
+  }
+  read(i);
+}
+"#,
+        );
+        assert_eq!(
+            code,
+            vec![
+                "% Begin main ===================================================================================================",
+                "entry",
+                "addi r14, r0, topaddr",
+                "% t0 := read",
+                "addi r1, r0, buf",
+                "sw -8(r14), r1",
+                "jl r15, getstr",
+                "jl r15, strint",
+                "add r4, r13, r0",
+                "% main__i := t0",
+                "sw main__i(r0), r4",
+                "hlt",
+                "% ==================================================================================================================",
+                "% END OF PROGRAM/BEGINNING OF DATA",
+                "% ==================================================================================================================",
+                "% Buffer space used for console output",
+                "buf res 20",
+                "align",
+                "% Reserved memory for function main",
+                "main__i res 4",
+                "align",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_emit_float_operation() {
+        let (ast, results, mut output) = analyze_source(
+            "emit_float.src",
+            r#"
+main
+{
+  write(1.5 * 2.0);
+}
+"#,
+        );
+        let program = ast::typed::Program::try_from(&ast).unwrap();
+        let program = visitor::build(&program, &results.symbol_table).unwrap();
+        assert_eq!(
+            emit(&program, &results.symbol_table, &mut output)
+                .unwrap_err()
+                .to_string(),
+            "No MOON code can be emitted for \"t0 := float 1069547520 * 1073741824\" in function main()"
+        );
+        assert!(output.code_exec.is_empty() && output.code_data.is_empty());
+    }
+}
@@ -0,0 +1,453 @@
+//! Three-address code organized in basic blocks, between the typed AST and the MOON code
+//!
+//! Each instruction computes at most one value from at most two operands. The values computed for
+//! an expression are held in temporaries numbered per function, which the MOON emitter keeps in
+//! registers or in memory. The instructions of a block run one after the other and its terminator
+//! says which block runs next.
+
+use ast::typed::BinaryOp;
+use std::fmt;
+use std::fmt::Write;
+
+/// A value read by an instruction, or the place it writes to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Operand {
+    /// The value computed by an instruction of the function
+    Temporary(usize),
+    /// An integer, or the bits of a float, known when compiling
+    Constant(i64),
+    /// The word at a label of the data section, such as a local or a parameter
+    Label(String),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Temporary(index) => write!(f, "t{}", index),
+            Operand::Constant(value) => write!(f, "{}", value),
+            Operand::Label(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+/// The type of the operands of an operation, the floats are held as the bits of their value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperandType {
+    Integer,
+    Float,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl From<BinaryOp> for Operator {
+    fn from(operator: BinaryOp) -> Self {
+        match operator {
+            BinaryOp::Add => Operator::Add,
+            BinaryOp::Sub => Operator::Subtract,
+            BinaryOp::Mul => Operator::Multiply,
+            BinaryOp::Div => Operator::Divide,
+            BinaryOp::And => Operator::And,
+            BinaryOp::Or => Operator::Or,
+            BinaryOp::Eq => Operator::Equal,
+            BinaryOp::Neq => Operator::NotEqual,
+            BinaryOp::Lt => Operator::Less,
+            BinaryOp::Leq => Operator::LessOrEqual,
+            BinaryOp::Gt => Operator::Greater,
+            BinaryOp::Geq => Operator::GreaterOrEqual,
+        }
+    }
+}
+
+impl Operator {
+    /// The operator giving the same result with the operands swapped
+    pub fn mirrored(self) -> Option<Operator> {
+        match self {
+            Operator::Subtract | Operator::Divide => None,
+            Operator::Less => Some(Operator::Greater),
+            Operator::LessOrEqual => Some(Operator::GreaterOrEqual),
+            Operator::Greater => Some(Operator::Less),
+            Operator::GreaterOrEqual => Some(Operator::LessOrEqual),
+            operator => Some(operator),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::And => "and",
+            Operator::Or => "or",
+            Operator::Equal => "==",
+            Operator::NotEqual => "<>",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// The destinations are temporaries or labels
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Copy {
+        destination: Operand,
+        source: Operand,
+    },
+    Binary {
+        destination: Operand,
+        operator: Operator,
+        operand_type: OperandType,
+        left: Operand,
+        right: Operand,
+    },
+    /// Take the address of a label
+    Address { destination: Operand, label: String },
+    /// Read the word at an offset from an address
+    Load {
+        destination: Operand,
+        address: Operand,
+        offset: usize,
+    },
+    /// Write the word at an offset from an address
+    Store {
+        address: Operand,
+        offset: usize,
+        source: Operand,
+    },
+    /// Take an argument left by the caller, the object of a member function comes first
+    Parameter { destination: Operand, index: usize },
+    /// Leave an argument for the next call
+    Argument { index: usize, source: Operand },
+    /// Call the function at its entry label
+    Call {
+        destination: Option<Operand>,
+        function: String,
+    },
+    /// Call the function in a slot of the virtual table of an object
+    VirtualCall {
+        destination: Option<Operand>,
+        object: Operand,
+        slot: usize,
+    },
+    /// Write an integer to the console
+    Write { source: Operand },
+    /// Read an integer from a line of the console
+    Read { destination: Operand },
+}
+
+impl Instruction {
+    /// The operands read by the instruction
+    pub fn uses(&self) -> Vec<&Operand> {
+        match self {
+            Instruction::Copy { source, .. } => vec![source],
+            Instruction::Binary { left, right, .. } => vec![left, right],
+            Instruction::Address { .. }
+            | Instruction::Parameter { .. }
+            | Instruction::Call { .. }
+            | Instruction::Read { .. } => Vec::new(),
+            Instruction::Load { address, .. } => vec![address],
+            Instruction::Store {
+                address, source, ..
+            } => vec![address, source],
+            Instruction::Argument { source, .. } | Instruction::Write { source } => vec![source],
+            Instruction::VirtualCall { object, .. } => vec![object],
+        }
+    }
+
+    /// The operand written by the instruction, if any
+    pub fn destination(&self) -> Option<&Operand> {
+        match self {
+            Instruction::Copy { destination, .. }
+            | Instruction::Binary { destination, .. }
+            | Instruction::Address { destination, .. }
+            | Instruction::Load { destination, .. }
+            | Instruction::Parameter { destination, .. }
+            | Instruction::Read { destination } => Some(destination),
+            Instruction::Call { destination, .. }
+            | Instruction::VirtualCall { destination, .. } => destination.as_ref(),
+            Instruction::Store { .. }
+            | Instruction::Argument { .. }
+            | Instruction::Write { .. } => None,
+        }
+    }
+
+    /// Whether the instruction runs code that doesn't preserve the registers
+    pub fn is_call(&self) -> bool {
+        matches!(
+            self,
+            Instruction::Call { .. }
+                | Instruction::VirtualCall { .. }
+                | Instruction::Write { .. }
+                | Instruction::Read { .. }
+        )
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Copy {
+                destination,
+                source,
+            } => write!(f, "{} := {}", destination, source),
+            Instruction::Binary {
+                destination,
+                operator,
+                operand_type: OperandType::Integer,
+                left,
+                right,
+            } => write!(f, "{} := {} {} {}", destination, left, operator, right),
+            Instruction::Binary {
+                destination,
+                operator,
+                operand_type: OperandType::Float,
+                left,
+                right,
+            } => write!(
+                f,
+                "{} := float {} {} {}",
+                destination, left, operator, right
+            ),
+            Instruction::Address { destination, label } => {
+                write!(f, "{} := &{}", destination, label)
+            }
+            Instruction::Load {
+                destination,
+                address,
+                offset,
+            } => write!(f, "{} := [{} + {}]", destination, address, offset),
+            Instruction::Store {
+                address,
+                offset,
+                source,
+            } => write!(f, "[{} + {}] := {}", address, offset, source),
+            Instruction::Parameter { destination, index } => {
+                write!(f, "{} := parameter {}", destination, index)
+            }
+            Instruction::Argument { index, source } => {
+                write!(f, "argument {} := {}", index, source)
+            }
+            Instruction::Call {
+                destination,
+                function,
+            } => {
+                if let Some(destination) = destination {
+                    write!(f, "{} := ", destination)?;
+                }
+                write!(f, "call {}", function)
+            }
+            Instruction::VirtualCall {
+                destination,
+                object,
+                slot,
+            } => {
+                if let Some(destination) = destination {
+                    write!(f, "{} := ", destination)?;
+                }
+                write!(f, "call slot {} of {}", slot, object)
+            }
+            Instruction::Write { source } => write!(f, "write {}", source),
+            Instruction::Read { destination } => write!(f, "{} := read", destination),
+        }
+    }
+}
+
+/// How the control leaves a block
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Jump(usize),
+    /// Go to the first block if the condition isn't zero, to the second otherwise
+    Branch {
+        condition: Operand,
+        then: usize,
+        otherwise: usize,
+    },
+    /// Return from the function, ending the program for main
+    Return(Option<Operand>),
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<usize> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![*then, *otherwise],
+            Terminator::Return(_) => Vec::new(),
+        }
+    }
+
+    /// The operands read by the terminator
+    pub fn uses(&self) -> Vec<&Operand> {
+        match self {
+            Terminator::Branch { condition, .. } => vec![condition],
+            Terminator::Return(Some(value)) => vec![value],
+            Terminator::Jump(_) | Terminator::Return(None) => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Terminator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Terminator::Jump(target) => write!(f, "goto b{}", target),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => write!(f, "if {} goto b{} else b{}", condition, then, otherwise),
+            Terminator::Return(Some(value)) => write!(f, "return {}", value),
+            Terminator::Return(None) => write!(f, "return"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+    pub instructions: Vec<Instruction>,
+    pub terminator: Terminator,
+}
+
+impl BasicBlock {
+    pub fn new() -> Self {
+        BasicBlock {
+            instructions: Vec::new(),
+            terminator: Terminator::Return(None),
+        }
+    }
+}
+
+/// The code of a function, its first block is entered when it is called
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    /// The name of the function qualified by its class, with its parameter types
+    pub name: String,
+    /// The label of its code
+    pub entry: String,
+    /// The label of the storage for its return address
+    pub link: String,
+    /// The prefix of the labels of the function, to name those of its blocks and temporaries
+    pub prefix: String,
+    /// The main function starts the program, and ends it instead of returning
+    pub is_main: bool,
+    pub blocks: Vec<BasicBlock>,
+    /// The number of temporaries the instructions use
+    pub temporaries: usize,
+}
+
+impl Function {
+    pub fn block_label(&self, index: usize) -> String {
+        format!("{}_b{}", self.prefix, index)
+    }
+
+    pub fn temporary_label(&self, index: usize) -> String {
+        format!("{}_t{}", self.prefix, index)
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "function {} at {}", self.name, self.entry)?;
+        for (index, block) in self.blocks.iter().enumerate() {
+            writeln!(f, "  b{}:", index)?;
+            for instruction in &block.instructions {
+                writeln!(f, "    {}", instruction)?;
+            }
+            writeln!(f, "    {}", block.terminator)?;
+        }
+        Ok(())
+    }
+}
+
+/// The functions of the program, main last
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Program {
+    pub functions: Vec<Function>,
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, function) in self.functions.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", function)?;
+        }
+        Ok(())
+    }
+}
+
+impl Program {
+    /// Render the blocks of each function as a graph, clustered by function
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph ir {\n");
+        result.push_str("  node [shape=box fontname=monospace]\n");
+        for (function_index, function) in self.functions.iter().enumerate() {
+            let node = |block: usize| format!("f{}b{}", function_index, block);
+            writeln!(result, "  subgraph cluster_{} {{", function_index).unwrap();
+            writeln!(result, "    label=\"{}\"", escape(&function.name)).unwrap();
+            for (index, block) in function.blocks.iter().enumerate() {
+                let mut label = format!("b{}:\\l", index);
+                for instruction in &block.instructions {
+                    write!(label, "{}\\l", escape(&instruction.to_string())).unwrap();
+                }
+                write!(label, "{}\\l", escape(&block.terminator.to_string())).unwrap();
+                writeln!(result, "    {} [label=\"{}\"]", node(index), label).unwrap();
+            }
+            for (index, block) in function.blocks.iter().enumerate() {
+                match &block.terminator {
+                    Terminator::Jump(target) => {
+                        writeln!(result, "    {} -> {}", node(index), node(*target)).unwrap()
+                    }
+                    Terminator::Branch {
+                        then, otherwise, ..
+                    } => {
+                        writeln!(
+                            result,
+                            "    {} -> {} [label=\"true\"]",
+                            node(index),
+                            node(*then)
+                        )
+                        .unwrap();
+                        writeln!(
+                            result,
+                            "    {} -> {} [label=\"false\"]",
+                            node(index),
+                            node(*otherwise)
+                        )
+                        .unwrap();
+                    }
+                    Terminator::Return(_) => (),
+                }
+            }
+            result.push_str("  }\n");
+        }
+        result.push_str("}\n");
+        result
+    }
+}
+
+/// Escape a line for a double quoted DOT string
+fn escape(line: &str) -> String {
+    line.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -3,9 +3,120 @@ mod register;
 mod visitor;
 mod macros;
 mod preamble;
+mod ir;
+mod emitter;
+mod optimizer;
 
+use ast::typed::{MalformedNode, Position};
 use ast::Node;
-use output_manager::OutputConfig;
+use output_manager::{OptimizationLevel, OutputConfig};
 use semantic_analyzer::SemanticAnalysisResults;
-pub use visitor::process;
+use std::convert::TryFrom;
+use std::fmt;
 
+/// Why the code of a program can't be generated
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodeGenError {
+    /// The AST doesn't have the shape the grammar gives it
+    Malformed(MalformedNode),
+    /// Something the code needs, like the function called, wasn't resolved by the type check
+    Unresolved(String, Position),
+    /// A construct the code generation doesn't handle yet
+    Unsupported(String, Position),
+    /// An instruction of the three-address code of a function without MOON code yet
+    Unemitted(String, String),
+}
+
+impl fmt::Display for CodeGenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeGenError::Malformed(err) => write!(f, "Malformed AST: {}", err),
+            CodeGenError::Unresolved(expected, position) => write!(
+                f,
+                "Expected {} resolved by the type check at line {}, col {}",
+                expected, position.line, position.column
+            ),
+            CodeGenError::Unsupported(construct, position) => write!(
+                f,
+                "No code can be generated for {} at line {}, col {}",
+                construct, position.line, position.column
+            ),
+            CodeGenError::Unemitted(instruction, function) => write!(
+                f,
+                "No MOON code can be emitted for \"{}\" in function {}",
+                instruction, function
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CodeGenError {}
+
+impl From<MalformedNode> for CodeGenError {
+    fn from(err: MalformedNode) -> Self {
+        CodeGenError::Malformed(err)
+    }
+}
+
+/// Build the three-address code of the program, write it next to the MOON code generated from it
+pub fn process(
+    node: &Node,
+    current_results: &mut SemanticAnalysisResults,
+    output: &mut OutputConfig,
+) -> Result<(), CodeGenError> {
+    // Converted again now that the type check annotated the nodes
+    let program = ast::typed::Program::try_from(node)?;
+    let mut program = visitor::build(&program, &current_results.symbol_table)?;
+    if output.optimization_level == OptimizationLevel::Constants {
+        optimizer::fold_constants(&mut program);
+    }
+    output_manager::warn_write(&mut output.ir_file, &output.ir_path, &program.to_string());
+    output_manager::warn_write(&mut output.ir_dot_file, &output.ir_dot_path, &program.to_dot());
+
+    emitter::emit(&program, &current_results.symbol_table, output)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use lexical_analyzer::{lexer::Lexer, lexical_rule::LexicalRule};
+    use std::fs::File;
+    use syntactic_analyzer::{parse, Grammar, ParseTable};
+
+    /// Parse and analyze a source file written to the temporary folder with the LL(1) grammar,
+    /// the program must have no errors
+    pub(crate) fn analyze_source(name: &str, contents: &str) -> (Node, SemanticAnalysisResults, OutputConfig) {
+        let (ast, results, output_config) = analyze_source_with_errors(name, contents);
+        assert!(!output_config.has_errors());
+        (ast, results, output_config)
+    }
+
+    /// Like `analyze_source`, the errors are left in the output
+    pub(crate) fn analyze_source_with_errors(name: &str, contents: &str) -> (Node, SemanticAnalysisResults, OutputConfig) {
+        let rules = LexicalRule::from_file("../resources/lex_tokens.txt").unwrap();
+        let keywords = std::fs::read_to_string("../resources/keywords.txt")
+            .unwrap()
+            .lines()
+            .map(|keyword| keyword.to_string())
+            .collect();
+        let lexer = Lexer::new(rules, keywords);
+        let grammar = Grammar::from_reader(File::open("../resources/LL1.paquet.grm").unwrap()).unwrap();
+        let parse_table = ParseTable::from_grammar(&grammar);
+
+        let output_folder = std::env::temp_dir().join("paquet_code_gen_test");
+        std::fs::create_dir_all(&output_folder).unwrap();
+        let source = output_folder.join(name);
+        std::fs::write(&source, contents).unwrap();
+        let source = source.to_str().unwrap();
+        let mut output_config = OutputConfig::new(source, output_folder.to_str().unwrap());
+        let mut ast = parse(
+            &mut lexer.lex(source, &output_config.lex_error_path),
+            &grammar,
+            &parse_table,
+            &mut output_config,
+        )
+        .unwrap();
+        let results = semantic_analyzer::analyze(&mut ast, &mut output_config).unwrap();
+        (ast, results, output_config)
+    }
+}
@@ -40,8 +40,8 @@ const CMP_EQ_I: &str = "ceqi";
 const CMP_NEQ_I: &str = "cnei";
 const CMP_LT_I: &str = "clti";
 const CMP_LTE_I: &str = "clei";
-const CMP_GT_I: &str = "cgei";
-const CMP_GTE_I: &str = "cge";
+const CMP_GT_I: &str = "cgti";
+const CMP_GTE_I: &str = "cgei";
 
 const LSHIFT: &str = "sl";
 const RSHIFT: &str = "sr";
@@ -0,0 +1,457 @@
+//! Fold and propagate the constants of the three-address code
+//!
+//! The values of the temporaries and of the locals known when compiling are followed through the
+//! blocks of each function, then the operations on them are computed as the machine would, on 32
+//! bit words. A division by zero is left for the program to fail at. The operations on floats are
+//! only given their known operands, their bits aren't integers to compute with.
+
+use crate::ir::{self, Instruction, Operand, OperandType, Operator, Program, Terminator};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+/// The operands holding a known value where the control reaches
+type Constants = HashMap<Operand, i64>;
+
+pub fn fold_constants(program: &mut Program) {
+    for function in program.functions.iter_mut() {
+        fold_function(function);
+    }
+}
+
+fn fold_function(function: &mut ir::Function) {
+    // The locals whose address is taken may be written through it
+    let addressed: HashSet<String> = function
+        .blocks
+        .iter()
+        .flat_map(|block| &block.instructions)
+        .filter_map(|instruction| match instruction {
+            Instruction::Address { label, .. } => Some(label.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let entries = block_entries(function, &addressed);
+    for (block, entry) in function.blocks.iter_mut().zip(entries) {
+        // The blocks that can't be reached are left as they are
+        let mut constants = match entry {
+            Some(constants) => constants,
+            None => continue,
+        };
+        for instruction in block.instructions.iter_mut() {
+            *instruction = fold_instruction(instruction, &constants);
+            transfer(instruction, &mut constants, &addressed);
+        }
+        block.terminator = match &block.terminator {
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => match resolve(condition, &constants) {
+                Operand::Constant(0) => Terminator::Jump(*otherwise),
+                Operand::Constant(_) => Terminator::Jump(*then),
+                condition => Terminator::Branch {
+                    condition,
+                    then: *then,
+                    otherwise: *otherwise,
+                },
+            },
+            Terminator::Return(Some(value)) => Terminator::Return(Some(resolve(value, &constants))),
+            terminator => terminator.clone(),
+        };
+    }
+
+    remove_unused_copies(function);
+}
+
+/// The constants known when entering each block, None for the blocks that can't be reached
+fn block_entries(function: &ir::Function, addressed: &HashSet<String>) -> Vec<Option<Constants>> {
+    let mut entries: Vec<Option<Constants>> = vec![None; function.blocks.len()];
+    entries[0] = Some(Constants::new());
+
+    // The values known on every path reaching a block only decrease, until none changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (index, block) in function.blocks.iter().enumerate() {
+            let mut constants = match &entries[index] {
+                Some(constants) => constants.clone(),
+                None => continue,
+            };
+            for instruction in &block.instructions {
+                let folded = fold_instruction(instruction, &constants);
+                transfer(&folded, &mut constants, addressed);
+            }
+            for successor in block.terminator.successors() {
+                let entry = match &entries[successor] {
+                    Some(entry) => meet(entry, &constants),
+                    None => constants.clone(),
+                };
+                if entries[successor].as_ref() != Some(&entry) {
+                    entries[successor] = Some(entry);
+                    changed = true;
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// The values known on both paths joining
+fn meet(left: &Constants, right: &Constants) -> Constants {
+    left.iter()
+        .filter(|(operand, value)| right.get(operand) == Some(value))
+        .map(|(operand, value)| (operand.clone(), *value))
+        .collect()
+}
+
+/// Update the values known after an instruction
+fn transfer(instruction: &Instruction, constants: &mut Constants, addressed: &HashSet<String>) {
+    if let Some(destination) = instruction.destination() {
+        match (instruction, destination) {
+            (_, Operand::Label(label)) if addressed.contains(label) => (),
+            (
+                Instruction::Copy {
+                    source: Operand::Constant(value),
+                    ..
+                },
+                _,
+            ) => {
+                constants.insert(destination.clone(), *value);
+                return;
+            }
+            _ => (),
+        }
+        constants.remove(destination);
+    }
+}
+
+/// The constant held by an operand if it's known
+fn resolve(operand: &Operand, constants: &Constants) -> Operand {
+    match constants.get(operand) {
+        Some(value) => Operand::Constant(*value),
+        None => operand.clone(),
+    }
+}
+
+/// The instruction with the known values in place of its operands, computed if they all are
+fn fold_instruction(instruction: &Instruction, constants: &Constants) -> Instruction {
+    match instruction {
+        Instruction::Copy {
+            destination,
+            source,
+        } => Instruction::Copy {
+            destination: destination.clone(),
+            source: resolve(source, constants),
+        },
+        Instruction::Binary {
+            destination,
+            operator,
+            operand_type,
+            left,
+            right,
+        } => {
+            let left = resolve(left, constants);
+            let right = resolve(right, constants);
+            if let (OperandType::Integer, Operand::Constant(left), Operand::Constant(right)) =
+                (operand_type, &left, &right)
+            {
+                if let Some(value) = evaluate(*operator, *left, *right) {
+                    return Instruction::Copy {
+                        destination: destination.clone(),
+                        source: Operand::Constant(value),
+                    };
+                }
+            }
+            Instruction::Binary {
+                destination: destination.clone(),
+                operator: *operator,
+                operand_type: *operand_type,
+                left,
+                right,
+            }
+        }
+        Instruction::Load {
+            destination,
+            address,
+            offset,
+        } => Instruction::Load {
+            destination: destination.clone(),
+            address: resolve(address, constants),
+            offset: *offset,
+        },
+        Instruction::Store {
+            address,
+            offset,
+            source,
+        } => Instruction::Store {
+            address: resolve(address, constants),
+            offset: *offset,
+            source: resolve(source, constants),
+        },
+        Instruction::Argument { index, source } => Instruction::Argument {
+            index: *index,
+            source: resolve(source, constants),
+        },
+        Instruction::Write { source } => Instruction::Write {
+            source: resolve(source, constants),
+        },
+        instruction => instruction.clone(),
+    }
+}
+
+/// The result of an operation on two words, None when the machine would fail computing it
+fn evaluate(operator: Operator, left: i64, right: i64) -> Option<i64> {
+    let left = i32::try_from(left).ok()?;
+    let right = i32::try_from(right).ok()?;
+    let value = match operator {
+        Operator::Add => left.wrapping_add(right),
+        Operator::Subtract => left.wrapping_sub(right),
+        Operator::Multiply => left.wrapping_mul(right),
+        Operator::Divide => left.checked_div(right)?,
+        Operator::And => (left != 0 && right != 0) as i32,
+        Operator::Or => (left != 0 || right != 0) as i32,
+        Operator::Equal => (left == right) as i32,
+        Operator::NotEqual => (left != right) as i32,
+        Operator::Less => (left < right) as i32,
+        Operator::LessOrEqual => (left <= right) as i32,
+        Operator::Greater => (left > right) as i32,
+        Operator::GreaterOrEqual => (left >= right) as i32,
+    };
+    Some(value as i64)
+}
+
+/// Remove the copies of the constants to the temporaries no longer read
+fn remove_unused_copies(function: &mut ir::Function) {
+    let mut used = HashSet::new();
+    for block in &function.blocks {
+        let operands = block
+            .instructions
+            .iter()
+            .flat_map(|instruction| instruction.uses())
+            .chain(block.terminator.uses());
+        for operand in operands {
+            if let Operand::Temporary(temporary) = operand {
+                used.insert(*temporary);
+            }
+        }
+    }
+    for block in function.blocks.iter_mut() {
+        block.instructions.retain(|instruction| match instruction {
+            Instruction::Copy {
+                destination: Operand::Temporary(temporary),
+                ..
+            } => used.contains(temporary),
+            _ => true,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::BasicBlock;
+
+    fn label(name: &str) -> Operand {
+        Operand::Label(name.to_string())
+    }
+
+    fn binary(
+        destination: usize,
+        operator: Operator,
+        left: Operand,
+        right: Operand,
+    ) -> Instruction {
+        Instruction::Binary {
+            destination: Operand::Temporary(destination),
+            operator,
+            operand_type: OperandType::Integer,
+            left,
+            right,
+        }
+    }
+
+    fn function(blocks: Vec<BasicBlock>, temporaries: usize) -> Program {
+        Program {
+            functions: vec![ir::Function {
+                name: "main()".to_string(),
+                entry: "main__entry".to_string(),
+                link: "main__link".to_string(),
+                prefix: "main__".to_string(),
+                is_main: true,
+                blocks,
+                temporaries,
+            }],
+        }
+    }
+
+    fn instruction_count(program: &Program) -> usize {
+        program.functions[0]
+            .blocks
+            .iter()
+            .map(|block| block.instructions.len())
+            .sum()
+    }
+
+    #[test]
+    fn test_fold_loop_bound() {
+        // n = 10; k = 2 * 3 + 4; i = 0; while (i < n - 1) { i = i + 1; }; write(k);
+        let mut program = function(
+            vec![
+                BasicBlock {
+                    instructions: vec![
+                        Instruction::Copy {
+                            destination: label("n"),
+                            source: Operand::Constant(10),
+                        },
+                        binary(
+                            0,
+                            Operator::Multiply,
+                            Operand::Constant(2),
+                            Operand::Constant(3),
+                        ),
+                        binary(
+                            1,
+                            Operator::Add,
+                            Operand::Temporary(0),
+                            Operand::Constant(4),
+                        ),
+                        Instruction::Copy {
+                            destination: label("k"),
+                            source: Operand::Temporary(1),
+                        },
+                        Instruction::Copy {
+                            destination: label("i"),
+                            source: Operand::Constant(0),
+                        },
+                    ],
+                    terminator: Terminator::Jump(1),
+                },
+                BasicBlock {
+                    instructions: vec![
+                        binary(2, Operator::Subtract, label("n"), Operand::Constant(1)),
+                        binary(3, Operator::Less, label("i"), Operand::Temporary(2)),
+                    ],
+                    terminator: Terminator::Branch {
+                        condition: Operand::Temporary(3),
+                        then: 2,
+                        otherwise: 3,
+                    },
+                },
+                BasicBlock {
+                    instructions: vec![
+                        binary(4, Operator::Add, label("i"), Operand::Constant(1)),
+                        Instruction::Copy {
+                            destination: label("i"),
+                            source: Operand::Temporary(4),
+                        },
+                    ],
+                    terminator: Terminator::Jump(1),
+                },
+                BasicBlock {
+                    instructions: vec![Instruction::Write { source: label("k") }],
+                    terminator: Terminator::Return(None),
+                },
+            ],
+            5,
+        );
+        assert_eq!(instruction_count(&program), 10);
+
+        fold_constants(&mut program);
+        let blocks = &program.functions[0].blocks;
+        assert_eq!(instruction_count(&program), 7);
+        // The bound is known but i changes in the loop
+        assert_eq!(
+            blocks[1].instructions,
+            vec![binary(3, Operator::Less, label("i"), Operand::Constant(9))]
+        );
+        assert_eq!(
+            blocks[3].instructions,
+            vec![Instruction::Write {
+                source: Operand::Constant(10)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_keep_division_by_zero() {
+        let mut program = function(
+            vec![BasicBlock {
+                instructions: vec![
+                    binary(
+                        0,
+                        Operator::Subtract,
+                        Operand::Constant(2),
+                        Operand::Constant(2),
+                    ),
+                    binary(
+                        1,
+                        Operator::Divide,
+                        Operand::Constant(1),
+                        Operand::Temporary(0),
+                    ),
+                    Instruction::Write {
+                        source: Operand::Temporary(1),
+                    },
+                ],
+                terminator: Terminator::Return(None),
+            }],
+            2,
+        );
+        assert_eq!(instruction_count(&program), 3);
+
+        fold_constants(&mut program);
+        assert_eq!(instruction_count(&program), 2);
+        assert_eq!(
+            program.functions[0].blocks[0].instructions,
+            vec![
+                binary(
+                    1,
+                    Operator::Divide,
+                    Operand::Constant(1),
+                    Operand::Constant(0)
+                ),
+                Instruction::Write {
+                    source: Operand::Temporary(1)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keep_float_operations() {
+        // x = 1.5; write(x * 2.0); with the floats as their bits
+        let bits = |value: f32| Operand::Constant(i32::from_ne_bytes(value.to_ne_bytes()) as i64);
+        let multiply = |left: Operand| Instruction::Binary {
+            destination: Operand::Temporary(0),
+            operator: Operator::Multiply,
+            operand_type: OperandType::Float,
+            left,
+            right: bits(2.0),
+        };
+        let mut program = function(
+            vec![BasicBlock {
+                instructions: vec![
+                    Instruction::Copy {
+                        destination: label("x"),
+                        source: bits(1.5),
+                    },
+                    multiply(label("x")),
+                    Instruction::Write {
+                        source: Operand::Temporary(0),
+                    },
+                ],
+                terminator: Terminator::Return(None),
+            }],
+            1,
+        );
+        assert_eq!(instruction_count(&program), 3);
+
+        fold_constants(&mut program);
+        assert_eq!(instruction_count(&program), 3);
+        // The value of x is propagated, but the product isn't computed on the bits
+        assert_eq!(
+            program.functions[0].blocks[0].instructions[1],
+            multiply(bits(1.5))
+        );
+    }
+}
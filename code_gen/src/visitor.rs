@@ -1,587 +1,1180 @@
-use ast::{Data, Node};
-use output_manager::OutputConfig;
-use semantic_analyzer::{SymbolTable, SymbolTableEntry, Literal, LiteralValue};
-use semantic_analyzer::SemanticAnalysisResults;
-use log::info;
-use crate::moon_instructions as moon;
-use crate::macros as mm; // for moon-macros
-use crate::register::{RegisterPool, Register, R0, R14, R13, R15};
-use crate::preamble;
-
-const OUTPUT_BUFFER_SIZE: usize = 20;
-
-pub struct State {
-    registers: RegisterPool
-}
+//! Build the three-address code of the program from the typed AST converted after the type check
+//!
+//! The member accessed and the function called by each access of a variable are those the type
+//! check resolved. The values of the expressions are computed in new temporaries. The elements of
+//! arrays are found from the address of the array, adding each index times the size of what it
+//! selects. The constructs without code yet are reported rather than left out.
+//!
+//! The control flow graphs of the semantic analysis only record the kind and position of the
+//! statements of each block, so the blocks are built again here along the same edges, with the
+//! instructions of their statements. The blocks that can't be reached are left to the emitter.
+
+use crate::ir::{
+    self, BasicBlock, Instruction, Operand, OperandType, Operator, Program, Terminator,
+};
+use crate::CodeGenError;
+use ast::typed::{self, Access, Expr, FuncBody, FuncDef, Position, Stmt, Variable};
+use semantic_analyzer::{DataSlot, Function, SymbolTable, SymbolTableEntry};
+
+type Result<T> = std::result::Result<T, CodeGenError>;
+
+pub fn build(program: &typed::Program, global_table: &SymbolTable) -> Result<Program> {
+    let mut result = Program::default();
+
+    for definition in &program.functions {
+        let function = find_function(definition, global_table)?;
+        result.functions.push(function_body(
+            &definition.body,
+            function,
+            global_table,
+            false,
+        )?);
+    }
 
-pub fn process(
-    node: &Node,
-    current_results: &mut SemanticAnalysisResults,
-    output: &mut OutputConfig,
-) {
-    // Add the contents of the lib file
-    output_manager::warn_write(&mut output.code_file, &mut output.code_path, preamble::PREAMBLE);
-    
-    // This is taken from the slides
-    // Add the buffer for output
-    output.add_data(&moon::cmt_line(" Buffer space used for console output"));
-    mm::res(OUTPUT_BUFFER_SIZE, "buf", output);
-
-    reserve_space(&current_results.symbol_table, output);
-    visit(
-        node,
-        &mut current_results.symbol_table.clone(),
-        &mut State {registers: RegisterPool::new()},
-        &mut current_results.symbol_table,
-        output,
-    )
+    if let Some(SymbolTableEntry::Function(main)) = global_table.get("main") {
+        result
+            .functions
+            .push(function_body(&program.main, main, global_table, true)?);
+    }
+    Ok(result)
 }
 
-fn reserve_space(
-    table: &SymbolTable,
-    output: &mut OutputConfig,
-) {
-    // Iterate over the functions and create labeled reserve statements for the various functions
-    for element in &table.values {
-        match element {
-            SymbolTableEntry::Function(function) => {
-                output.add_data(&moon::cmt_line(&format!("Reserved memory for function {}", function.id())));
-                reserve_space(function.symbol_table(), output);
-            }
-            SymbolTableEntry::Local(local) => {
-                mm::res(*local.bytes(), &table.mangle(local.id()), output)
-            },
-            SymbolTableEntry::Param(param) => {
-                mm::res(*param.bytes(), &table.mangle(param.id()), output)
+/// The function of a definition, member functions are defined as class::id
+fn find_function<'a>(definition: &FuncDef, global_table: &'a SymbolTable) -> Result<&'a Function> {
+    let table = match &definition.scope {
+        Some(scope) => match global_table.get(&scope.name) {
+            Some(SymbolTableEntry::Class(class)) => class.symbol_table(),
+            _ => return Err(unresolved("class of a member function", scope.position)),
+        },
+        None => global_table,
+    };
+    table
+        .get_all(&definition.name.name)
+        .into_iter()
+        .find_map(|entry| match entry {
+            SymbolTableEntry::Function(function)
+                if function
+                    .parameter_types()
+                    .iter()
+                    .map(|parameter| parameter.data_type())
+                    .eq(definition
+                        .params
+                        .iter()
+                        .map(|param| param.param_type.name())) =>
+            {
+                Some(function)
             }
-            SymbolTableEntry::Literal(literal) => {
-                let result = match literal.value() {
-                    LiteralValue::Integer(int) => int.to_string(),
-                    LiteralValue::Real(f) => i32::from_ne_bytes(f.to_ne_bytes()).to_string(),
-                    LiteralValue::StrLit(s) => panic!(), // don't know what to do here
-                };
-                let t = vec![result.as_str()];
-                output.add_data(&moon::labeled_line(&table.mangle(literal.id()), &moon::mem_store_w(t.as_slice())));
-                // mm::res(*literal.bytes(), &table.mangle(literal.id()), output)
-            },
-            SymbolTableEntry::Temporary(temporary) => {
-                mm::res(*temporary.bytes(), &table.mangle(temporary.id()), output)
-            },
-            _ => ()
-        }
-    }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            unresolved(
+                "function declaration matching the definition",
+                definition.name.position,
+            )
+        })
 }
 
-fn visit(
-    node: &Node,
-    current_context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    match node.name().as_str() {
-        "prog" => prog(node, current_context, state, global_table, output),
-        // "classDeclList" => class_list(node, current_context, state, global_table, output),
-        // "funcDefList" => function_list(node, current_context, state, global_table, output),
-
-        // "funcDecl" => func_decl(node, current_context, state, global_table, output),
-        // "funcBody" => func_body(node, current_context, state, global_table, output),
-        // "funcDef" => func_def(node, current_context, state, global_table, output),
-        // "fCall" => f_call(node, current_context, state, global_table, output),
-        "varList" => var_list(node, current_context, state, global_table, output),
-
-        "statBlock" => stat_block(node, current_context, state, global_table, output),
-        // "var" => var(node, current_context, state, global_table, output),
-        // "dataMember" => data_member(node, current_context, state, global_table, output),
-        // "intfactor" => intfactor(node, current_context, state, global_table, output),
-        // "floatfactor" => floatfactor(node, current_context, state, global_table, output),
-        // "stringfactor" => stringfactor(node, current_context, state, global_table, output),
-        // "type" => type_node(node, current_context, state, global_table, output),
-        "varDecl" => var_decl(node, current_context, state, global_table, output),
-        // "id" => id(node, current_context, state, global_table, output),
-        // "indexList" => mandatory_indexlist(node, current_context, state, global_table, output),
-        // "aParams" => a_params_children(node, current_context, state, global_table, output),
-
-        // "returnStat" => return_stat(node, current_context, state, global_table, output),
-        "ifStat" => if_stat(node, current_context, state, global_table, output),
-        "whileStat" => while_stat(node, current_context, state, global_table, output),
-        "writeStat" => write_stat(node, current_context, state, global_table, output),
-        // "readStat" => read_stat(node, current_context, state, global_table, output),
-
-        "assignOp" => assign_op(node, current_context, state, global_table, output),
-        "addOp" => add_op(node, current_context, state, global_table, output),
-        "mulOp" => mul_op(node, current_context, state, global_table, output),
-        "relOp" => rel_op(node, current_context, state, global_table, output),
-        _ => {}
+fn function_body(
+    body: &FuncBody,
+    function: &Function,
+    global_table: &SymbolTable,
+    is_main: bool,
+) -> Result<ir::Function> {
+    let table = function.symbol_table();
+    let mut builder = Builder {
+        table,
+        global_table,
+        blocks: vec![BasicBlock::new()],
+        current: 0,
+        temporaries: 0,
+        loops: Vec::new(),
+    };
+
+    // Copy the object and the arguments left by the caller
+    let mut index = 0;
+    if function.is_class_member() {
+        builder.push(Instruction::Parameter {
+            destination: Operand::Label(table.this_label()),
+            index,
+        });
+        index += 1;
+    }
+    for parameter in function.parameter_types() {
+        builder.push(Instruction::Parameter {
+            destination: Operand::Label(table.mangle(parameter.id())),
+            index,
+        });
+        index += 1;
     }
 
+    builder.init_objects();
+    builder.statements(&body.statements)?;
+
+    Ok(ir::Function {
+        name: function.qualified_name(),
+        entry: table.entry_label(),
+        link: table.link_label(),
+        prefix: table.mangle(""),
+        is_main,
+        blocks: builder.blocks,
+        temporaries: builder.temporaries,
+    })
 }
 
-fn prog(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    // Here we'll explicitly invoke the individual children
-    if let Data::Children(children) = node.data() {
-        // class_list(node, context, state, global_table, output);
-        // function_list(&children[1], context, output);
-        // entry_point(&children[2], context, state, global_table, output);
-        if let Some(SymbolTableEntry::Function(main)) = context.get_mut("main") {
-            entry_point(
-                &children[2],
-                main.symbol_table_mut(),
-                state,
-                global_table,
-                output,
-            );
-
-            *global_table = context.clone();
-        }
-    } else {
-        panic!();
-    }
+/// A loop being built, the blocks ending with a break jump to the block following it
+struct Loop {
+    condition: usize,
+    breaks: Vec<usize>,
+}
 
-    // if let Data::Children(children) = node.data() {
-    //     for child in children {
-    //         visit(child, current_results);
-    //     }
-    // }
+struct Builder<'a> {
+    /// The table of the function being built
+    table: &'a SymbolTable,
+    global_table: &'a SymbolTable,
+    blocks: Vec<BasicBlock>,
+    /// The block the instructions are added to
+    current: usize,
+    temporaries: usize,
+    /// The enclosing loops, innermost last
+    loops: Vec<Loop>,
 }
 
-fn class_list(
-    node: &Node,
-    context: &SymbolTable,
-    state: &mut State,
-    global_table: &SymbolTable,
-    output: &mut OutputConfig,
-) {}
+impl<'a> Builder<'a> {
+    fn new_temporary(&mut self) -> Operand {
+        self.temporaries += 1;
+        Operand::Temporary(self.temporaries - 1)
+    }
 
-fn function_list(
-    node: &Node,
-    context: &SymbolTable,
-    state: &mut State,
-    global_table: &SymbolTable,
-    output: &mut OutputConfig,
-) {}
-
-fn entry_point(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    output.add_exec(&moon::cmt_line("Begin main ==================================================================================================="));
-    output.add_exec(&moon::instr_line(&moon::entry()));
-    output.add_exec(&moon::instr_line(&moon::add_i(&R14, &R0, "topaddr")));
-
-
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
-        }
+    fn new_block(&mut self) -> usize {
+        self.blocks.push(BasicBlock::new());
+        self.blocks.len() - 1
     }
 
-    
-    output.add_exec(&moon::instr_line(&moon::halt()));
-    mm::cmt_exec("==================================================================================================================", output);
-    mm::cmt_exec("   END OF PROGRAM/BEGINNING OF DATA", output);
-    mm::cmt_exec("==================================================================================================================", output);
-}
+    fn push(&mut self, instruction: Instruction) {
+        self.blocks[self.current].instructions.push(instruction);
+    }
 
-fn var_list(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
-        }
+    fn end_block(&mut self, terminator: Terminator) {
+        self.blocks[self.current].terminator = terminator;
     }
-}
 
-fn var_decl(
-    node: &Node,
-    context: &SymbolTable,
-    state: &mut State,
-    global_table: &SymbolTable,
-    output: &mut OutputConfig,
-) {
-    // Create the reserved space? That should already be done
-}
+    /// End the current block, the statements following it until the next block can't be reached
+    fn leave(&mut self, terminator: Terminator) {
+        self.end_block(terminator);
+        self.current = self.new_block();
+    }
 
-fn stat_block(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
+    /// Store the address of the virtual table of their class in the header of the objects of the
+    /// function, and of the objects they contain
+    fn init_objects(&mut self) {
+        let table = self.table;
+        for element in &table.values {
+            if let SymbolTableEntry::Local(local) = element {
+                if let Some(SymbolTableEntry::Class(_)) = self.global_table.get(local.data_type()) {
+                    let address = self.new_temporary();
+                    self.push(Instruction::Address {
+                        destination: address.clone(),
+                        label: table.mangle(local.id()),
+                    });
+                    self.init_object(local.data_type(), local.dimension(), 0, &address);
+                }
+            }
         }
     }
-}
 
-fn assign_op(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
+    fn init_object(&mut self, class_id: &str, dimension: &[i64], offset: usize, address: &Operand) {
+        let global_table = self.global_table;
+        if let Some(SymbolTableEntry::Class(class)) = global_table.get(class_id) {
+            let count: i64 = dimension.iter().product();
+            for element in 0..count as usize {
+                let element_offset = offset + element * class.bytes();
+                let virtual_table = self.new_temporary();
+                self.push(Instruction::Address {
+                    destination: virtual_table.clone(),
+                    label: class.virtual_table_label(),
+                });
+                self.push(Instruction::Store {
+                    address: address.clone(),
+                    offset: element_offset,
+                    source: virtual_table,
+                });
+
+                for slot in class.data_slots() {
+                    self.init_object(
+                        slot.data_type(),
+                        slot.dimension(),
+                        element_offset + slot.offset(),
+                        address,
+                    );
+                }
+            }
         }
     }
 
-    let r = state.registers.reserve(1);
-    let local_register = state.registers.pop();
-    // let dst_offset_reg = state.registers.pop();
-    // let src_offset_reg = state.registers.pop();
-
-    // Zero the offsets
-    // mm::zero(&dst_offset_reg, output);
-    // mm::zero(&src_offset_reg, output);
+    fn statements(&mut self, statements: &[Stmt]) -> Result<()> {
+        for statement in statements {
+            self.statement(statement)?;
+        }
+        Ok(())
+    }
 
+    fn statement(&mut self, statement: &Stmt) -> Result<()> {
+        match statement {
+            // The destination is only evaluated once the value is
+            Stmt::Assign { target, value, .. } => {
+                let value = self.expression(value)?;
+                self.assign(target, value)?;
+            }
+            Stmt::Write { value, .. } => {
+                let source = self.expression(value)?;
+                self.push(Instruction::Write { source });
+            }
+            Stmt::Return { value, .. } => {
+                let value = self.expression(value)?;
+                self.leave(Terminator::Return(Some(value)));
+            }
+            Stmt::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => self.if_stat(condition, then_block, else_block)?,
+            Stmt::While {
+                condition, body, ..
+            } => self.while_stat(condition, body)?,
+            Stmt::Break(_) => {
+                if let Some(enclosing) = self.loops.last_mut() {
+                    enclosing.breaks.push(self.current);
+                    self.current = self.new_block();
+                }
+            }
+            Stmt::Continue(_) => {
+                if let Some(enclosing) = self.loops.last() {
+                    self.leave(Terminator::Jump(enclosing.condition));
+                }
+            }
+            // A call whose result is unused
+            Stmt::Call(variable) => {
+                self.variable(variable)?;
+            }
+            // The integer is read before the destination is evaluated, like an assigned value
+            Stmt::Read { target, position } => {
+                if target.data_type() != Some("integer") {
+                    return Err(unsupported(
+                        "reading a value other than an integer",
+                        *position,
+                    ));
+                }
+                let value = self.new_temporary();
+                self.push(Instruction::Read {
+                    destination: value.clone(),
+                });
+                self.assign(target, value)?;
+            }
+        }
+        Ok(())
+    }
 
-    // 
+    fn if_stat(
+        &mut self,
+        condition: &Expr,
+        then_block: &[Stmt],
+        else_block: &[Stmt],
+    ) -> Result<()> {
+        let condition = self.expression(condition)?;
+        let branch = self.current;
+
+        let then = self.new_block();
+        self.current = then;
+        self.statements(then_block)?;
+        let then_end = self.current;
+
+        let otherwise = self.new_block();
+        self.current = otherwise;
+        self.statements(else_block)?;
+        let otherwise_end = self.current;
+
+        let join = self.new_block();
+        self.blocks[branch].terminator = Terminator::Branch {
+            condition,
+            then,
+            otherwise,
+        };
+        self.blocks[then_end].terminator = Terminator::Jump(join);
+        self.blocks[otherwise_end].terminator = Terminator::Jump(join);
+        self.current = join;
+        Ok(())
+    }
 
-    let dst = get_child_label(node, 0);
-    // let dst_offset = get_index(node: &Node)
-    let src = get_child_label(node, 1);
-    // let src_offset
+    fn while_stat(&mut self, condition: &Expr, body_statements: &[Stmt]) -> Result<()> {
+        let condition_block = self.new_block();
+        self.end_block(Terminator::Jump(condition_block));
+        self.current = condition_block;
+        let condition = self.expression(condition)?;
+        let branch = self.current;
+
+        let body = self.new_block();
+        self.current = body;
+        self.loops.push(Loop {
+            condition: condition_block,
+            breaks: Vec::new(),
+        });
+        self.statements(body_statements)?;
+        self.end_block(Terminator::Jump(condition_block));
+        let breaks = self.loops.pop().map(|enclosing| enclosing.breaks);
+
+        let after = self.new_block();
+        self.blocks[branch].terminator = Terminator::Branch {
+            condition,
+            then: body,
+            otherwise: after,
+        };
+        for block in breaks.into_iter().flatten() {
+            self.blocks[block].terminator = Terminator::Jump(after);
+        }
+        self.current = after;
+        Ok(())
+    }
 
-    // Here the added offset to the dst is what will affect arrays
-    mm::cmt_exec(&format!("Processing assign op to \"{}\" from \"{}\"", src, dst), output);
+    fn expression(&mut self, expression: &Expr) -> Result<Operand> {
+        Ok(match expression {
+            Expr::Integer(value, _) => Operand::Constant(*value),
+            // The floats are stored as the bits of their single precision value
+            Expr::Float(value, _) => {
+                Operand::Constant(i32::from_ne_bytes((*value as f32).to_ne_bytes()) as i64)
+            }
+            Expr::Binary { op, lhs, rhs, .. } => {
+                let left = self.expression(lhs)?;
+                let right = self.expression(rhs)?;
+                // The operands of a relational operation are of the same type, unlike its value
+                let operand_type = match lhs.data_type() {
+                    Some("float") => OperandType::Float,
+                    _ => OperandType::Integer,
+                };
+                let destination = self.new_temporary();
+                self.push(Instruction::Binary {
+                    destination: destination.clone(),
+                    operator: Operator::from(*op),
+                    operand_type,
+                    left,
+                    right,
+                });
+                destination
+            }
+            Expr::Variable(variable) => self
+                .variable(variable)?
+                .ok_or_else(|| unresolved("variable with a value", variable.position))?,
+            Expr::String(..) => return Err(unsupported("a string", expression.position())),
+            Expr::Not(..) => return Err(unsupported("a negation", expression.position())),
+            Expr::Sign(..) => return Err(unsupported("a sign", expression.position())),
+            Expr::Ternary { .. } => {
+                return Err(unsupported("a ternary operation", expression.position()))
+            }
+        })
+    }
 
-    output.add_exec(&moon::instr_line(&moon::load_w(&local_register, &src, &R0)));
-    output.add_exec(&moon::instr_line(&moon::store_w(&dst, &R0, &local_register)));
+    /// Call the functions of the accesses of a variable and return its value, if it has one
+    fn variable(&mut self, variable: &Variable) -> Result<Option<Operand>> {
+        let accesses = get_accesses(variable)?;
+        let mut result = None;
+        for index in 0..accesses.len() {
+            if let Access::Call { .. } = accesses[index] {
+                result = self.call(accesses, index)?;
+            }
+        }
 
-    state.registers.release(r);
-}
+        Ok(if is_indirect(accesses) {
+            let (address, offset) = self.address_of(accesses, accesses.len())?;
+            let destination = self.new_temporary();
+            self.push(Instruction::Load {
+                destination: destination.clone(),
+                address,
+                offset,
+            });
+            Some(destination)
+        } else if let Some(Access::Call { .. }) = accesses.last() {
+            result
+        } else {
+            Some(Operand::Label(self.table.mangle(&accesses[0].name().name)))
+        })
+    }
 
-fn mul_op(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
+    fn assign(&mut self, variable: &Variable, value: Operand) -> Result<()> {
+        let accesses = get_accesses(variable)?;
+        if is_indirect(accesses) {
+            let (address, offset) = self.address_of(accesses, accesses.len())?;
+            self.push(Instruction::Store {
+                address,
+                offset,
+                source: value,
+            });
+        } else {
+            self.push(Instruction::Copy {
+                destination: Operand::Label(self.table.mangle(&accesses[0].name().name)),
+                source: value,
+            });
         }
+        Ok(())
     }
 
-    let r = state.registers.reserve(3);
-    let local_register = state.registers.pop();
-    let lhs = state.registers.pop();
-    let rhs = state.registers.pop();
-
-    let lhs_label = get_child_label(node, 0);
-    let rhs_label = get_child_label(node, 2);
-    let dst = get_label(node);
-    let op = get_child_name(node, 1);
-
-    mm::cmt_exec(&format!("Processing mul op {} <- {} {} {} ", dst, lhs_label, op, rhs_label), output);
+    /// Call the function of an access of a variable, member functions are found through the
+    /// virtual table of the object preceding it, or of the object of the member function
+    fn call(&mut self, accesses: &[Access], index: usize) -> Result<Option<Operand>> {
+        let (name, args, resolution) = match &accesses[index] {
+            Access::Call {
+                name,
+                args,
+                resolution,
+            } => (name, args, resolution),
+            Access::DataMember { name, .. } => return Err(unresolved("call", name.position)),
+        };
+
+        // The arguments may call functions in turn, so they are evaluated before being passed
+        let mut arguments = Vec::new();
+        for argument in args {
+            // Objects are passed by address
+            let is_object = argument
+                .data_type()
+                .is_some_and(|d_type| self.is_object(d_type));
+            let value = match argument {
+                Expr::Variable(variable) if is_object => {
+                    let argument_accesses = get_accesses(variable)?;
+                    let address = self.address_of(argument_accesses, argument_accesses.len())?;
+                    self.offset_address(address)
+                }
+                _ if is_object => {
+                    return Err(unsupported(
+                        "an object which isn't a variable",
+                        argument.position(),
+                    ))
+                }
+                _ => self.expression(argument)?,
+            };
+            arguments.push(value);
+        }
 
-    output.add_exec(&moon::instr_line(&moon::load_w(&lhs, &lhs_label, &R0)));
-    output.add_exec(&moon::instr_line(&moon::load_w(&rhs, &rhs_label, &R0)));
+        let slot = resolution.slot;
+        let receiver = match (index, slot) {
+            (0, Some(_)) => Some(Operand::Label(self.table.this_label())),
+            (0, None) => None,
+            _ => {
+                let address = self.address_of(accesses, index)?;
+                Some(self.offset_address(address))
+            }
+        };
+
+        let mut first_argument = 0;
+        if let (Some(_), Some(receiver)) = (slot, &receiver) {
+            self.push(Instruction::Argument {
+                index: 0,
+                source: receiver.clone(),
+            });
+            first_argument = 1;
+        }
+        for (index, source) in arguments.into_iter().enumerate() {
+            self.push(Instruction::Argument {
+                index: first_argument + index,
+                source,
+            });
+        }
 
-    if op == "*" {
-        output.add_exec(&moon::instr_line(&moon::mul(&local_register, &lhs, &rhs)));
-    } else if op == "/" {
-        output.add_exec(&moon::instr_line(&moon::div(&local_register, &lhs, &rhs)));
-    } else if op == "and" {
-        panic!();
-    } else {
-        panic!();
+        // Void functions have no type
+        let destination = resolution.data_type.as_ref().map(|_| self.new_temporary());
+        let instruction = match (slot, &resolution.function, receiver) {
+            (Some(slot), _, Some(object)) => Instruction::VirtualCall {
+                destination: destination.clone(),
+                object,
+                slot,
+            },
+            (None, Some(function), _) => Instruction::Call {
+                destination: destination.clone(),
+                function: function.clone(),
+            },
+            _ => return Err(unresolved("function called", name.position)),
+        };
+        self.push(instruction);
+        Ok(destination)
     }
 
-    output.add_exec(&moon::instr_line(&moon::store_w(&dst, &R0, &local_register)));
-
-    state.registers.release(r);
-}
-
-fn add_op(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
+    /// The address of the object or of the value named by the first accesses of a variable, as
+    /// an operand holding the address of its first object and the offset from it
+    fn address_of(&mut self, accesses: &[Access], upto: usize) -> Result<(Operand, usize)> {
+        // The objects returned by the calls aren't kept anywhere
+        if let Some(call) = accesses[..upto]
+            .iter()
+            .find(|access| matches!(access, Access::Call { .. }))
+        {
+            return Err(unsupported(
+                "the object returned by a call",
+                call.name().position,
+            ));
         }
+        let first = &accesses[0];
+        let id = &first.name().name;
+
+        let (address, dimension, data_type) = match &first.resolution().member {
+            // A member of the object of the member function
+            Some(declaring_class) => {
+                let class_id = self.table.scope.clone().ok_or_else(|| {
+                    unresolved("member in a member function", first.name().position)
+                })?;
+                let slot = data_slot(first, &class_id, declaring_class, self.global_table)?;
+                let location = (Operand::Label(self.table.this_label()), *slot.offset());
+                let dimension = slot.dimension().iter().copied().map(Some).collect();
+                (location, dimension, slot.data_type().clone())
+            }
+            None => match self.table.get(id) {
+                // Objects and arrays are passed by address
+                Some(SymbolTableEntry::Param(param))
+                    if !param.dimension().is_empty() || self.is_object(param.data_type()) =>
+                {
+                    let location = (Operand::Label(self.table.mangle(id)), 0);
+                    (
+                        location,
+                        param.dimension().clone(),
+                        param.data_type().clone(),
+                    )
+                }
+                Some(SymbolTableEntry::Param(param)) => {
+                    let location = self.label_address(id);
+                    (location, Vec::new(), param.data_type().clone())
+                }
+                Some(SymbolTableEntry::Local(local)) => {
+                    let location = self.label_address(id);
+                    let dimension = local.dimension().iter().copied().map(Some).collect();
+                    (location, dimension, local.data_type().clone())
+                }
+                _ => return Err(unresolved("local or parameter", first.name().position)),
+            },
+        };
+        let mut location = self.element(first, address, &dimension, &data_type)?;
+
+        for index in 1..upto {
+            let object = &accesses[index - 1];
+            let class_id = object
+                .resolution()
+                .data_type
+                .as_ref()
+                .ok_or_else(|| unresolved("type of the object", object.name().position))?;
+            let member = &accesses[index];
+            let declaring_class = member
+                .resolution()
+                .member
+                .as_ref()
+                .ok_or_else(|| unresolved("member of the object", member.name().position))?;
+            let slot = data_slot(member, class_id, declaring_class, self.global_table)?;
+            let dimension: Vec<Option<i64>> = slot.dimension().iter().copied().map(Some).collect();
+            location.1 += slot.offset();
+            location = self.element(member, location, &dimension, slot.data_type())?;
+        }
+        Ok(location)
     }
 
-    let r = state.registers.reserve(3);
-    let local_register = state.registers.pop();
-    let lhs = state.registers.pop();
-    let rhs = state.registers.pop();
-
-    let lhs_label = get_child_label(node, 0);
-    let rhs_label = get_child_label(node, 2);
-    let dst = get_label(node);
-    let op = get_child_name(node, 1);
+    /// The address of the label of a local or of a parameter in a new temporary
+    fn label_address(&mut self, id: &str) -> (Operand, usize) {
+        let address = self.new_temporary();
+        self.push(Instruction::Address {
+            destination: address.clone(),
+            label: self.table.mangle(id),
+        });
+        (address, 0)
+    }
 
-    mm::cmt_exec(&format!("Processing add op {} <- {} {} {} ", dst, lhs_label, op, rhs_label), output);
+    /// The location of the element selected by the indices of an access in the array at a
+    /// location, the indices known when compiling are added to the offset
+    fn element(
+        &mut self,
+        access: &Access,
+        (mut address, mut offset): (Operand, usize),
+        dimension: &[Option<i64>],
+        data_type: &str,
+    ) -> Result<(Operand, usize)> {
+        let (name, indices) = match access {
+            Access::DataMember { name, indices, .. } if !indices.is_empty() => (name, indices),
+            _ => return Ok((address, offset)),
+        };
+        if indices.len() != dimension.len() {
+            return Err(unsupported(
+                "an array not indexed in each dimension",
+                name.position,
+            ));
+        }
+        let element_size = self
+            .size_of(data_type)
+            .ok_or_else(|| unsupported("an array of strings", name.position))?;
+
+        // The arrays are laid out row by row, the size of a dimension left out only matters if
+        // it isn't the first
+        let mut strides = vec![element_size as i64; indices.len()];
+        for index in (0..indices.len() - 1).rev() {
+            let size = dimension[index + 1].ok_or_else(|| {
+                unsupported("an array with an inner dimension left out", name.position)
+            })?;
+            strides[index] = strides[index + 1] * size;
+        }
 
-    output.add_exec(&moon::instr_line(&moon::load_w(&lhs, &lhs_label, &R0)));
-    output.add_exec(&moon::instr_line(&moon::load_w(&rhs, &rhs_label, &R0)));
+        for (index, stride) in indices.iter().zip(strides) {
+            match self.expression(index)? {
+                Operand::Constant(value) if value >= 0 => offset += (value * stride) as usize,
+                value => {
+                    let bytes = self.new_temporary();
+                    self.push(Instruction::Binary {
+                        destination: bytes.clone(),
+                        operator: Operator::Multiply,
+                        operand_type: OperandType::Integer,
+                        left: value,
+                        right: Operand::Constant(stride),
+                    });
+                    let element = self.new_temporary();
+                    self.push(Instruction::Binary {
+                        destination: element.clone(),
+                        operator: Operator::Add,
+                        operand_type: OperandType::Integer,
+                        left: address,
+                        right: bytes,
+                    });
+                    address = element;
+                }
+            }
+        }
+        Ok((address, offset))
+    }
 
-    if op == "+" {
-        output.add_exec(&moon::instr_line(&moon::add(&local_register, &lhs, &rhs)));
-    } else if op == "-" {
-        output.add_exec(&moon::instr_line(&moon::sub(&local_register, &lhs, &rhs)));
-    } else if op == "or" {
-        panic!();
-    } else {
-        panic!();
+    /// The size of a value of a type, strings have no size
+    fn size_of(&self, data_type: &str) -> Option<usize> {
+        match (data_type, self.global_table.get(data_type)) {
+            ("integer", _) | ("float", _) => Some(4),
+            (_, Some(SymbolTableEntry::Class(class))) => Some(*class.bytes()),
+            _ => None,
+        }
     }
 
-    output.add_exec(&moon::instr_line(&moon::store_w(&dst, &R0, &local_register)));
+    fn is_object(&self, data_type: &str) -> bool {
+        matches!(
+            self.global_table.get(data_type),
+            Some(SymbolTableEntry::Class(_))
+        )
+    }
 
-    state.registers.release(r);
+    /// Add the offset to the address, for the addresses passed to functions
+    fn offset_address(&mut self, (address, offset): (Operand, usize)) -> Operand {
+        if offset == 0 {
+            return address;
+        }
+        let destination = self.new_temporary();
+        self.push(Instruction::Binary {
+            destination: destination.clone(),
+            operator: Operator::Add,
+            operand_type: OperandType::Integer,
+            left: address,
+            right: Operand::Constant(offset as i64),
+        });
+        destination
+    }
 }
 
-fn rel_op(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
+/// The data member of an access as laid out in the objects of a class
+fn data_slot<'a>(
+    access: &Access,
+    class_id: &str,
+    declaring_class: &str,
+    global_table: &'a SymbolTable,
+) -> Result<&'a DataSlot> {
+    if let Some(SymbolTableEntry::Class(class)) = global_table.get(class_id) {
+        if let Some(slot) = class.data_slot(&access.name().name, declaring_class) {
+            return Ok(slot);
         }
     }
+    Err(unresolved(
+        "data member laid out in its class",
+        access.name().position,
+    ))
+}
 
-    let r = state.registers.reserve(3);
-    let local_register = state.registers.pop();
-    let lhs = state.registers.pop();
-    let rhs = state.registers.pop();
-
-    let lhs_label = get_child_label(node, 0);
-    let rhs_label = get_child_label(node, 2);
-    let dst = get_label(node);
-    let op = get_child_name(node, 1);
-
-    mm::cmt_exec(&format!("Processing rel op {} <- {} {} {}", dst, lhs_label, op, rhs_label), output);
-
-    output.add_exec(&moon::instr_line(&moon::load_w(&lhs, &lhs_label, &R0)));
-    output.add_exec(&moon::instr_line(&moon::load_w(&rhs, &rhs_label, &R0)));
-
-    if op == "lt" {
-        output.add_exec(&moon::instr_line(&moon::cmp_lt(&local_register, &lhs, &rhs)));
-    } else if op == "lte" {
-        output.add_exec(&moon::instr_line(&moon::cmp_lte(&local_register, &lhs, &rhs)));
-    } else if op == "gt" {
-        output.add_exec(&moon::instr_line(&moon::cmp_gt(&local_register, &lhs, &rhs)));
-    } else if op == "gte" {
-        output.add_exec(&moon::instr_line(&moon::cmp_gte(&local_register, &lhs, &rhs)));
-    } else if op == "eq" {
-        output.add_exec(&moon::instr_line(&moon::cmp_eq(&local_register, &lhs, &rhs)));
-    } else {
-        panic!();
+/// Whether a variable is a data member or an element found through an address, rather than at
+/// its label
+fn is_indirect(accesses: &[Access]) -> bool {
+    match accesses.last() {
+        Some(Access::DataMember { indices, .. }) => {
+            accesses.len() > 1 || accesses[0].resolution().member.is_some() || !indices.is_empty()
+        }
+        _ => false,
     }
-
-    output.add_exec(&moon::instr_line(&moon::store_w(&dst, &R0, &local_register)));
-
-    state.registers.release(r);
 }
 
-fn if_stat(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        let (else_label, endif_label) = context.get_next_if_else_label();
-        mm::cmt_exec(&format!("If statement ({}, {})", else_label, endif_label), output);
-
-
-        // Loads the relOp label with the value
-        visit(&children[0], context, state, global_table, output);
-
-        let rel_op = get_child_label(node, 0);
-        
-
-
-        let r = state.registers.reserve(1);
-        let cmp_res = state.registers.pop();
-
-
-
-        output.add_exec(&moon::instr_line(&moon::load_w(&cmp_res, &rel_op, &R0)));
-        output.add_exec(&moon::instr_line(&moon::jmp_zero(&cmp_res, &else_label)));
-
-        mm::cmt_exec(&format!("If statement ({}, {}) TRUE block", else_label, endif_label), output);
-
-        // True block
-        visit(&children[1], context, state, global_table, output);
-        output.add_exec(&moon::instr_line(&moon::jmp(&endif_label)));
-
-        mm::cmt_exec(&format!("If statement ({}, {}) FALSE block", else_label, endif_label), output);
-
-        // False block
-        output.add_exec(&moon::labeled_line(&else_label, &moon::noop()));
-        visit(&children[2], context, state, global_table, output);
-
-        // End if
-        output.add_exec(&moon::labeled_line(&endif_label, &moon::noop()));
-
-        state.registers.release(r);
+/// The accesses of a variable, there is at least one
+fn get_accesses(variable: &Variable) -> Result<&[Access]> {
+    match variable.accesses.as_slice() {
+        [] => Err(unresolved(
+            "variable with at least one access",
+            variable.position,
+        )),
+        accesses => Ok(accesses),
     }
 }
 
-fn while_stat(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    // The labels to jump to must be unique
-    // They could use some incrementing value plus mangling
-    // so main__else1, main__endif1, main__else2, main__endif2
-    // The most logical place to store that counter would be the symbol table
-    // perform comparison
-    if let Data::Children(children) = node.data() {
-        let rel_op = get_child_label(node, 0);
-        let (go_while, end_while) = context.get_next_while_label();
-        mm::cmt_exec(&format!("While statement  ({}, {})", go_while, end_while), output);
-
-
-        let r = state.registers.reserve(1);
-        let cmp_res = state.registers.pop();
-
-        output.add_exec(&moon::labeled_line(&go_while, &moon::noop()));
+fn unresolved(expected: &str, position: Position) -> CodeGenError {
+    CodeGenError::Unresolved(expected.to_string(), position)
+}
 
-        mm::cmt_exec(&format!("While statement ({}, {}), Evaluation of conditional", go_while, end_while), output);
+fn unsupported(construct: &str, position: Position) -> CodeGenError {
+    CodeGenError::Unsupported(construct.to_string(), position)
+}
 
-        visit(&children[0], context, state, global_table, output);
-        output.add_exec(&moon::instr_line(&moon::load_w(&cmp_res, &rel_op, &R0)));
-        output.add_exec(&moon::instr_line(&moon::jmp_zero(&cmp_res, &end_while)));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{analyze_source, analyze_source_with_errors};
+    use std::convert::TryFrom;
 
-        mm::cmt_exec(&&format!("While statement ({}, {}), Statement block", go_while, end_while), output);
+    /// Build the code of a source file which has no errors
+    fn build_source(name: &str, contents: &str) -> Result<Program> {
+        let (ast, results, _) = analyze_source(name, contents);
+        build(&typed::Program::try_from(&ast)?, &results.symbol_table)
+    }
 
-        // True block
-        visit(&children[1], context, state, global_table, output);
-        output.add_exec(&moon::instr_line(&moon::jmp(&go_while)));
-        output.add_exec(&moon::labeled_line(&end_while, &moon::noop()));
+    #[test]
+    fn test_build_loop_with_break_and_call() {
+        let program = build_source(
+            "lowering.src",
+            r#"
+func twice(integer n) : integer
+{
+  return (n * 2);
+}
 
-        state.registers.release(r);
+main
+{
+  var
+  {
+    integer i;
+  }
+  i = 0;
+  while (i < 10)
+  {
+    if (i == 5) then
+    {
+      break;
     }
+    else
+    {
+      write(twice(i));
+    };
+    i = i + 1;
+  };
 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            program.to_string(),
+            "\
+function twice(integer) at twice__entry
+  b0:
+    twice__n := parameter 0
+    t0 := twice__n * 2
+    return t0
+  b1:
+    return
+
+function main() at main__entry
+  b0:
+    main__i := 0
+    goto b1
+  b1:
+    t0 := main__i < 10
+    if t0 goto b2 else b7
+  b2:
+    t1 := main__i == 5
+    if t1 goto b3 else b5
+  b3:
+    goto b7
+  b4:
+    goto b6
+  b5:
+    argument 0 := main__i
+    t2 := call twice__entry
+    write t2
+    goto b6
+  b6:
+    t3 := main__i + 1
+    main__i := t3
+    goto b1
+  b7:
+    return
+"
+        );
+    }
 
+    #[test]
+    fn test_build_float_operations() {
+        let program = build_source(
+            "float_operations.src",
+            r#"
+main
+{
+  write(1.5 * 2.0);
+  write(1.5 < 2.0);
+  write(1 + 2);
+}
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            program.to_string(),
+            "\
+function main() at main__entry
+  b0:
+    t0 := float 1069547520 * 1073741824
+    write t0
+    t1 := float 1069547520 < 1073741824
+    write t1
+    t2 := 1 + 2
+    write t2
+    return
+"
+        );
+    }
 
-fn write_stat(
-    node: &Node,
-    context: &mut SymbolTable,
-    state: &mut State,
-    global_table: &mut SymbolTable,
-    output: &mut OutputConfig,
-) {
-    if let Data::Children(children) = node.data() {
-        for child in children {
-            visit(child, context, state, global_table, output);
-        }
+    #[test]
+    fn test_build_array_elements() {
+        let program = build_source(
+            "array_elements.src",
+            r#"
+class POINT
+{
+  public integer x;
+  public integer y[3];
+};
+
+main
+{
+  var
+  {
+    integer a[5];
+    integer m[2][3];
+    integer i;
+    POINT p[2];
+  }
+  i = 1;
+  a[2] = 1;
+  a[i] = 4;
+  write(a[2]);
+  m[1][i] = 5;
+  p[1].y[i] = 6;
+}
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            program.to_string(),
+            "\
+function main() at main__entry
+  b0:
+    t0 := &main__p
+    t1 := &POINT__vtable
+    [t0 + 0] := t1
+    t2 := &POINT__vtable
+    [t0 + 20] := t2
+    main__i := 1
+    t3 := &main__a
+    [t3 + 8] := 1
+    t4 := &main__a
+    t5 := main__i * 4
+    t6 := t4 + t5
+    [t6 + 0] := 4
+    t7 := &main__a
+    t8 := [t7 + 8]
+    write t8
+    t9 := &main__m
+    t10 := main__i * 4
+    t11 := t9 + t10
+    [t11 + 12] := 5
+    t12 := &main__p
+    t13 := main__i * 4
+    t14 := t12 + t13
+    [t14 + 28] := 6
+    return
+"
+        );
     }
 
-    let r = state.registers.reserve(1);
-    let local = state.registers.pop();
-    let src = get_child_label(node, 0);
-
-    // Taken from slides
-    output.add_exec(&moon::cmt_line("Processing write statement"));
-    output.add_exec(&moon::instr_line(&moon::load_w(&local, &src, &R0)));
-    output.add_exec(&moon::cmt_line("put value on stack"));
-    output.add_exec(&moon::instr_line(&moon::store_w("-8", &R14, &local)));
-    output.add_exec(&moon::cmt_line("link buffer to stack"));
-    output.add_exec(&moon::instr_line(&moon::add_i(&local, &R0, "buf")));
-    output.add_exec(&moon::instr_line(&moon::store_w("-12", &R14, &local)));
-    output.add_exec(&moon::cmt_line("convert int to string for output"));
-    output.add_exec(&moon::instr_line(&moon::jmp_lnk(&R15, "intstr")));
-    output.add_exec(&moon::instr_line(&moon::store_w("-8", &R14, &R13)));
-    output.add_exec(&moon::cmt_line("output to console"));
-    output.add_exec(&moon::instr_line(&moon::jmp_lnk(&R15, "putstr")));
-
-    state.registers.release(r);
+    #[test]
+    fn test_build_read() {
+        let program = build_source(
+            "read.src",
+            r#"
+main
+{
+  var
+  {
+    integer a[5];
+    integer i;
+  }
+  read(i);
+  read(a[i]);
 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            program.to_string(),
+            "\
+function main() at main__entry
+  b0:
+    t0 := read
+    main__i := t0
+    t1 := read
+    t2 := &main__a
+    t3 := main__i * 4
+    t4 := t2 + t3
+    [t4 + 0] := t1
+    return
+"
+        );
+    }
 
+    #[test]
+    fn test_build_unsupported_expression() {
+        let error = build_source(
+            "unsupported.src",
+            r#"
+main
+{
+  var
+  {
+    integer i;
+  }
+  i = 1;
+  write(!i);
+}
+"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No code can be generated for a negation at line 9, col 9"
+        );
+    }
 
+    #[test]
+    fn test_build_override_through_base_parameter() {
+        let program = build_source(
+            "base_parameter.src",
+            r#"
+class A
+{
+  public integer x;
+  public func get() : integer;
+};
+
+class B inherits A
+{
+  public func get() : integer;
+};
+
+func A::get() : integer
+{
+  return (x);
+}
 
-// fn entry_point(node: &Node, context: &SymbolTable, output: &mut OutputConfig) {
-//     if let Data::Children(children) = node.data() {
-//         for child in children {
-//             visit(child, context, output);
-//         }
-//     }
-// }
-
-// fn var_list(node: &Node, context: &SymbolTable, output: &mut OutputConfig) {
-//     if let Data::Children(children) = node.data() {
-//         for child in children {
-//             visit(child, context, output);
-//         }
-//     }
-// }
-
-// fn var_decl(_node: &Node, _context: &SymbolTable, _output: &mut OutputConfig) {
-//     // one challenge here, we need to create the entry but we also
-//     // need the name of the enclosing function to prefix the label
-// }
-
-fn get_child_label(node: &Node, index: usize) -> String {
-    if let Data::Children(children) = node.data() {
-        if let Some(label) = children[index].label() {
-            return label;
-        } else {
-            panic!();
-        }
-    } else {
-        panic!();
-    };
+func B::get() : integer
+{
+  return (x + 1);
 }
 
-// This accepts a dataMember
-// fn get_index(node: &Node, child: usize) -> usize {
-//     if let Data::Children(children) = node.data() { // This would be the assignOp
-//         if let Data::Children(children) = children[child].data() {
-//             if let Data::Children(indx_list_children) = children[0].data() {
-//                 if let Data::Integer(offset) = indx_list_children[0].data() {
-//                     return *offset as usize;
-//                 }
-//             }
-//         }
-    
-//     }
-
-//     return 0;
-// }
-
-fn get_child_name(node: &Node, index: usize) -> String {
-    if let Data::Children(children) = node.data() {
-        return children[index].name().clone();
-    } else {
-        panic!();
-    };
+func show(A a) : integer
+{
+  return (a.get());
+}
+
+main
+{
+  var
+  {
+    B b;
+  }
+  b.x = 1;
+  write(show(b));
 }
+"#,
+        )
+        .unwrap();
+        // The object passed is called through the slot B overrides
+        assert_eq!(
+            program.functions[2].to_string(),
+            "\
+function show(A) at show__entry
+  b0:
+    show__a := parameter 0
+    argument 0 := show__a
+    t0 := call slot 0 of show__a
+    return t0
+  b1:
+    return
+"
+        );
+        assert_eq!(
+            program.functions[3].to_string(),
+            "\
+function main() at main__entry
+  b0:
+    t0 := &main__b
+    t1 := &B__vtable
+    [t0 + 0] := t1
+    t2 := &main__b
+    [t2 + 4] := 1
+    t3 := &main__b
+    argument 0 := t3
+    t4 := call show__entry
+    write t4
+    return
+"
+        );
+    }
+
+    #[test]
+    fn test_build_secondary_base_calls() {
+        let source = |call: &str| {
+            format!(
+                r#"
+class A
+{{
+  public integer x;
+  public func get() : integer;
+}};
+
+class B
+{{
+  public integer y;
+  public func other() : integer;
+  public func same() : integer;
+}};
+
+class C inherits A, B
+{{
+  public func other() : integer;
+}};
+
+func A::get() : integer
+{{
+  return (x);
+}}
+
+func B::other() : integer
+{{
+  return (y);
+}}
+
+func B::same() : integer
+{{
+  return (y);
+}}
+
+func C::other() : integer
+{{
+  return (y + 1);
+}}
+
+main
+{{
+  var
+  {{
+    C c;
+  }}
+  c.y = 7;
+  write(c.{}());
+}}
+"#,
+                call
+            )
+        };
+
+        // The override in C is laid out with the members of B copied into C
+        let (ast, results, _) = analyze_source("secondary_override.src", &source("other"));
+        let program = build(
+            &typed::Program::try_from(&ast).unwrap(),
+            &results.symbol_table,
+        )
+        .unwrap();
+        assert_eq!(
+            program.functions[3].to_string(),
+            "\
+function C::other() at C__other__entry
+  b0:
+    C__other__this := parameter 0
+    t0 := [C__other__this + 8]
+    t1 := t0 + 1
+    return t1
+  b1:
+    return
+"
+        );
+        assert_eq!(
+            program.functions[4].to_string(),
+            "\
+function main() at main__entry
+  b0:
+    t0 := &main__c
+    t1 := &C__vtable
+    [t0 + 0] := t1
+    t2 := &main__c
+    [t2 + 8] := 7
+    t3 := &main__c
+    argument 0 := t3
+    t4 := call slot 1 of t3
+    write t4
+    return
+"
+        );
+
+        // The members of B aren't where B::same finds them in the objects of C
+        let (_, _, output) = analyze_source_with_errors("secondary_call.src", &source("same"));
+        let messages: Vec<&String> = output
+            .semantic_error_buffer
+            .iter()
+            .map(|message| message.message())
+            .filter(|message| message.starts_with("Semantic error"))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic error: 47:11 Member function \"B::same()\" can't be called on an object of class \"C\", which doesn't inherit it through its first inherited classes"
+            ]
+        );
+    }
 
-fn get_label(node: &Node) -> String {
-    return node.label().clone().unwrap();
+    #[test]
+    fn test_build_unresolved_call() {
+        let (_, results, _) = analyze_source("unresolved.src", "main\n{\n}\n");
+        let main = match results.symbol_table.get("main") {
+            Some(SymbolTableEntry::Function(main)) => main,
+            _ => panic!("main is missing"),
+        };
+        let mut builder = Builder {
+            table: main.symbol_table(),
+            global_table: &results.symbol_table,
+            blocks: vec![BasicBlock::new()],
+            current: 0,
+            temporaries: 0,
+            loops: Vec::new(),
+        };
+        let position = Position { line: 3, column: 9 };
+        // A call the type check didn't resolve
+        let call = Variable {
+            accesses: vec![Access::Call {
+                name: typed::Ident {
+                    name: "f".to_string(),
+                    position,
+                },
+                args: vec![],
+                resolution: typed::Resolution::default(),
+            }],
+            position,
+        };
+        assert_eq!(
+            builder.variable(&call).unwrap_err().to_string(),
+            "Expected function called resolved by the type check at line 3, col 9"
+        );
+    }
 }
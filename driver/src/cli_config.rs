@@ -1,6 +1,17 @@
 //! Application specific validation and parsing of CLI arguments
 
 use clap::ArgMatches;
+use lexical_analyzer::ColumnUnit;
+use log::error;
+use output_manager::{DerivationMode, Lint, LintLevel, OptimizationLevel};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParserKind {
+    Ll1,
+    Lalr,
+    RecursiveDescent,
+}
 
 pub struct CliConfig<'a> {
     pub source_folder: &'a str,
@@ -8,10 +19,24 @@ pub struct CliConfig<'a> {
     pub lex_tokens_file: &'a str,
     pub keyword_file: &'a str,
     pub grammar_file: &'a str,
+    pub parser: ParserKind,
+    pub column_unit: ColumnUnit,
+    pub tab_width: usize,
+    pub use_table_cache: bool,
+    pub derivation_mode: DerivationMode,
+    pub parse_tree: bool,
+    pub lint_levels: HashMap<Lint, LintLevel>,
+    pub optimization_level: OptimizationLevel,
 }
 
 impl<'a> CliConfig<'a> {
     pub fn new(matches: &'a ArgMatches) -> CliConfig<'a> {
+        // The possible values are restricted by the argument parser
+        let parser = match matches.value_of("parser") {
+            Some("lalr") => ParserKind::Lalr,
+            Some("recursive") => ParserKind::RecursiveDescent,
+            _ => ParserKind::Ll1,
+        };
         CliConfig {
             source_folder: matches.value_of("INPUT").unwrap_or("test_sources"),
             output_folder: matches.value_of("output").unwrap_or("test_output"),
@@ -21,9 +46,49 @@ impl<'a> CliConfig<'a> {
             keyword_file: matches
                 .value_of("keywords")
                 .unwrap_or("resources/keywords.txt"),
-            grammar_file: matches
-                .value_of("grammar")
-                .unwrap_or("resources/LL1.paquet.grm"),
+            grammar_file: matches.value_of("grammar").unwrap_or(match parser {
+                ParserKind::Ll1 | ParserKind::RecursiveDescent => "resources/LL1.paquet.grm",
+                ParserKind::Lalr => "resources/LALR.paquet.grm",
+            }),
+            parser,
+            // The possible values are restricted by the argument parser
+            column_unit: matches
+                .value_of("columns")
+                .map(|unit| unit.parse().unwrap())
+                .unwrap_or_default(),
+            tab_width: match matches.value_of("tabwidth").map(|width| width.parse()) {
+                None => 1,
+                Some(Ok(width)) if width > 0 => width,
+                _ => {
+                    error!("Tab width must be a positive number of columns");
+                    std::process::exit(1);
+                }
+            },
+            use_table_cache: !matches.is_present("nocache"),
+            // The possible values are restricted by the argument parser
+            derivation_mode: matches
+                .value_of("derivation")
+                .map(|mode| mode.parse().unwrap())
+                .unwrap_or_default(),
+            parse_tree: matches.is_present("parsetree"),
+            lint_levels: lint_levels(matches),
+            // The possible values are restricted by the argument parser
+            optimization_level: matches
+                .value_of("optimize")
+                .map(|level| level.parse().unwrap())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The levels of the lints allowed or denied, denying a lint overrides allowing it
+fn lint_levels(matches: &ArgMatches) -> HashMap<Lint, LintLevel> {
+    let mut levels = HashMap::new();
+    for (arg, level) in &[("allow", LintLevel::Allow), ("deny", LintLevel::Deny)] {
+        // The possible values are restricted by the argument parser
+        for code in matches.values_of(arg).into_iter().flatten() {
+            levels.insert(code.parse().unwrap(), *level);
         }
     }
+    levels
 }
@@ -0,0 +1,92 @@
+//! Subcommands operating on the grammar file rather than on source files
+
+use clap::ArgMatches;
+use log::{error, info};
+use std::fs::File;
+use syntactic_analyzer::{
+    check_grammar, export_first_follow, export_parse_table, read_productions, ExportFormat,
+    Grammar, ParseTable, EXPORT_FORMATS,
+};
+
+const DEFAULT_GRAMMAR_FILE: &str = "resources/LL1.paquet.grm";
+const DEFAULT_OUTPUT_FOLDER: &str = "test_output";
+
+/// Run a grammar subcommand, returning false if it found problems
+pub fn run(matches: &ArgMatches) -> std::io::Result<bool> {
+    let grammar_file = matches.value_of("grammar").unwrap_or(DEFAULT_GRAMMAR_FILE);
+    match matches.subcommand() {
+        ("check", Some(_)) => check(grammar_file),
+        ("export", Some(export_matches)) => {
+            let output_folder = export_matches
+                .value_of("output")
+                .unwrap_or(DEFAULT_OUTPUT_FOLDER);
+            // The possible values are restricted by the argument parser
+            let formats = match export_matches.value_of("format") {
+                Some(format) => vec![format.parse().unwrap()],
+                None => EXPORT_FORMATS.to_vec(),
+            };
+            export(grammar_file, output_folder, &formats)
+        }
+        _ => {
+            error!("A grammar subcommand is required, see --help");
+            Ok(false)
+        }
+    }
+}
+
+fn check(grammar_file: &str) -> std::io::Result<bool> {
+    info!("Checking grammar file \"{}\"", grammar_file);
+    let (productions, start) = read_productions(File::open(grammar_file)?)?;
+    let issues = check_grammar(&productions, &start);
+    for issue in &issues {
+        println!(
+            "{}: {}\n",
+            if issue.is_error() { "error" } else { "warning" },
+            issue
+        );
+    }
+
+    let errors = issues.iter().filter(|issue| issue.is_error()).count();
+    info!(
+        "Found {} errors and {} warnings in \"{}\"",
+        errors,
+        issues.len() - errors,
+        grammar_file
+    );
+    Ok(errors == 0)
+}
+
+fn export(
+    grammar_file: &str,
+    output_folder: &str,
+    formats: &[ExportFormat],
+) -> std::io::Result<bool> {
+    let grammar = Grammar::from_reader(File::open(grammar_file)?)?;
+    let parse_table = ParseTable::from_grammar(&grammar);
+    std::fs::create_dir_all(output_folder)?;
+
+    let stem = std::path::Path::new(grammar_file)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("grammar");
+    for format in formats {
+        let documents = [
+            ("first_follow", export_first_follow(&grammar, *format)),
+            (
+                "parse_table",
+                export_parse_table(&grammar, &parse_table, *format),
+            ),
+        ];
+        for (name, contents) in &documents {
+            let path = std::path::Path::new(output_folder).join(format!(
+                "{}.{}.{}",
+                stem,
+                name,
+                format.extension()
+            ));
+            info!("Writing {}", path.display());
+            std::fs::write(path, contents)?;
+        }
+    }
+    Ok(true)
+}
@@ -1,17 +1,25 @@
 mod cli_config;
+mod grammar_command;
 
 use clap::{load_yaml, App};
-use cli_config::CliConfig;
+use cli_config::{CliConfig, ParserKind};
 use code_gen;
 use lexical_analyzer::{lexer::Lexer, lexical_rule::LexicalRule};
-use log::{error, info};
-use output_manager::OutputConfig;
+use log::{error, info, warn};
+use output_manager::{DerivationMode, OutputConfig};
 use simplelog::*;
-use syntactic_analyzer::{parse, Grammar, ParseTable};
+use syntactic_analyzer::{
+    load_or_build, parse, parse_lalr, Grammar, LalrGrammar, LalrTable, ParseTable,
+    RecursiveDescentParser,
+};
 
 /// Development switch to easily turn terminal logging on or off
 const LOGGING_SWITCH: LevelFilter = LevelFilter::Info;
 
+/// The parse table of a grammar file is cached in the output folder under the grammar's file
+/// name with this extension appended
+const TABLE_CACHE_EXTENSION: &str = "tablecache";
+
 fn init_logging(level: LevelFilter) {
     TermLogger::init(level, Config::default(), TerminalMode::Mixed)
         .expect("Could not create logging interface");
@@ -23,12 +31,27 @@ fn init_logging(level: LevelFilter) {
 
 use std::fs::File;
 
+/// The tables driving the parser selected on the command line
+enum Parser {
+    Ll1(Grammar, ParseTable),
+    Lalr(LalrTable),
+    RecursiveDescent(RecursiveDescentParser),
+}
+
 fn main() -> std::io::Result<()> {
     // CLI args processing ====================================================
     let cli_config = load_yaml!("cli.yml");
     let matches = App::from_yaml(cli_config).get_matches();
-    let config = CliConfig::new(&matches);
+    // The configuration reports the invalid arguments through the logging interface
     init_logging(LOGGING_SWITCH);
+    let config = CliConfig::new(&matches);
+
+    if let ("grammar", Some(grammar_matches)) = matches.subcommand() {
+        if !grammar_command::run(grammar_matches)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     let output_dir = std::path::Path::new(config.output_folder);
     if !output_dir.exists() {
@@ -47,33 +70,91 @@ fn main() -> std::io::Result<()> {
         .lines()
         .map(|keyword| keyword.to_string())
         .collect();
-    let l = Lexer::new(rules, keywords);
+    let mut l = Lexer::new(rules, keywords);
+    l.set_column_unit(config.column_unit);
+    l.set_tab_width(config.tab_width);
     info!(
         "Extracting grammar productions from file \"{}\"",
         config.grammar_file
     );
-    let g = Grammar::from_reader(File::open(config.grammar_file)?)?;
-    let parse_table = ParseTable::from_grammar(&g);
+    let parser = match config.parser {
+        ParserKind::Ll1 | ParserKind::RecursiveDescent => {
+            let (g, parse_table) = if config.use_table_cache {
+                let cache_path = output_dir.join(format!(
+                    "{}.{}",
+                    path::file_name(config.grammar_file).unwrap_or("grammar"),
+                    TABLE_CACHE_EXTENSION
+                ));
+                load_or_build(config.grammar_file, &cache_path.to_string_lossy())?
+            } else {
+                let g = Grammar::from_reader(File::open(config.grammar_file)?)?;
+                let parse_table = ParseTable::from_grammar(&g);
+                (g, parse_table)
+            };
+            if config.parser == ParserKind::RecursiveDescent {
+                // The grammar only provides the sets used to recover from syntax errors
+                Parser::RecursiveDescent(RecursiveDescentParser::new(&g))
+            } else {
+                Parser::Ll1(g, parse_table)
+            }
+        }
+        ParserKind::Lalr => {
+            let table = LalrTable::from_grammar(LalrGrammar::from_reader(File::open(
+                config.grammar_file,
+            )?)?);
+            for conflict in table.conflicts() {
+                warn!("LALR(1) conflict in {}", conflict);
+            }
+            info!(
+                "Built LALR(1) parse table with {} states",
+                table.state_count()
+            );
+            Parser::Lalr(table)
+        }
+    };
+
+    if config.parser != ParserKind::Ll1
+        && (config.derivation_mode != DerivationMode::Trace || config.parse_tree)
+    {
+        warn!("The leftmost derivation and the parse tree are only written by the LL(1) parser");
+    }
 
     for source_file in path::directory(config.source_folder)
         .filter(|x| path::is_file(x) && path::extension(x).unwrap_or("") == "src")
     {
         let mut oc = OutputConfig::new(&source_file, config.output_folder);
-        let mut result = parse(
-            &mut l.lex(&source_file, &oc.lex_error_path),
-            &g,
-            &parse_table,
-            &mut oc,
-        );
+        oc.derivation_mode = config.derivation_mode;
+        oc.lint_levels = config.lint_levels.clone();
+        oc.optimization_level = config.optimization_level;
+        if config.parse_tree {
+            oc.enable_parse_tree();
+        }
+        let mut lex = l.lex(&source_file, &oc.lex_error_path);
+        let mut result = match &parser {
+            Parser::Ll1(g, parse_table) => parse(&mut lex, g, parse_table, &mut oc),
+            Parser::Lalr(table) => parse_lalr(&mut lex, table, &mut oc),
+            Parser::RecursiveDescent(parser) => parser.parse(&mut lex, &mut oc),
+        };
 
         if let Some(ref mut ast) = result {
-            let mut result = semantic_analyzer::analyze(ast, &mut oc);
+            // A tree the grammar's semantic actions built wrong is reported before
+            // semantic analysis trips over it
+            let mut result = match semantic_analyzer::analyze(ast, &mut oc) {
+                Ok(result) => result,
+                Err(err) => {
+                    error!("Malformed AST in \"{}\": {}", source_file, err);
+                    continue;
+                }
+            };
             // TODO: Add check if the semantic analysis failed or not
             oc.flush_semantic_messages();
-            if !oc.has_errors() {
-                code_gen::process(&ast, &mut result, &mut oc);
-
-                oc.flush_code();
+            if oc.has_syntax_errors() {
+                error!("Syntax errors have occurred, no code is generated");
+            } else if !oc.has_errors() {
+                match code_gen::process(&ast, &mut result, &mut oc) {
+                    Ok(()) => oc.flush_code(),
+                    Err(err) => error!("No code is generated for \"{}\": {}", source_file, err),
+                }
             } else {
                 error!("Semantic errors have occurred, please check the error files");
             }
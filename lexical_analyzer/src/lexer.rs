@@ -1,7 +1,7 @@
 use crate::double_buffer::{DoubleFixedBuffer, DoubleFixedBufferCursor, BUFFER_SIZE};
 use crate::lexical_rule::{FusedRuleState, LexicalRule, RuleState};
 use crate::token::Token;
-use crate::utilities::is_start_of_codepoint;
+use crate::utilities::{escape_string_content, is_start_of_codepoint};
 use lazy_static::lazy_static;
 use log::{error, trace, warn};
 use regex_automata::DFA;
@@ -12,6 +12,34 @@ use std::io::Read;
 pub struct Lexer {
     rules: Vec<LexicalRule>,
     keywords: Vec<String>,
+    column_unit: ColumnUnit,
+    tab_width: usize,
+}
+
+/// The unit in which token columns are counted
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColumnUnit {
+    /// One column per Unicode scalar value
+    #[default]
+    ScalarValue,
+
+    /// One column per UTF-16 code unit, as expected by the language server protocol
+    Utf16,
+}
+
+impl std::str::FromStr for ColumnUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scalar" => Ok(ColumnUnit::ScalarValue),
+            "utf16" => Ok(ColumnUnit::Utf16),
+            _ => Err(format!(
+                "Unknown column unit \"{}\", should be \"scalar\" or \"utf16\"",
+                s
+            )),
+        }
+    }
 }
 
 /// An iterator over the lexical elements of a file
@@ -24,6 +52,7 @@ pub struct Lex<'a, T: Read> {
     column: usize,
     previous_line: usize,
     previous_column: usize,
+    after_carriage_return: bool,
     lex_error_file: File,
 }
 
@@ -41,6 +70,168 @@ impl From<Token> for LexingError {
     }
 }
 
+impl LexingError {
+    /// A short explanation of why the lexeme was rejected, aimed at someone
+    /// unfamiliar with the lexical rules of the language
+    pub fn help(&self) -> Option<String> {
+        match self {
+            LexingError::LexemeTooLong(_, _) => Some(format!(
+                "lexemes are limited to {} bytes; split it into smaller pieces",
+                BUFFER_SIZE - 1
+            )),
+            LexingError::InvalidCharacter(lexeme, _, _)
+                if lexeme.chars().any(|c| c.is_alphanumeric() && !c.is_ascii()) =>
+            {
+                Some(
+                    "identifiers may only contain ASCII letters, digits and underscores"
+                        .to_string(),
+                )
+            }
+            LexingError::InvalidCharacter(_, _, _) => Some(
+                "this character is not part of the language; remove it or place it in a comment"
+                    .to_string(),
+            ),
+            LexingError::ErrorToken(token_type, lexeme, line, col) => {
+                error_token_help(token_type, lexeme, *line, *col)
+            }
+        }
+    }
+
+    /// A corrected lexeme that would be accepted in place of the erroneous one
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            LexingError::ErrorToken(token_type, lexeme, _, _) => {
+                error_token_suggestion(token_type, lexeme)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Help for the error token classes defined in the lexical rule file
+fn error_token_help(token_type: &str, lexeme: &str, line: usize, col: usize) -> Option<String> {
+    let help = match token_type {
+        "invalidnum" if lexeme.chars().any(|c| c.is_ascii_alphabetic()) => {
+            "identifiers must start with a letter and numbers cannot contain letters"
+        }
+        "invalidnum" => "integers cannot have leading zeros",
+        "invalidfloat" => {
+            let (integral, fraction, exponent) = split_float(lexeme);
+            if exponent == Some("") {
+                "an exponent must follow 'e'"
+            } else if integral.len() > 1 && integral.starts_with('0') {
+                "the integral part of a float cannot have leading zeros"
+            } else if integral == "0" {
+                "a float starting with 0 can only be written 0.0"
+            } else if fraction.len() > 1 && fraction.ends_with('0') {
+                "the fractional part of a float cannot have trailing zeros"
+            } else if exponent.is_some_and(|e| e.trim_start_matches(SIGNS).starts_with('0')) {
+                "the exponent of a float cannot have leading zeros"
+            } else {
+                return None;
+            }
+        }
+        "invalidid" => "identifiers must start with a letter",
+        "invalidstringlit" => {
+            return Some(format!(
+                "the string literal starting at line {} col {} must be closed with '\"' before the end of the line",
+                line, col
+            ))
+        }
+        "invalidstringcontent" => {
+            "string literals may only contain printable ASCII characters and the escapes \\n, \\t, \\\", \\\\ and \\xNN"
+        }
+        "invalidblockcmt" => {
+            return Some(format!(
+                "the block comment starting at line {} col {} is never closed with '*/'",
+                line, col
+            ))
+        }
+        _ => return None,
+    };
+    Some(help.to_string())
+}
+
+/// Fixes for the error token classes defined in the lexical rule file
+fn error_token_suggestion(token_type: &str, lexeme: &str) -> Option<String> {
+    let suggestion = match token_type {
+        "invalidnum" if lexeme.chars().all(|c| c.is_ascii_digit()) => {
+            strip_leading_zeros(lexeme).to_string()
+        }
+        "invalidfloat" => {
+            let (integral, fraction, exponent) = split_float(lexeme);
+            let integral = strip_leading_zeros(integral);
+            let fraction = match fraction.trim_end_matches('0') {
+                "" => "0",
+                trimmed => trimmed,
+            };
+            let exponent = match exponent {
+                // Insert the missing exponent
+                Some("") => "e1".to_string(),
+                Some(exponent) => {
+                    let sign =
+                        &exponent[..exponent.len() - exponent.trim_start_matches(SIGNS).len()];
+                    match exponent.trim_start_matches(SIGNS).trim_start_matches('0') {
+                        "" => "".to_string(),
+                        digits => format!("e{}{}", sign, digits),
+                    }
+                }
+                None => "".to_string(),
+            };
+            format!("{}.{}{}", integral, fraction, exponent)
+        }
+        "invalidid" => lexeme
+            .trim_start_matches(|c: char| c == '_' || c.is_ascii_digit())
+            .to_string(),
+        "invalidstringlit" => format!("{}\"", lexeme),
+        "invalidstringcontent" => format!(
+            "\"{}\"",
+            escape_string_content(&lexeme[1..lexeme.len() - 1])
+        ),
+        "invalidblockcmt" => format!("{}*/", lexeme),
+        _ => return None,
+    };
+
+    if suggestion.is_empty() || suggestion == lexeme {
+        None
+    } else {
+        Some(suggestion)
+    }
+}
+
+/// Name the codepoints of a string in the U+XXXX notation
+fn codepoints(s: &str) -> String {
+    if s.is_empty() {
+        return "invalid UTF-8".to_string();
+    }
+    s.chars()
+        .map(|c| format!("U+{:04X}", c as u32))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const SIGNS: &[char] = &['+', '-'];
+
+/// Split a float lexeme into its integral part, fractional part and exponent
+fn split_float(lexeme: &str) -> (&str, &str, Option<&str>) {
+    let (integral, rest) = match lexeme.find('.') {
+        Some(dot) => (&lexeme[..dot], &lexeme[dot + 1..]),
+        None => (lexeme, ""),
+    };
+    match rest.find('e') {
+        Some(e) => (integral, &rest[..e], Some(&rest[e + 1..])),
+        None => (integral, rest, None),
+    }
+}
+
+/// Remove leading zeros while keeping at least one digit
+fn strip_leading_zeros(digits: &str) -> &str {
+    match digits.trim_start_matches('0') {
+        "" if !digits.is_empty() => "0",
+        trimmed => trimmed,
+    }
+}
+
 impl std::fmt::Display for LexingError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -48,27 +239,71 @@ impl std::fmt::Display for LexingError {
                 f,
                 "Lexical error: lexeme too long: line {} col {}",
                 line, col
-            ),
+            )?,
             LexingError::InvalidCharacter(lexeme, line, col) => write!(
                 f,
-                "Lexical error: Invalid character \"{}\": line {} col {}",
-                lexeme, line, col
-            ),
+                "Lexical error: Invalid character \"{}\" ({}): line {} col {}",
+                lexeme,
+                codepoints(lexeme),
+                line,
+                col
+            )?,
+            // The lexeme of an unterminated comment spans the rest of the file
+            LexingError::ErrorToken(token_type, _, line, col)
+                if token_type == "invalidblockcmt" =>
+            {
+                write!(
+                    f,
+                    "Lexical error: unterminated block comment: line {} col {}",
+                    line, col
+                )?
+            }
             LexingError::ErrorToken(token_type, lexeme, line, col) => write!(
                 f,
                 "Lexical error: {} \"{}\": line {} col {}",
                 token_type, lexeme, line, col
-            ),
+            )?,
+        }
+
+        if let Some(help) = self.help() {
+            write!(f, "\n    help: {}", help)?;
+        }
+        match self {
+            LexingError::ErrorToken(token_type, _, _, _) if token_type == "invalidblockcmt" => {
+                write!(f, "\n    suggestion: close the comment with \"*/\"")
+            }
+            _ => match self.suggestion() {
+                Some(suggestion) => write!(f, "\n    suggestion: replace with \"{}\"", suggestion),
+                None => Ok(()),
+            },
         }
     }
 }
 
 const NEWLINE: u8 = b'\n';
+const CARRIAGE_RETURN: u8 = b'\r';
+const TAB: u8 = b'\t';
 
 impl Lexer {
     pub fn new(rules: Vec<LexicalRule>, keywords: Vec<String>) -> Self {
         assert!(rules.len() != 0, "Lexer must define one or more tokens");
-        Lexer { rules, keywords }
+        Lexer {
+            rules,
+            keywords,
+            column_unit: ColumnUnit::default(),
+            tab_width: 1,
+        }
+    }
+
+    /// Tabs advance the column to the next multiple of the tab width, as an
+    /// editor would display them
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        assert!(tab_width != 0, "Tab width must be at least one column");
+        self.tab_width = tab_width;
+    }
+
+    pub fn set_column_unit(&mut self, column_unit: ColumnUnit) {
+        self.column_unit = column_unit;
     }
 
     pub fn lex(&self, source_path: &str, lex_error_path: &str) -> Lex<File> {
@@ -155,6 +390,7 @@ impl<'a, T: Read> Lex<'a, T> {
             column: 1,
             previous_line: 1,
             previous_column: 1,
+            after_carriage_return: false,
             lex_error_file,
         }
     }
@@ -310,14 +546,136 @@ impl<'a, T: Read> Lex<'a, T> {
     }
 
     /// Advance the line and column index based on the content of the utf-8 byte
+    ///
+    /// \r\n, \n and a lone \r are all a single line break
     fn advance_character_position(&mut self, byte: u8) {
+        let after_carriage_return = self.after_carriage_return;
+        self.after_carriage_return = byte == CARRIAGE_RETURN;
         if is_start_of_codepoint(byte) {
-            if byte == NEWLINE {
+            if byte == NEWLINE && after_carriage_return {
+                // The line break was counted on the \r
+            } else if byte == NEWLINE || byte == CARRIAGE_RETURN {
                 self.line += 1;
                 self.column = 1;
+            } else if byte == TAB {
+                let tab_width = self.lexer.tab_width;
+                self.column = ((self.column - 1) / tab_width + 1) * tab_width + 1;
+            } else if self.lexer.column_unit == ColumnUnit::Utf16 && byte.leading_ones() == 4 {
+                // Four byte sequences lie outside the basic multilingual plane and
+                // are encoded as a surrogate pair
+                self.column += 2;
             } else {
                 self.column += 1;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexical_rule::LexicalRule;
+
+    /// The lexeme, line and column of each token of a source
+    fn positions(
+        source: &str,
+        tab_width: usize,
+        column_unit: ColumnUnit,
+    ) -> Vec<(String, usize, usize)> {
+        let rules = LexicalRule::from_file("../resources/lex_tokens.txt").unwrap();
+        let mut lexer = Lexer::new(rules, Vec::new());
+        lexer.set_tab_width(tab_width);
+        lexer.set_column_unit(column_unit);
+        let lex_error_path = std::env::temp_dir().join("paquet_lexer_test.outlexerrors");
+        let lex_error_file = File::create(lex_error_path).unwrap();
+        Lex::new(&lexer, source.as_bytes(), lex_error_file)
+            .map(|token| (token.lexeme, token.line, token.column))
+            .collect()
+    }
+
+    fn suggest(token_type: &str, lexeme: &str) -> Option<String> {
+        LexingError::ErrorToken(token_type.to_string(), lexeme.to_string(), 1, 1).suggestion()
+    }
+
+    #[test]
+    fn error_token_suggestions() {
+        assert_eq!(suggest("invalidnum", "007"), Some("7".to_string()));
+        assert_eq!(suggest("invalidnum", "00"), Some("0".to_string()));
+        assert_eq!(suggest("invalidnum", "12abc"), None);
+        assert_eq!(suggest("invalidfloat", "01.5"), Some("1.5".to_string()));
+        assert_eq!(suggest("invalidfloat", "1.5e"), Some("1.5e1".to_string()));
+        assert_eq!(suggest("invalidfloat", "1.00"), Some("1.0".to_string()));
+        assert_eq!(
+            suggest("invalidfloat", "1.5e-007"),
+            Some("1.5e-7".to_string())
+        );
+        assert_eq!(suggest("invalidid", "_abc"), Some("abc".to_string()));
+        assert_eq!(
+            suggest("invalidstringlit", "\"abc"),
+            Some("\"abc\"".to_string())
+        );
+        assert_eq!(
+            suggest("invalidstringcontent", "\"a\tb\\q\""),
+            Some("\"a\\tb\\\\q\"".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_character_names_codepoint() {
+        let err = LexingError::InvalidCharacter("é".to_string(), 4, 6);
+        assert!(err.to_string().starts_with(
+            "Lexical error: Invalid character \"é\" (U+00E9): line 4 col 6\n    help: identifiers"
+        ));
+    }
+
+    #[test]
+    fn columns_after_character_outside_basic_plane() {
+        let source = "a /* \u{1F600} */ b";
+        assert_eq!(
+            positions(source, 1, ColumnUnit::ScalarValue),
+            vec![("a".to_string(), 1, 1), ("b".to_string(), 1, 11)]
+        );
+        assert_eq!(
+            positions(source, 1, ColumnUnit::Utf16),
+            vec![("a".to_string(), 1, 1), ("b".to_string(), 1, 12)]
+        );
+    }
+
+    #[test]
+    fn columns_after_tabs() {
+        let source = "a\tb\t\tc\n\td";
+        assert_eq!(
+            positions(source, 1, ColumnUnit::ScalarValue),
+            vec![
+                ("a".to_string(), 1, 1),
+                ("b".to_string(), 1, 3),
+                ("c".to_string(), 1, 6),
+                ("d".to_string(), 2, 2)
+            ]
+        );
+        assert_eq!(
+            positions(source, 4, ColumnUnit::ScalarValue),
+            vec![
+                ("a".to_string(), 1, 1),
+                ("b".to_string(), 1, 5),
+                ("c".to_string(), 1, 13),
+                ("d".to_string(), 2, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn line_breaks() {
+        for tab_width in &[1, 4] {
+            assert_eq!(
+                positions("a\r\nb\rc\n\r\n\td", *tab_width, ColumnUnit::ScalarValue),
+                vec![
+                    ("a".to_string(), 1, 1),
+                    ("b".to_string(), 2, 1),
+                    ("c".to_string(), 3, 1),
+                    ("d".to_string(), 5, 1 + tab_width)
+                ]
+            );
+        }
+    }
+}
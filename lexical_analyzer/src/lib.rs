@@ -6,6 +6,7 @@ mod utilities;
 pub mod lexer;
 pub mod lexical_rule;
 
+pub use lexer::ColumnUnit;
 pub use lexer::Lex;
 pub use lexer::Lexer;
 pub use token::Token;
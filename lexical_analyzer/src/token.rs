@@ -1,4 +1,5 @@
 use crate::double_buffer::{DoubleFixedBuffer, DoubleFixedBufferCursor};
+use crate::utilities::unescape_string_literal;
 use std::io::Read;
 
 const IDENTIFIER: &str = "id";
@@ -40,6 +41,12 @@ impl Token {
             column,
         }
     }
+
+    /// The decoded value of a string literal token, without its quotes and
+    /// with its escape sequences replaced
+    pub fn string_value(&self) -> Option<String> {
+        unescape_string_literal(&self.lexeme)
+    }
 }
 
 impl std::fmt::Display for Token {
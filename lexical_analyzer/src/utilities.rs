@@ -1,3 +1,126 @@
 pub fn is_start_of_codepoint(byte: u8) -> bool {
     byte.leading_ones() == 0 || byte.leading_ones() > 1
 }
+
+/// Decode the escape sequences of a quoted string literal lexeme
+///
+/// The surrounding quotes are removed. The \xNN escapes are bytes of the UTF-8
+/// encoding of the string, like the ones written by `escape_string_content`.
+/// None is returned if the lexeme is not quoted, contains an unknown escape
+/// sequence or its bytes aren't valid UTF-8.
+pub fn unescape_string_literal(lexeme: &str) -> Option<String> {
+    let content = lexeme.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = Vec::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        let decoded = match c {
+            '\\' => chars.next()?,
+            _ => {
+                let mut bytes = [0; 4];
+                result.extend_from_slice(c.encode_utf8(&mut bytes).as_bytes());
+                continue;
+            }
+        };
+
+        match decoded {
+            'n' => result.push(b'\n'),
+            't' => result.push(b'\t'),
+            '"' => result.push(b'"'),
+            '\\' => result.push(b'\\'),
+            'x' => {
+                let digits: String = chars.by_ref().take(2).collect();
+                if digits.len() != 2 {
+                    return None;
+                }
+                result.push(u8::from_str_radix(&digits, 16).ok()?);
+            }
+            _ => return None,
+        }
+    }
+    String::from_utf8(result).ok()
+}
+
+/// Rewrite the content of a string literal so it only uses printable ASCII
+/// characters and valid escape sequences
+///
+/// Unknown escape sequences have their backslash escaped and other characters
+/// are replaced by the \xNN escape of their UTF-8 bytes.
+pub fn escape_string_content(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek().copied() {
+                Some(escape @ ('n' | 't' | '"' | '\\')) => {
+                    chars.next();
+                    result.push(c);
+                    result.push(escape);
+                }
+                Some('x')
+                    if chars
+                        .clone()
+                        .skip(1)
+                        .take(2)
+                        .filter(char::is_ascii_hexdigit)
+                        .count()
+                        == 2 =>
+                {
+                    result.push(c)
+                }
+                _ => result.push_str("\\\\"),
+            },
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            ' '..='~' => result.push(c),
+            _ => {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    result.push_str(&format!("\\x{:02X}", byte));
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_literal_escapes() {
+        assert_eq!(
+            unescape_string_literal(r#""a\tb\n\"c\" \\ \x41""#),
+            Some("a\tb\n\"c\" \\ A".to_string())
+        );
+        assert_eq!(unescape_string_literal(r#""\q""#), None);
+        assert_eq!(unescape_string_literal(r#""\x4""#), None);
+        // A lone byte of a multi-byte character isn't valid UTF-8
+        assert_eq!(unescape_string_literal(r#""\xE9""#), None);
+        assert_eq!(unescape_string_literal("abc"), None);
+    }
+
+    #[test]
+    fn string_content_escapes() {
+        assert_eq!(escape_string_content("a\tb\nc"), "a\\tb\\nc");
+        assert_eq!(escape_string_content(r"\q \x41 \x4"), r"\\q \x41 \\x4");
+        assert_eq!(escape_string_content("é"), r"\xC3\xA9");
+        let escaped = escape_string_content("tab\t\"quoted\" \\");
+        assert_eq!(
+            unescape_string_literal(&format!("\"{}\"", escaped)),
+            Some("tab\t\"quoted\" \\".to_string())
+        );
+    }
+
+    #[test]
+    fn string_content_round_trip() {
+        for content in ["é", "naïve café", "€ and 𝄞"] {
+            let escaped = escape_string_content(content);
+            assert!(escaped.chars().all(|c| (' '..='~').contains(&c)));
+            assert_eq!(
+                unescape_string_literal(&format!("\"{}\"", escaped)),
+                Some(content.to_string())
+            );
+        }
+    }
+}
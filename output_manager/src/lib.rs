@@ -1,17 +1,27 @@
 use log::{error, info, warn};
 use path;
 use std::cmp;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 
 const SYMBOL_TABLE_EXT: &str = "outsymboltable";
+const SYMBOL_TABLE_JSON_EXT: &str = "outsymboltable.json";
+const SYMBOL_TABLE_DOT_EXT: &str = "outsymboltable.dot";
+const XREF_EXT: &str = "outxref";
 const SEMANTIC_ERROR_EXT: &str = "outsemanticerrors";
 const DERIVATION_EXT: &str = "outderivation";
 const AST_EXT: &str = "outast";
+const PARSE_TREE_EXT: &str = "outparsetree";
 const PARSE_ERROR_EXT: &str = "outsyntaxerrors";
 const LEX_ERROR_EXT: &str = "outlexerrors";
+const IR_EXT: &str = "outir";
+const IR_DOT_EXT: &str = "outir.dot";
 const CODE_EXT: &str = "moon";
 
+/// Start of the semantic messages reporting errors rather than warnings
+const SEMANTIC_ERROR_PREFIX: &str = "Semantic error";
+
 pub struct ErrorMessage {
     line: usize,
     column: usize,
@@ -66,25 +76,174 @@ impl PartialEq for ErrorMessage {
 
 impl Eq for ErrorMessage {}
 
+/// Contents of the derivation file
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DerivationMode {
+    /// The tokens, productions and symbol stacks as the parser processes them
+    #[default]
+    Trace,
+
+    /// The sentential forms of the leftmost derivation, one per line
+    Leftmost,
+}
+
+impl std::str::FromStr for DerivationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trace" => Ok(DerivationMode::Trace),
+            "leftmost" => Ok(DerivationMode::Leftmost),
+            _ => Err(format!(
+                "Unknown derivation mode \"{}\", should be \"trace\" or \"leftmost\"",
+                s
+            )),
+        }
+    }
+}
+
+/// Optimizations of the generated code
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OptimizationLevel {
+    /// The code computes each expression as written
+    #[default]
+    None,
+
+    /// The constant expressions are computed when compiling, and the values of the locals
+    /// known when compiling are used in their place
+    Constants,
+}
+
+impl std::str::FromStr for OptimizationLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(OptimizationLevel::None),
+            "1" => Ok(OptimizationLevel::Constants),
+            _ => Err(format!(
+                "Unknown optimization level \"{}\", should be \"0\" or \"1\"",
+                s
+            )),
+        }
+    }
+}
+
+/// Checks of the semantic analysis reporting code that is legal but likely a mistake
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// A local variable is never used
+    UnusedVariable,
+
+    /// A parameter is never used by the function
+    UnusedParameter,
+
+    /// A private data member is never used or a private member function never called
+    UnusedPrivate,
+
+    /// A function is never called, directly or not, from the main function
+    UncalledFunction,
+
+    /// A variable is assigned but its value is never read
+    UnreadVariable,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedVariable,
+        Lint::UnusedParameter,
+        Lint::UnusedPrivate,
+        Lint::UncalledFunction,
+        Lint::UnreadVariable,
+    ];
+
+    /// The code naming the lint on the command line and in its messages
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnusedParameter => "unused-parameter",
+            Lint::UnusedPrivate => "unused-private",
+            Lint::UncalledFunction => "uncalled-function",
+            Lint::UnreadVariable => "unread-variable",
+        }
+    }
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl std::str::FromStr for Lint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Lint::ALL.iter().find(|lint| lint.code() == s) {
+            Some(lint) => Ok(*lint),
+            None => Err(format!("Unknown lint \"{}\"", s)),
+        }
+    }
+}
+
+/// How the findings of a lint are reported
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LintLevel {
+    /// Not reported
+    Allow,
+
+    /// Reported as warnings, code is still generated
+    #[default]
+    Warn,
+
+    /// Reported as errors, preventing the code generation
+    Deny,
+}
+
 pub struct OutputConfig {
     pub code_path: String,
     pub code_exec: Vec<String>,
     pub code_data: Vec<String>,
     pub code_file: File,
+    pub optimization_level: OptimizationLevel,
+
+    /// The three-address code the MOON code is generated from, as text and as a graph
+    pub ir_path: String,
+    pub ir_file: File,
+    pub ir_dot_path: String,
+    pub ir_dot_file: File,
 
     pub symbol_table_path: String,
     pub symbol_table_file: File,
 
+    /// The symbol table serialized for tools, and rendered as a graph
+    pub symbol_table_json_path: String,
+    pub symbol_table_json_file: File,
+    pub symbol_table_dot_path: String,
+    pub symbol_table_dot_file: File,
+
+    /// Where each symbol is defined and referenced
+    pub xref_path: String,
+    pub xref_file: File,
+
     pub semantic_error_path: String,
     pub semantic_error_file: File,
     pub semantic_error_buffer: Vec<ErrorMessage>,
 
+    /// The level of the lints set on the command line, the others warn
+    pub lint_levels: HashMap<Lint, LintLevel>,
+
     pub derivation_path: String,
     pub derivation_file: File,
+    pub derivation_mode: DerivationMode,
 
     pub ast_path: String,
     pub ast_file: File,
 
+    /// The parse tree is only written when requested, see `enable_parse_tree`
+    pub parse_tree_path: String,
+    pub parse_tree_file: Option<File>,
+
     pub syntax_error_path: String,
     pub syntax_error_file: File,
 
@@ -154,15 +313,27 @@ impl OutputConfig {
         let output_no_ext = path::replace_extension(&output, "").unwrap();
 
         let code_path = path::replace_extension(&output, CODE_EXT).unwrap();
+        let ir_path = path::replace_extension(&output, IR_EXT).unwrap();
+        let ir_dot_path = path::replace_extension(&output, IR_DOT_EXT).unwrap();
         let symbol_table_path = path::replace_extension(&output, SYMBOL_TABLE_EXT).unwrap();
+        let symbol_table_json_path =
+            path::replace_extension(&output, SYMBOL_TABLE_JSON_EXT).unwrap();
+        let symbol_table_dot_path = path::replace_extension(&output, SYMBOL_TABLE_DOT_EXT).unwrap();
+        let xref_path = path::replace_extension(&output, XREF_EXT).unwrap();
         let semantic_error_path = path::replace_extension(&output, SEMANTIC_ERROR_EXT).unwrap();
         let derivation_path = path::replace_extension(&output, DERIVATION_EXT).unwrap();
         let ast_path = path::replace_extension(&output, AST_EXT).unwrap();
+        let parse_tree_path = path::replace_extension(&output, PARSE_TREE_EXT).unwrap();
         let syntax_error_path = path::replace_extension(&output, PARSE_ERROR_EXT).unwrap();
         let lex_error_path = path::replace_extension(&output, LEX_ERROR_EXT).unwrap();
 
         let code_file = panic_open(&code_path);
+        let ir_file = panic_open(&ir_path);
+        let ir_dot_file = panic_open(&ir_dot_path);
         let symbol_table_file = panic_open(&symbol_table_path);
+        let symbol_table_json_file = panic_open(&symbol_table_json_path);
+        let symbol_table_dot_file = panic_open(&symbol_table_dot_path);
+        let xref_file = panic_open(&xref_path);
         let semantic_error_file = panic_open(&semantic_error_path);
         let derivation_file = panic_open(&derivation_path);
         let ast_file = panic_open(&ast_path);
@@ -180,21 +351,41 @@ impl OutputConfig {
             code_file,
             code_exec: Vec::new(),
             code_data: Vec::new(),
+            optimization_level: OptimizationLevel::default(),
+            ir_file,
+            ir_path,
+            ir_dot_file,
+            ir_dot_path,
             symbol_table_file,
             symbol_table_path,
+            symbol_table_json_file,
+            symbol_table_json_path,
+            symbol_table_dot_file,
+            symbol_table_dot_path,
+            xref_file,
+            xref_path,
             semantic_error_file,
             semantic_error_path,
             semantic_error_buffer: Vec::new(),
+            lint_levels: HashMap::new(),
             derivation_file,
             derivation_path,
+            derivation_mode: DerivationMode::default(),
             ast_file,
             ast_path,
+            parse_tree_path,
+            parse_tree_file: None,
             syntax_error_file,
             syntax_error_path,
             lex_error_path,
         }
     }
 
+    /// Write the concrete parse tree of the source file next to its AST
+    pub fn enable_parse_tree(&mut self) {
+        self.parse_tree_file = Some(panic_open(&self.parse_tree_path));
+    }
+
     pub fn flush_semantic_messages(&mut self) {
         self.semantic_error_buffer.sort();
         for message in &self.semantic_error_buffer {
@@ -206,6 +397,10 @@ impl OutputConfig {
         }
     }
 
+    pub fn lint_level(&self, lint: Lint) -> LintLevel {
+        self.lint_levels.get(&lint).copied().unwrap_or_default()
+    }
+
     pub fn add(&mut self, message: &str, line: usize, column: usize) {
         self.semantic_error_buffer
             .push(ErrorMessage::new(line, column, message))
@@ -229,12 +424,24 @@ impl OutputConfig {
         }
     }
 
-    pub fn has_errors(&self) -> bool {
-        for entry in &self.semantic_error_buffer {
-            if let Some(position) = entry.message().find("error") {
-                return true;
+    /// Whether the parser reported any syntax error for the source file
+    pub fn has_syntax_errors(&self) -> bool {
+        match self.syntax_error_file.metadata() {
+            Ok(metadata) => metadata.len() > 0,
+            Err(err) => {
+                warn!(
+                    "Failed to read the size of \"{}\": {}",
+                    self.syntax_error_path, err
+                );
+                false
             }
         }
-        return false;
+    }
+
+    pub fn has_errors(&self) -> bool {
+        // The warnings quote identifiers, which may contain the word
+        self.semantic_error_buffer
+            .iter()
+            .any(|entry| entry.message().starts_with(SEMANTIC_ERROR_PREFIX))
     }
 }
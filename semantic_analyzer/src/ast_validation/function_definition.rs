@@ -129,6 +129,26 @@ impl<'a> FunctionDefinition<'a> {
             None => (self.id, None),
         }
     }
+
+    /// Find the function of the symbol table defined, member functions are found in the table
+    /// of their class
+    pub fn find_function<'b>(&self, global_table: &'b SymbolTable) -> Option<&'b Function> {
+        let (id, scope) = self.get_corrected_scoped_id();
+        let function_table = match scope {
+            Some(scope) => match global_table.get(scope) {
+                Some(SymbolTableEntry::Class(class)) => class.symbol_table(),
+                _ => return None,
+            },
+            None => global_table,
+        };
+        function_table
+            .get_all(id)
+            .into_iter()
+            .find_map(|entry| match entry {
+                SymbolTableEntry::Function(function) if self == function => Some(function),
+                _ => None,
+            })
+    }
 }
 
 impl<'a> ToSymbol for FunctionDefinition<'a> {
@@ -0,0 +1,390 @@
+//! Build the control flow graph of each function body
+//!
+//! The statements run one after the other are grouped in basic blocks, which end by jumping to the
+//! next block, branching on the condition of an ifStat or whileStat, returning, or reaching the
+//! end of the body. The graphs are kept in the analysis results, after reporting the functions
+//! that may reach the end of their body without returning a value and the statements that can't
+//! be reached.
+
+use crate::SemanticAnalysisResults;
+use crate::SemanticError;
+use crate::{Function, SymbolTable, SymbolTableEntry};
+use ast::typed::{FuncBody, FuncDef, Position, Program, Stmt};
+use derive_getters::Getters;
+use output_manager::OutputConfig;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A statement run by a block, the ifStat and whileStat are the branches ending the blocks
+///
+/// The kind is the name of the statement's node in the AST.
+#[derive(Debug, Clone, Getters)]
+pub struct Statement {
+    kind: String,
+    position: Position,
+}
+
+/// How the control leaves a block
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Goto(usize),
+    /// The condition of an ifStat or whileStat is evaluated, to go to the first block when true
+    Branch {
+        condition: Position,
+        then: usize,
+        otherwise: usize,
+    },
+    Return,
+    /// The end of the function body is reached
+    End,
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<usize> {
+        match self {
+            Terminator::Goto(target) => vec![*target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![*then, *otherwise],
+            Terminator::Return | Terminator::End => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Terminator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Terminator::Goto(target) => write!(f, "goto {}", target),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => write!(
+                f,
+                "branch on {}:{} to {} else {}",
+                condition.line, condition.column, then, otherwise
+            ),
+            Terminator::Return => write!(f, "return"),
+            Terminator::End => write!(f, "end"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Getters)]
+pub struct BasicBlock {
+    statements: Vec<Statement>,
+    terminator: Terminator,
+}
+
+/// The basic blocks of a function body, the first is entered when the function is called
+#[derive(Debug, Clone, Getters)]
+pub struct ControlFlowGraph {
+    blocks: Vec<BasicBlock>,
+}
+
+impl fmt::Display for ControlFlowGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, block) in self.blocks.iter().enumerate() {
+            writeln!(f, "block {}:", index)?;
+            for statement in &block.statements {
+                writeln!(
+                    f,
+                    "    {} at {}:{}",
+                    statement.kind, statement.position.line, statement.position.column
+                )?;
+            }
+            writeln!(f, "    {}", block.terminator)?;
+        }
+        Ok(())
+    }
+}
+
+impl ControlFlowGraph {
+    /// Whether each block can be reached from the first
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut pending = vec![0];
+        while let Some(index) = pending.pop() {
+            if !reachable[index] {
+                reachable[index] = true;
+                pending.extend(self.blocks[index].terminator.successors());
+            }
+        }
+        reachable
+    }
+
+    /// Whether the end of the function body can be reached without returning
+    pub fn may_reach_end(&self) -> bool {
+        self.reachable()
+            .iter()
+            .zip(&self.blocks)
+            .any(|(reachable, block)| *reachable && block.terminator == Terminator::End)
+    }
+}
+
+struct Builder {
+    blocks: Vec<BasicBlock>,
+    /// The block the next statement is added to, None after a return, break or continue
+    current: Option<usize>,
+    /// The condition block and the block following each enclosing loop, innermost last
+    loops: Vec<(usize, usize)>,
+    /// The first statement of each sequence that can't be reached
+    unreachable: Vec<Position>,
+}
+
+impl Builder {
+    fn new_block(&mut self) -> usize {
+        self.blocks.push(BasicBlock {
+            statements: Vec::new(),
+            terminator: Terminator::End,
+        });
+        self.blocks.len() - 1
+    }
+
+    fn end_block(&mut self, terminator: Terminator) {
+        if let Some(current) = self.current.take() {
+            self.blocks[current].terminator = terminator;
+        }
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        let position = statement.position();
+        let current = match self.current {
+            Some(current) => current,
+            None => {
+                self.unreachable.push(position);
+                let block = self.new_block();
+                self.current = Some(block);
+                block
+            }
+        };
+
+        match statement {
+            Stmt::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                let then = self.new_block();
+                let otherwise = self.new_block();
+                self.end_block(Terminator::Branch {
+                    condition: condition.position(),
+                    then,
+                    otherwise,
+                });
+                self.current = Some(then);
+                self.statements(then_block);
+                let then_end = self.current.take();
+                self.current = Some(otherwise);
+                self.statements(else_block);
+                let otherwise_end = self.current.take();
+
+                // Both branches may return, leaving nothing to join
+                if then_end.is_some() || otherwise_end.is_some() {
+                    let join = self.new_block();
+                    for end in then_end.iter().chain(&otherwise_end) {
+                        self.blocks[*end].terminator = Terminator::Goto(join);
+                    }
+                    self.current = Some(join);
+                }
+            }
+            Stmt::While {
+                condition, body, ..
+            } => {
+                let condition_block = self.new_block();
+                let body_block = self.new_block();
+                let after = self.new_block();
+                self.end_block(Terminator::Goto(condition_block));
+                self.blocks[condition_block].terminator = Terminator::Branch {
+                    condition: condition.position(),
+                    then: body_block,
+                    otherwise: after,
+                };
+
+                self.loops.push((condition_block, after));
+                self.current = Some(body_block);
+                self.statements(body);
+                self.end_block(Terminator::Goto(condition_block));
+                self.loops.pop();
+                self.current = Some(after);
+            }
+            _ => {
+                self.blocks[current].statements.push(Statement {
+                    kind: kind(statement).to_owned(),
+                    position,
+                });
+                match (statement, self.loops.last()) {
+                    (Stmt::Return { .. }, _) => self.end_block(Terminator::Return),
+                    (Stmt::Break(_), Some(&(_, after))) => self.end_block(Terminator::Goto(after)),
+                    (Stmt::Continue(_), Some(&(condition, _))) => {
+                        self.end_block(Terminator::Goto(condition))
+                    }
+                    // A break or continue outside of a loop doesn't change the flow
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+/// The name of the AST node of a statement
+fn kind(statement: &Stmt) -> &'static str {
+    match statement {
+        Stmt::Assign { .. } => "assignOp",
+        Stmt::Call(_) => "var",
+        Stmt::If { .. } => "ifStat",
+        Stmt::While { .. } => "whileStat",
+        Stmt::Read { .. } => "readStat",
+        Stmt::Write { .. } => "writeStat",
+        Stmt::Return { .. } => "returnStat",
+        Stmt::Break(_) => "breakStat",
+        Stmt::Continue(_) => "continueStat",
+    }
+}
+
+pub fn process(
+    program: &Program,
+    current_results: &mut SemanticAnalysisResults,
+    output: &mut OutputConfig,
+) {
+    let global_table = &current_results.symbol_table;
+    let mut graphs = HashMap::new();
+
+    for definition in &program.functions {
+        // Definitions matching no declaration were reported while assembling the symbol table
+        if let Some(function) = defined_function(definition, global_table) {
+            let graph = function_body(&definition.body, output);
+            if graph.may_reach_end() && function.return_type().is_some() {
+                // Where the qualified name of the function starts
+                let position = definition
+                    .scope
+                    .as_ref()
+                    .unwrap_or(&definition.name)
+                    .position;
+                let err = SemanticError::new_missing_return(
+                    &position.line,
+                    &position.column,
+                    &function.qualified_name(),
+                );
+                output.add(&err.to_string(), err.line(), err.col());
+            }
+            graphs.insert(function.qualified_name(), graph);
+        }
+    }
+
+    // The main function returns no value
+    if let Some(SymbolTableEntry::Function(main)) = global_table.get("main") {
+        graphs.insert(main.qualified_name(), function_body(&program.main, output));
+    }
+
+    current_results.control_flow_graphs = graphs;
+}
+
+/// The function of the symbol table a definition defines, member functions are found in the
+/// table of their class
+fn defined_function<'a>(
+    definition: &FuncDef,
+    global_table: &'a SymbolTable,
+) -> Option<&'a Function> {
+    let function_table = match &definition.scope {
+        Some(class) => match global_table.get(&class.name) {
+            Some(SymbolTableEntry::Class(class)) => class.symbol_table(),
+            _ => return None,
+        },
+        None => global_table,
+    };
+    function_table
+        .get_all(&definition.name.name)
+        .into_iter()
+        .find_map(|entry| match entry {
+            SymbolTableEntry::Function(function)
+                if function.parameter_types().len() == definition.params.len()
+                    && function
+                        .parameter_types()
+                        .iter()
+                        .zip(&definition.params)
+                        .all(|(parameter, param)| {
+                            parameter.data_type() == param.param_type.name()
+                        }) =>
+            {
+                Some(function)
+            }
+            _ => None,
+        })
+}
+
+/// Build the graph of a function body, reporting the statements that can't be reached
+fn function_body(body: &FuncBody, output: &mut OutputConfig) -> ControlFlowGraph {
+    let mut builder = Builder {
+        blocks: Vec::new(),
+        current: None,
+        loops: Vec::new(),
+        unreachable: Vec::new(),
+    };
+    builder.current = Some(builder.new_block());
+    builder.statements(&body.statements);
+
+    for position in &builder.unreachable {
+        let err = SemanticError::new_unreachable_statement(&position.line, &position.column);
+        output.add(&err.to_string(), err.line(), err.col());
+    }
+
+    ControlFlowGraph {
+        blocks: builder.blocks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic_analyzer::tests::analyze_source;
+
+    #[test]
+    fn test_missing_return_and_unreachable_statements() {
+        let messages = analyze_source(
+            "control_flow.src",
+            r#"
+func sign(integer n) : integer
+{
+  if (n > 0) then
+  {
+    return (1);
+    write(n);
+  }
+  else
+  {
+  };
+}
+
+func clamp(integer n) : integer
+{
+  while (n > 10)
+  {
+    break;
+    n = n - 1;
+  };
+  return (n);
+}
+
+main
+{
+  write(sign(2) + clamp(3));
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic error: 2:6 Function \"sign(integer)\" may reach its end without returning a value",
+                "Semantic warning: 7:10 Statement can't be reached",
+                "Semantic warning: 19:5 Statement can't be reached",
+            ]
+        );
+    }
+}
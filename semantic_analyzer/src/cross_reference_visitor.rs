@@ -0,0 +1,442 @@
+//! Index where each symbol of the symbol table is defined and where it is referenced
+//!
+//! The references are found in the type checked AST, which records the member accessed or the
+//! overload called by each variable element. Only the symbols declared in the program are indexed,
+//! the literals and temporaries are introduced by the analysis itself.
+
+use crate::ast_validation::{FunctionDefinition, ViewAs};
+use crate::SemanticAnalysisResults;
+use crate::{Function, SymbolTable, SymbolTableEntry};
+use ast::typed::Position;
+use ast::{Data, Node};
+use derive_getters::Getters;
+use output_manager::OutputConfig;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How a reference uses its symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Usage {
+    /// The value of a variable is read, or an object is used
+    Read,
+    /// A variable is assigned or read into
+    Write,
+    /// A function is called
+    Call,
+    /// A class is the type of a variable, is inherited or scopes a member function definition
+    Type,
+    /// A member function definition names its declaration and the parameters of it again
+    Definition,
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Usage::Read => write!(f, "read"),
+            Usage::Write => write!(f, "written"),
+            Usage::Call => write!(f, "called"),
+            Usage::Type => write!(f, "named as a type"),
+            Usage::Definition => write!(f, "named by a definition"),
+        }
+    }
+}
+
+/// A use of the name of a symbol, inside the body of the function if any
+#[derive(Debug, Clone, Getters)]
+pub struct Reference {
+    position: Position,
+    usage: Usage,
+    function: Option<String>,
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}:{}",
+            self.usage, self.position.line, self.position.column
+        )?;
+        if let Some(function) = &self.function {
+            write!(f, " in {}", function)?;
+        }
+        Ok(())
+    }
+}
+
+/// A symbol, named after its enclosing class or function, with the position of its declaration
+/// and of each use of its name
+#[derive(Debug, Clone, Getters)]
+pub struct CrossReference {
+    symbol: String,
+    kind: String,
+    definition: Position,
+    references: Vec<Reference>,
+}
+
+impl fmt::Display for CrossReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} {}, defined at {}:{}",
+            self.kind, self.symbol, self.definition.line, self.definition.column
+        )?;
+        if self.references.is_empty() {
+            writeln!(f, "    never referenced")?;
+        }
+        for reference in &self.references {
+            writeln!(f, "    {}", reference)?;
+        }
+        Ok(())
+    }
+}
+
+impl CrossReference {
+    /// Whether any reference uses the symbol in one of the ways supplied
+    pub fn is_used_as(&self, usages: &[Usage]) -> bool {
+        self.references
+            .iter()
+            .any(|reference| usages.contains(&reference.usage))
+    }
+}
+
+/// The cross references of every symbol, in the order of the symbol table
+#[derive(Debug, Clone, Default)]
+pub struct CrossReferences {
+    entries: Vec<CrossReference>,
+    symbols: HashMap<String, usize>,
+}
+
+impl fmt::Display for CrossReferences {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl CrossReferences {
+    pub fn entries(&self) -> &[CrossReference] {
+        &self.entries
+    }
+
+    /// Find a symbol by its qualified name, such as "LINEAR::evaluate(float)::x"
+    pub fn get(&self, symbol: &str) -> Option<&CrossReference> {
+        self.symbols.get(symbol).map(|&index| &self.entries[index])
+    }
+
+    /// Find the symbol defined or referenced at a position
+    pub fn at(&self, line: usize, column: usize) -> Option<&CrossReference> {
+        let position = Position { line, column };
+        self.entries.iter().find(|entry| {
+            entry.definition == position
+                || entry
+                    .references
+                    .iter()
+                    .any(|reference| reference.position == position)
+        })
+    }
+
+    fn define(&mut self, symbol: String, kind: &str, line: usize, column: usize) {
+        // Symbols declared twice were reported while assembling the symbol table
+        if self.symbols.contains_key(&symbol) {
+            return;
+        }
+        self.symbols.insert(symbol.clone(), self.entries.len());
+        self.entries.push(CrossReference {
+            symbol,
+            kind: kind.to_owned(),
+            definition: Position { line, column },
+            references: Vec::new(),
+        });
+    }
+
+    /// Add a reference to the symbol at the position of the node, unknown symbols were reported
+    /// by the type check
+    fn refer(&mut self, symbol: &str, node: &Node, usage: Usage, function: Option<&Function>) {
+        if let Some(&index) = self.symbols.get(symbol) {
+            self.entries[index].references.push(Reference {
+                position: Position {
+                    line: *node.line(),
+                    column: *node.column(),
+                },
+                usage,
+                function: function.map(|function| function.qualified_name()),
+            });
+        }
+    }
+}
+
+pub fn process(
+    node: &Node,
+    current_results: &mut SemanticAnalysisResults,
+    _output: &mut OutputConfig,
+) {
+    let global_table = &current_results.symbol_table;
+    let mut index = CrossReferences::default();
+    define_table(global_table, None, &mut index);
+    visit(node, None, global_table, &mut index);
+    current_results.cross_references = index;
+}
+
+/// Qualify the name of a symbol by the class or function enclosing it
+pub fn qualify(scope: Option<&str>, id: &str) -> String {
+    match scope {
+        Some(scope) => format!("{}::{}", scope, id),
+        None => id.to_owned(),
+    }
+}
+
+fn define_table(table: &SymbolTable, scope: Option<&str>, index: &mut CrossReferences) {
+    for entry in &table.values {
+        match entry {
+            SymbolTableEntry::Class(class) => {
+                index.define(class.id().clone(), "class", *class.line(), *class.column());
+                define_table(class.symbol_table(), Some(class.id()), index);
+            }
+            SymbolTableEntry::Function(function) => {
+                let name = function.qualified_name();
+                index.define(
+                    name.clone(),
+                    "function",
+                    *function.line(),
+                    *function.column(),
+                );
+                define_table(function.symbol_table(), Some(&name), index);
+            }
+            SymbolTableEntry::Data(data) => {
+                let name = qualify(scope, data.id());
+                index.define(name, "data", *data.line(), *data.column());
+            }
+            SymbolTableEntry::Param(param) => {
+                let name = qualify(scope, param.id());
+                index.define(name, "param", *param.line(), *param.column());
+            }
+            SymbolTableEntry::Local(local) => {
+                let name = qualify(scope, local.id());
+                index.define(name, "local", *local.line(), *local.column());
+            }
+            SymbolTableEntry::Inherit(_)
+            | SymbolTableEntry::Literal(_)
+            | SymbolTableEntry::Temporary(_) => (),
+        }
+    }
+}
+
+/// Visit the node in the context of the function whose body encloses it, if any
+fn visit(
+    node: &Node,
+    function: Option<&Function>,
+    global_table: &SymbolTable,
+    index: &mut CrossReferences,
+) {
+    match node.name().as_str() {
+        "prog" => prog(node, global_table, index),
+        "funcDef" => func_def(node, global_table, index),
+        "assignOp" => {
+            let children = get_children(node);
+            var(&children[0], Usage::Write, function, global_table, index);
+            visit(&children[1], function, global_table, index);
+        }
+        "readStat" => {
+            for child in get_children(node) {
+                var(child, Usage::Write, function, global_table, index);
+            }
+        }
+        "var" => var(node, Usage::Read, function, global_table, index),
+        "dataMember" | "fCall" => element(node, false, Usage::Read, function, global_table, index),
+        "type" => type_node(node, function, global_table, index),
+        // The classes inherited are referenced by their ids
+        "inherList" => {
+            for id in get_children(node) {
+                type_node(id, function, global_table, index);
+            }
+        }
+        _ => {
+            for child in get_children(node) {
+                visit(child, function, global_table, index);
+            }
+        }
+    }
+}
+
+fn get_children(node: &Node) -> &[Node] {
+    match node.data() {
+        Data::Children(children) => children,
+        _ => &[],
+    }
+}
+
+fn prog(node: &Node, global_table: &SymbolTable, index: &mut CrossReferences) {
+    let children = get_children(node);
+    visit(&children[0], None, global_table, index);
+    visit(&children[1], None, global_table, index);
+
+    let main = match global_table.get("main") {
+        Some(SymbolTableEntry::Function(main)) => Some(main),
+        _ => None,
+    };
+    visit(&children[2], main, global_table, index);
+}
+
+fn func_def(node: &Node, global_table: &SymbolTable, index: &mut CrossReferences) {
+    let validated_node = match FunctionDefinition::view_as(node) {
+        Ok(validated_node) => validated_node,
+        Err(_) => return,
+    };
+    let scope = validated_node.get_corrected_scoped_id().1;
+    let function = validated_node.find_function(global_table);
+
+    let children = get_children(node);
+    if let (Some(function), Some(class_id)) = (function, scope) {
+        // The definition of a member function names the declaration in its class,
+        // along with its parameters
+        let name = function.qualified_name();
+        index.refer(class_id, &children[0], Usage::Type, None);
+        index.refer(&name, &children[1], Usage::Definition, None);
+        for parameter in get_children(&children[2]) {
+            if let Data::Children(parameter_children) = parameter.data() {
+                let id = &parameter_children[1];
+                if let Data::String(id_str) = id.data() {
+                    let parameter_name = qualify(Some(&name), id_str);
+                    index.refer(&parameter_name, id, Usage::Definition, None);
+                }
+            }
+        }
+    }
+    for child in &children[2..] {
+        visit(child, function, global_table, index);
+    }
+}
+
+/// A variable is a sequence of elements, all but the first following a dot, used as a whole
+fn var(
+    node: &Node,
+    usage: Usage,
+    function: Option<&Function>,
+    global_table: &SymbolTable,
+    index: &mut CrossReferences,
+) {
+    for (position, child) in get_children(node).iter().enumerate() {
+        element(child, position > 0, usage, function, global_table, index);
+    }
+}
+
+/// A dataMember or fCall, the members and overloads are those resolved by the type check
+fn element(
+    node: &Node,
+    dotted: bool,
+    usage: Usage,
+    function: Option<&Function>,
+    global_table: &SymbolTable,
+    index: &mut CrossReferences,
+) {
+    let children = get_children(node);
+    let id = &children[0];
+    let id_str = match id.data() {
+        Data::String(id_str) => id_str,
+        _ => return,
+    };
+
+    match (node.name().as_str(), node.attribute("member")) {
+        ("fCall", _) => {
+            if let Some(definition) = node.attribute("definition") {
+                index.refer(definition, id, Usage::Call, function);
+            }
+        }
+        (_, Some(declaring_class)) => {
+            let name = qualify(Some(declaring_class), id_str);
+            index.refer(&name, id, usage, function)
+        }
+        (_, None) if !dotted => {
+            if let Some(function) = function {
+                match function.symbol_table().get(id_str) {
+                    Some(SymbolTableEntry::Param(_)) | Some(SymbolTableEntry::Local(_)) => {
+                        let name = qualify(Some(&function.qualified_name()), id_str);
+                        index.refer(&name, id, usage, Some(function))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        _ => (),
+    }
+
+    // The indices and arguments
+    for child in &children[1..] {
+        visit(child, function, global_table, index);
+    }
+}
+
+fn type_node(
+    node: &Node,
+    function: Option<&Function>,
+    global_table: &SymbolTable,
+    index: &mut CrossReferences,
+) {
+    if let Data::String(type_id) = node.data() {
+        if let Some(SymbolTableEntry::Class(_)) = global_table.get(type_id) {
+            index.refer(type_id, node, Usage::Type, function);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic_analyzer::tests::analyze_source_with_results;
+
+    #[test]
+    fn test_cross_reference_member_accesses() {
+        let (results, messages) = analyze_source_with_results(
+            "cross_reference.src",
+            r#"
+class POINT {
+  public integer x;
+  public func moved(integer dx) : integer;
+};
+
+func POINT::moved(integer dx) : integer
+{
+  return (x + dx);
+}
+
+main
+{
+  var
+  {
+    POINT p;
+  }
+  p.x = 1;
+  write(p.moved(2));
+}
+"#,
+        );
+        assert_eq!(messages, Vec::<String>::new());
+
+        let index = &results.cross_references;
+        assert_eq!(
+            index.to_string(),
+            "\
+function main(), defined at 15:3
+    never referenced
+local main()::p, defined at 16:5
+    written at 18:3 in main()
+    read at 19:9 in main()
+class POINT, defined at 2:7
+    named as a type at 7:6
+    named as a type at 16:5 in main()
+data POINT::x, defined at 3:10
+    read at 9:11 in POINT::moved(integer)
+    written at 18:5 in main()
+function POINT::moved(integer), defined at 4:15
+    named by a definition at 7:13
+    called at 19:11 in main()
+param POINT::moved(integer)::dx, defined at 4:21
+    named by a definition at 7:27
+    read at 9:15 in POINT::moved(integer)
+"
+        );
+        // The member accessed through p is found from its reference
+        assert_eq!(index.at(18, 5).unwrap().symbol(), "POINT::x");
+    }
+}
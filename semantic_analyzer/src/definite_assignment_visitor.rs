@@ -0,0 +1,236 @@
+//! Check that the locals are assigned before they are read
+//!
+//! The statements of each function body are followed in order, a local is definitely assigned
+//! where the control reaches if it is assigned on every path leading there. The elements of arrays
+//! and the members of objects are assigned one by one, so only the other locals are checked.
+
+use crate::ast_validation::{FunctionDefinition, ViewAs};
+use crate::SemanticAnalysisResults;
+use crate::SemanticError;
+use crate::{Function, SymbolTable, SymbolTableEntry};
+use ast::{Data, Node};
+use output_manager::OutputConfig;
+use std::collections::HashSet;
+
+/// The locals definitely assigned where the control reaches, None where it can't be reached
+type Assigned = Option<HashSet<String>>;
+
+struct State {
+    /// The locals of the function that are checked
+    checked: HashSet<String>,
+    /// The locals already reported, only the first read of each is
+    reported: HashSet<String>,
+}
+
+pub fn process(node: &Node, current_results: &SemanticAnalysisResults, output: &mut OutputConfig) {
+    let global_table = &current_results.symbol_table;
+    let children = get_children(node);
+
+    for definition in get_children(&children[1]) {
+        if let Ok(validated_node) = FunctionDefinition::view_as(definition) {
+            // Definitions matching no declaration were reported while assembling the symbol table
+            if let Some(function) = validated_node.find_function(global_table) {
+                let body = &get_children(definition)[4];
+                function_body(body, function, global_table, output);
+            }
+        }
+    }
+
+    if let Some(SymbolTableEntry::Function(main)) = global_table.get("main") {
+        function_body(&children[2], main, global_table, output);
+    }
+}
+
+fn get_children(node: &Node) -> &[Node] {
+    match node.data() {
+        Data::Children(children) => children,
+        _ => &[],
+    }
+}
+
+fn function_body(
+    body: &Node,
+    function: &Function,
+    global_table: &SymbolTable,
+    output: &mut OutputConfig,
+) {
+    let checked = function
+        .symbol_table()
+        .values
+        .iter()
+        .filter_map(|entry| match entry {
+            SymbolTableEntry::Local(local)
+                if local.dimension().is_empty()
+                    && !matches!(
+                        global_table.get(local.data_type()),
+                        Some(SymbolTableEntry::Class(_))
+                    ) =>
+            {
+                Some(local.id().clone())
+            }
+            _ => None,
+        })
+        .collect();
+    let mut state = State {
+        checked,
+        reported: HashSet::new(),
+    };
+
+    let statements = &get_children(body)[1];
+    statement(statements, Some(HashSet::new()), &mut state, output);
+}
+
+/// The locals assigned where two paths join
+fn join(left: Assigned, right: Assigned) -> Assigned {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(left.intersection(&right).cloned().collect()),
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+/// Check a statement reached with the locals assigned, and return those assigned after it
+fn statement(
+    node: &Node,
+    assigned: Assigned,
+    state: &mut State,
+    output: &mut OutputConfig,
+) -> Assigned {
+    // The statements that can't be reached aren't checked
+    let mut assigned = assigned?;
+    let children = get_children(node);
+
+    match node.name().as_str() {
+        "statBlock" => children.iter().try_fold(assigned, |assigned, child| {
+            statement(child, Some(assigned), state, output)
+        }),
+        "assignOp" => {
+            reads(&children[1], &assigned, state, output);
+            assign(&children[0], &mut assigned, state, output);
+            Some(assigned)
+        }
+        "readStat" => {
+            assign(&children[0], &mut assigned, state, output);
+            Some(assigned)
+        }
+        "ifStat" => {
+            reads(&children[0], &assigned, state, output);
+            let then = statement(&children[1], Some(assigned.clone()), state, output);
+            let otherwise = statement(&children[2], Some(assigned), state, output);
+            join(then, otherwise)
+        }
+        "whileStat" => {
+            reads(&children[0], &assigned, state, output);
+            // The body may not run at all, and runs again with the locals it assigned
+            statement(&children[1], Some(assigned.clone()), state, output);
+            Some(assigned)
+        }
+        "returnStat" => {
+            reads(node, &assigned, state, output);
+            None
+        }
+        "breakStat" | "continueStat" => None,
+        _ => {
+            reads(node, &assigned, state, output);
+            Some(assigned)
+        }
+    }
+}
+
+/// Check the indices and arguments of the variable assigned, then assign it if it's a local
+fn assign(
+    variable: &Node,
+    assigned: &mut HashSet<String>,
+    state: &mut State,
+    output: &mut OutputConfig,
+) {
+    let elements = get_children(variable);
+    for element in elements {
+        for child in &get_children(element)[1..] {
+            reads(child, assigned, state, output);
+        }
+    }
+    if let [element] = elements {
+        if let Some(id) = local_id(element, state) {
+            assigned.insert(id.clone());
+        }
+    }
+}
+
+/// Report the locals read by an expression before they are assigned
+fn reads(node: &Node, assigned: &HashSet<String>, state: &mut State, output: &mut OutputConfig) {
+    if node.name() == "var" {
+        let first = &get_children(node)[0];
+        if let Some(id) = local_id(first, state) {
+            if !assigned.contains(id) && state.reported.insert(id.clone()) {
+                let err =
+                    SemanticError::new_possibly_uninitialized(first.line(), first.column(), id);
+                output.add(&err.to_string(), err.line(), err.col());
+            }
+        }
+    }
+    for child in get_children(node) {
+        reads(child, assigned, state, output);
+    }
+}
+
+/// The id of a checked local named by the first element of a variable
+fn local_id<'a>(element: &'a Node, state: &State) -> Option<&'a String> {
+    if element.name() != "dataMember" || element.attribute("member").is_some() {
+        return None;
+    }
+    match get_children(element)[0].data() {
+        Data::String(id) if state.checked.contains(id) => Some(id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic_analyzer::tests::analyze_source;
+
+    #[test]
+    fn test_possibly_uninitialized_reads() {
+        // b is assigned on both branches, c on one only, and d in a loop that may not run
+        let messages = analyze_source(
+            "uninitialized.src",
+            r#"
+main
+{
+  var
+  {
+    integer a;
+    integer b;
+    integer c;
+    integer d;
+  }
+  read(a);
+  if (a > 0) then
+  {
+    b = 1;
+    c = 1;
+  }
+  else
+  {
+    b = 2;
+  };
+  while (a > 0)
+  {
+    d = a;
+    a = a - 1;
+  };
+  write(b);
+  write(c);
+  write(d);
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic warning: 27:9 Local variable \"c\" may be read before it is assigned",
+                "Semantic warning: 28:9 Local variable \"d\" may be read before it is assigned",
+            ]
+        );
+    }
+}
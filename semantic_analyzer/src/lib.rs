@@ -1,4 +1,8 @@
+mod control_flow_graph;
+mod cross_reference_visitor;
+mod definite_assignment_visitor;
 mod format_table;
+mod lints;
 mod memory_size_visitor;
 mod semantic_analyzer;
 mod semantic_error;
@@ -6,6 +10,8 @@ mod type_check_visitor;
 mod utils;
 mod visibility;
 
+pub use control_flow_graph::{BasicBlock, ControlFlowGraph, Statement, Terminator};
+pub use cross_reference_visitor::{CrossReference, CrossReferences, Reference, Usage};
 pub use semantic_analyzer::analyze;
 pub use semantic_analyzer::SemanticAnalysisResults;
 use semantic_error::SemanticError;
@@ -13,10 +19,12 @@ use semantic_error::SemanticError;
 pub mod symbol_table {
     pub mod class;
     pub mod data;
+    pub mod dot_graph;
     pub mod function;
     pub mod inherit;
     pub mod literal;
     pub mod local;
+    pub mod object_layout;
     pub mod param;
     pub mod rules;
     pub mod sizes;
@@ -31,8 +39,9 @@ pub mod symbol_table {
     pub use inherit::Inherit;
     pub use literal::{Literal, LiteralValue};
     pub use local::Local;
+    pub use object_layout::{DataSlot, VirtualSlot};
     pub use param::Param;
-    pub use symbol_table::{SymbolTable, SymbolTableEntry};
+    pub use symbol_table::{Search, SymbolTable, SymbolTableEntry};
     pub use temporary::Temporary;
 }
 
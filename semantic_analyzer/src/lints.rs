@@ -0,0 +1,384 @@
+//! Report the symbols the program declares but makes no use of
+//!
+//! The lints are found from the cross references, each is reported at the level set for it on the
+//! command line, as warnings unless denied.
+
+use crate::cross_reference_visitor::{qualify, CrossReference, CrossReferences, Usage};
+use crate::symbol_table::object_layout::is_primary_base;
+use crate::SemanticAnalysisResults;
+use crate::SemanticError;
+use crate::{Function, Search, SymbolTable, SymbolTableEntry, Visibility};
+use output_manager::{Lint, LintLevel, OutputConfig};
+use std::collections::{HashMap, HashSet};
+
+const MAIN: &str = "main()";
+
+pub fn process(current_results: &SemanticAnalysisResults, output: &mut OutputConfig) {
+    let global_table = &current_results.symbol_table;
+    let references = &current_results.cross_references;
+    let reachable = reachable_functions(global_table, references);
+    lint_table(
+        global_table,
+        None,
+        global_table,
+        references,
+        &reachable,
+        output,
+    );
+}
+
+fn report(lint: Lint, entry: &CrossReference, message: &str, output: &mut OutputConfig) {
+    let level = output.lint_level(lint);
+    if level == LintLevel::Allow {
+        return;
+    }
+    let position = entry.definition();
+    let err = SemanticError::new_lint(&position.line, &position.column, lint, level, message);
+    output.add(&err.to_string(), err.line(), err.col());
+}
+
+fn lint_table(
+    table: &SymbolTable,
+    scope: Option<&str>,
+    global_table: &SymbolTable,
+    references: &CrossReferences,
+    reachable: &HashSet<String>,
+    output: &mut OutputConfig,
+) {
+    for entry in &table.values {
+        match entry {
+            SymbolTableEntry::Class(class) => lint_table(
+                class.symbol_table(),
+                Some(class.id()),
+                global_table,
+                references,
+                reachable,
+                output,
+            ),
+            // The member functions never defined were reported while assembling the symbol table
+            SymbolTableEntry::Function(function) if *function.defined() => {
+                let name = function.qualified_name();
+                if let Some(entry) = references.get(&name) {
+                    lint_function(function, entry, reachable, output);
+                }
+                lint_table(
+                    function.symbol_table(),
+                    Some(&name),
+                    global_table,
+                    references,
+                    reachable,
+                    output,
+                );
+            }
+            SymbolTableEntry::Data(data) => {
+                let unused = match data.visibility() {
+                    Visibility::Private => Some(Lint::UnusedPrivate),
+                    Visibility::Public => None,
+                };
+                let description = format!("Data member \"{}\"", qualify(scope, data.id()));
+                lint_variable(data.id(), scope, unused, &description, references, output);
+            }
+            SymbolTableEntry::Param(param) => {
+                let description = format!(
+                    "Parameter \"{}\" of \"{}\"",
+                    param.id(),
+                    scope.unwrap_or_default()
+                );
+                // An override has to declare the parameters of the member function it replaces
+                let unused = match scope.and_then(|name| find_member_function(name, global_table)) {
+                    Some(function) if shares_signature(function, global_table) => None,
+                    _ => Some(Lint::UnusedParameter),
+                };
+                lint_variable(param.id(), scope, unused, &description, references, output);
+            }
+            SymbolTableEntry::Local(local) => {
+                let description = format!(
+                    "Local variable \"{}\" of \"{}\"",
+                    local.id(),
+                    scope.unwrap_or_default()
+                );
+                let unused = Some(Lint::UnusedVariable);
+                lint_variable(local.id(), scope, unused, &description, references, output);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Report a variable never used, if the lint applies to it, or else assigned but never read
+fn lint_variable(
+    id: &str,
+    scope: Option<&str>,
+    unused: Option<Lint>,
+    description: &str,
+    references: &CrossReferences,
+    output: &mut OutputConfig,
+) {
+    let entry = match references.get(&qualify(scope, id)) {
+        Some(entry) => entry,
+        None => return,
+    };
+    if !entry.is_used_as(&[Usage::Read, Usage::Write]) {
+        if let Some(lint) = unused {
+            let message = format!("{} is never used", description);
+            report(lint, entry, &message, output);
+        }
+    } else if !entry.is_used_as(&[Usage::Read]) {
+        let message = format!("{} is assigned but never read", description);
+        report(Lint::UnreadVariable, entry, &message, output);
+    }
+}
+
+fn lint_function(
+    function: &Function,
+    entry: &CrossReference,
+    reachable: &HashSet<String>,
+    output: &mut OutputConfig,
+) {
+    let name = entry.symbol();
+    if name == MAIN {
+        return;
+    }
+    if let (Some(Visibility::Private), false) =
+        (function.visibility(), entry.is_used_as(&[Usage::Call]))
+    {
+        let message = format!("Private member function \"{}\" is never called", name);
+        report(Lint::UnusedPrivate, entry, &message, output);
+    } else if !reachable.contains(name) {
+        let message = format!("Function \"{}\" is never called from main", name);
+        report(Lint::UncalledFunction, entry, &message, output);
+    }
+}
+
+/// The functions called from main, directly or through the functions it calls
+///
+/// A call to a member function may reach its overrides in the classes the class of the object
+/// stands in for, through the virtual table.
+fn reachable_functions(
+    global_table: &SymbolTable,
+    references: &CrossReferences,
+) -> HashSet<String> {
+    let mut callees: HashMap<&str, Vec<&str>> = HashMap::new();
+    for entry in references.entries() {
+        for reference in entry.references() {
+            if let (Usage::Call, Some(caller)) = (reference.usage(), reference.function()) {
+                callees
+                    .entry(caller.as_str())
+                    .or_default()
+                    .push(entry.symbol().as_str());
+            }
+        }
+    }
+
+    let mut reachable = HashSet::new();
+    let mut pending = vec![MAIN.to_owned()];
+    while let Some(name) = pending.pop() {
+        if !reachable.insert(name.clone()) {
+            continue;
+        }
+        if let Some(called) = callees.get(name.as_str()) {
+            pending.extend(called.iter().map(|callee| callee.to_string()));
+        }
+        pending.extend(overrides(&name, global_table));
+    }
+    reachable
+}
+
+/// The overrides of a member function in the classes deriving from its own through their first
+/// inherited classes
+fn overrides(name: &str, global_table: &SymbolTable) -> Vec<String> {
+    let overridden = match find_member_function(name, global_table) {
+        Some(function) => function,
+        None => return Vec::new(),
+    };
+    let class_id = overridden.scope().as_ref().unwrap();
+    let overridden_types = parameter_types(overridden);
+
+    let mut result = Vec::new();
+    for entry in &global_table.values {
+        if let SymbolTableEntry::Class(derived) = entry {
+            if derived.id() == class_id || !is_primary_base(class_id, derived.id(), global_table) {
+                continue;
+            }
+            for member in derived.symbol_table().get_all(overridden.id()) {
+                if let SymbolTableEntry::Function(function) = member {
+                    if parameter_types(function) == overridden_types {
+                        result.push(function.qualified_name());
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Whether a member function overrides an inherited one or is overridden in a derived class
+fn shares_signature(function: &Function, global_table: &SymbolTable) -> bool {
+    let class = match function
+        .scope()
+        .as_deref()
+        .and_then(|id| global_table.get(id))
+    {
+        Some(SymbolTableEntry::Class(class)) => class,
+        _ => return false,
+    };
+    let types = parameter_types(function);
+    let same_signature = |entry: &SymbolTableEntry| match entry {
+        SymbolTableEntry::Function(other) => parameter_types(other) == types,
+        _ => false,
+    };
+
+    let overrides =
+        SymbolTable::get_all_inherited(class, function.id(), Search::Exclusive, global_table)
+            .into_iter()
+            .any(|(_, entry)| same_signature(entry));
+    let overridden = global_table.values.iter().any(|entry| match entry {
+        SymbolTableEntry::Class(derived) if derived.id() != class.id() => {
+            derived
+                .symbol_table()
+                .get_all(function.id())
+                .into_iter()
+                .any(same_signature)
+                && SymbolTable::get_all_inherited(
+                    derived,
+                    function.id(),
+                    Search::Exclusive,
+                    global_table,
+                )
+                .into_iter()
+                .any(|(base, _)| base.id() == class.id())
+        }
+        _ => false,
+    });
+    overrides || overridden
+}
+
+fn find_member_function<'a>(name: &str, global_table: &'a SymbolTable) -> Option<&'a Function> {
+    global_table.values.iter().find_map(|entry| match entry {
+        SymbolTableEntry::Class(class) => {
+            class
+                .symbol_table()
+                .values
+                .iter()
+                .find_map(|member| match member {
+                    SymbolTableEntry::Function(function) if function.qualified_name() == name => {
+                        Some(function)
+                    }
+                    _ => None,
+                })
+        }
+        _ => None,
+    })
+}
+
+fn parameter_types(function: &Function) -> Vec<&String> {
+    function
+        .parameter_types()
+        .iter()
+        .map(|parameter| parameter.data_type())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic_analyzer::tests::analyze_source;
+
+    #[test]
+    fn test_lints() {
+        let messages = analyze_source(
+            "lints.src",
+            r#"
+class COUNTER {
+  private integer unused;
+  public integer count;
+};
+
+func helper(integer unread) : integer
+{
+  return (1);
+}
+
+func twice(integer n) : integer
+{
+  return (n * 2);
+}
+
+main
+{
+  var
+  {
+    COUNTER c;
+    integer written;
+    integer idle;
+  }
+  written = 3;
+  c.count = twice(4);
+  write(c.count);
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic warning: 3:11 Data member \"COUNTER::unused\" is never used [unused-private]",
+                "Semantic warning: 7:6 Function \"helper(integer)\" is never called from main [uncalled-function]",
+                "Semantic warning: 7:13 Parameter \"unread\" of \"helper(integer)\" is never used [unused-parameter]",
+                "Semantic warning: 22:5 Local variable \"written\" of \"main()\" is assigned but never read [unread-variable]",
+                "Semantic warning: 23:5 Local variable \"idle\" of \"main()\" is never used [unused-variable]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overridden_parameters_are_used() {
+        // SHAPE::area has to declare the parameter its override reads, SHAPE::name doesn't
+        let messages = analyze_source(
+            "overridden_parameters.src",
+            r#"
+class SHAPE {
+  public func area(float scale) : float;
+  public func name(integer code) : integer;
+};
+
+class SQUARE inherits SHAPE {
+  public float side;
+  public func area(float scale) : float;
+};
+
+func SHAPE::area(float scale) : float
+{
+  return (0.0);
+}
+
+func SHAPE::name(integer code) : integer
+{
+  return (1);
+}
+
+func SQUARE::area(float scale) : float
+{
+  return (side * side * scale);
+}
+
+main
+{
+  var
+  {
+    SQUARE s;
+  }
+  s.side = 2.0;
+  write(s.area(1.0));
+  write(s.name(0));
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic warning: 3:15 Function \"SHAPE::area(float)\" is never called from main [uncalled-function]",
+                "Semantic warning: 4:20 Parameter \"code\" of \"SHAPE::name(integer)\" is never used [unused-parameter]",
+                "Semantic warning: 9:15 Member function \"SQUARE::area(float)\" provides override for inherited method \"SHAPE::area(float)\"",
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Find the sizes needed to reserve adequate memory for the program to run
 //! Add literal and temporary values to the symbol table
 
+use crate::symbol_table::object_layout;
 use crate::SemanticAnalysisResults;
 // use crate::{SymbolTable, SymbolTableEntry};
 use ast::Node;
@@ -12,10 +13,11 @@ pub fn process(
     current_results: &mut SemanticAnalysisResults,
     _output: &mut OutputConfig,
 ) {
-    // we can just sum the elements of a symbol table
+    // we can just sum the elements of a symbol table, the objects of every class are laid out
+    let class_sizes = object_layout::class_sizes(&current_results.symbol_table);
 
     for element in current_results.symbol_table.values.iter_mut() {
-        element.computed_size();
+        element.computed_size(&class_sizes);
     }
     // info!("Starting memory size check");
     // visit(node, &mut current_results.symbol_table.clone(), &mut State {}, &mut current_results.symbol_table)
@@ -1,17 +1,49 @@
+use crate::control_flow_graph::{self, ControlFlowGraph};
+use crate::cross_reference_visitor::{self, CrossReferences};
+use crate::definite_assignment_visitor;
+use crate::lints;
 use crate::memory_size_visitor;
 use crate::symbol_table;
 use crate::symbol_table::symbol_table::SymbolTable;
 use crate::type_check_visitor;
+use ast::typed::{MalformedNode, Program};
+use ast::Data;
+use log::warn;
 use output_manager::{warn_write, OutputConfig};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Nodes listing declarations or statements, an element lost to a syntax error is
+/// dropped from them while the rest of the list is analyzed
+const RECOVERABLE_LISTS: [&str; 5] = [
+    "classDeclList",
+    "funcDefList",
+    "membList",
+    "varList",
+    "statBlock",
+];
+
+/// Attribute of a `varList` naming the local variables whose declaration was lost to a
+/// syntax error, separated by spaces
+pub(crate) const LOST_LOCALS: &str = "lostLocals";
+
+/// Stands for a local variable in `LOST_LOCALS` whose identifier was lost as well, any
+/// undefined identifier of the function may then be it
+pub(crate) const LOST_IDENTIFIER: &str = "?";
 
 pub struct SemanticAnalysisResults {
     pub symbol_table: SymbolTable,
+    pub cross_references: CrossReferences,
+    /// The control flow graph of each function body, by the qualified name of the function
+    pub control_flow_graphs: HashMap<String, ControlFlowGraph>,
 }
 
 impl SemanticAnalysisResults {
     pub fn new() -> Self {
         SemanticAnalysisResults {
             symbol_table: SymbolTable::new("global"),
+            cross_references: CrossReferences::default(),
+            control_flow_graphs: HashMap::new(),
         }
     }
 }
@@ -33,7 +65,15 @@ impl Phase {
     }
 }
 
-pub fn analyze(root: &mut ast::Node, output_config: &mut OutputConfig) -> SemanticAnalysisResults {
+/// Analyze an AST, which may contain error nodes left by syntax errors
+///
+/// The declarations and statements containing an error node are skipped, the rest of
+/// the program is analyzed as usual. A tree without the shape the grammar gives it is
+/// rejected before any analysis.
+pub fn analyze(
+    root: &mut ast::Node,
+    output_config: &mut OutputConfig,
+) -> Result<SemanticAnalysisResults, MalformedNode> {
     let phases: Vec<Vec<Phase>> = vec![vec![Phase::new(
         symbol_table::visitor::visit,
         symbol_table::visitor::end_of_phase,
@@ -42,6 +82,13 @@ pub fn analyze(root: &mut ast::Node, output_config: &mut OutputConfig) -> Semant
     let mut results: SemanticAnalysisResults = SemanticAnalysisResults::new();
     let current_scope = Vec::new();
 
+    if !skip_syntax_errors(root) {
+        warn!("Syntax errors left nothing to analyze");
+        return Ok(results);
+    }
+    // The analyses migrated to the typed AST walk this view of the tree
+    let program = Program::try_from(&*root)?;
+
     let _main_node = match root.data() {
         ast::Data::Children(children) => &children[2],
         _ => {
@@ -58,8 +105,14 @@ pub fn analyze(root: &mut ast::Node, output_config: &mut OutputConfig) -> Semant
         }
     }
 
+    // Member calls are resolved against the virtual tables during the type check
+    symbol_table::object_layout::lay_out_classes(&mut results.symbol_table);
     type_check_visitor::process(root, &mut results, output_config);
+    control_flow_graph::process(&program, &mut results, output_config);
+    definite_assignment_visitor::process(root, &results, output_config);
     memory_size_visitor::process(root, &mut results, output_config);
+    cross_reference_visitor::process(root, &mut results, output_config);
+    lints::process(&results, output_config);
 
     // output_config.flush_semantic_messages();
 
@@ -69,6 +122,186 @@ pub fn analyze(root: &mut ast::Node, output_config: &mut OutputConfig) -> Semant
         &output_config.symbol_table_path,
         &format!("{}", results.symbol_table),
     );
+    warn_write(
+        &mut output_config.symbol_table_json_file,
+        &output_config.symbol_table_json_path,
+        &results.symbol_table.to_json(),
+    );
+    warn_write(
+        &mut output_config.symbol_table_dot_file,
+        &output_config.symbol_table_dot_path,
+        &results.symbol_table.to_dot(),
+    );
+    warn_write(
+        &mut output_config.xref_file,
+        &output_config.xref_path,
+        &results.cross_references.to_string(),
+    );
+
+    Ok(results)
+}
 
-    results
+/// Remove the declarations and statements containing error nodes from their lists,
+/// returns false if the node itself is lost to a syntax error
+///
+/// The identifiers of the local variables whose declaration is removed are listed in the
+/// `LOST_LOCALS` attribute of their `varList`, so their uses aren't reported as undefined.
+fn skip_syntax_errors(node: &mut ast::Node) -> bool {
+    if node.is_error() {
+        return false;
+    }
+    let recoverable = RECOVERABLE_LISTS.contains(&node.name().as_str());
+    let declares_locals = node.name() == "varList";
+    let mut lost_locals = Vec::new();
+    let data = node.data_mut();
+    if let Data::Children(children) = data {
+        if recoverable {
+            children.retain_mut(|child| {
+                let intact = skip_syntax_errors(child);
+                if !intact && declares_locals {
+                    lost_locals.push(declared_local(child));
+                }
+                intact
+            });
+            if children.is_empty() {
+                // Like the empty lists of the parser
+                *data = Data::Epsilon;
+            }
+        } else {
+            // A lost node is removed with its parent, the other children need no pruning
+            return children.iter_mut().all(skip_syntax_errors);
+        }
+    }
+    if !lost_locals.is_empty() {
+        node.set_attribute(LOST_LOCALS, &lost_locals.join(" "));
+    }
+    true
+}
+
+/// The identifier of a lost local variable declaration, or `LOST_IDENTIFIER` if it was lost too
+fn declared_local(node: &ast::Node) -> String {
+    match (node.name().as_str(), node.data()) {
+        ("varDecl", Data::Children(children)) => match children.get(1).map(|id| id.data()) {
+            Some(Data::String(id)) => id.clone(),
+            _ => LOST_IDENTIFIER.to_string(),
+        },
+        _ => LOST_IDENTIFIER.to_string(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use lexical_analyzer::{lexer::Lexer, lexical_rule::LexicalRule};
+    use std::fs::File;
+    use syntactic_analyzer::{parse, Grammar, ParseTable};
+
+    /// Parse and analyze a source file written to the temporary folder with the LL(1) grammar,
+    /// returns the semantic messages in the order they're written
+    pub(crate) fn analyze_source(name: &str, contents: &str) -> Vec<String> {
+        analyze_source_with_results(name, contents).1
+    }
+
+    /// Like `analyze_source`, along with the results of the analysis
+    pub(crate) fn analyze_source_with_results(
+        name: &str,
+        contents: &str,
+    ) -> (SemanticAnalysisResults, Vec<String>) {
+        let rules = LexicalRule::from_file("../resources/lex_tokens.txt").unwrap();
+        let keywords = std::fs::read_to_string("../resources/keywords.txt")
+            .unwrap()
+            .lines()
+            .map(|keyword| keyword.to_string())
+            .collect();
+        let lexer = Lexer::new(rules, keywords);
+        let grammar =
+            Grammar::from_reader(File::open("../resources/LL1.paquet.grm").unwrap()).unwrap();
+        let parse_table = ParseTable::from_grammar(&grammar);
+
+        let output_folder = std::env::temp_dir().join("paquet_semantic_test");
+        std::fs::create_dir_all(&output_folder).unwrap();
+        let source = output_folder.join(name);
+        std::fs::write(&source, contents).unwrap();
+        let source = source.to_str().unwrap();
+        let mut output_config = OutputConfig::new(source, output_folder.to_str().unwrap());
+        let mut ast = parse(
+            &mut lexer.lex(source, &output_config.lex_error_path),
+            &grammar,
+            &parse_table,
+            &mut output_config,
+        )
+        .unwrap();
+        let results = analyze(&mut ast, &mut output_config).unwrap();
+
+        output_config.semantic_error_buffer.sort();
+        let messages = output_config
+            .semantic_error_buffer
+            .iter()
+            .map(|message| message.message().clone())
+            .collect();
+        (results, messages)
+    }
+
+    #[test]
+    fn test_analyze_partial_ast() {
+        // The declaration of y is lost with its identifier, its uses aren't reported again
+        let messages = analyze_source(
+            "partial.src",
+            r#"
+func f(float a) : float
+{
+  return (a);
+}
+
+main
+{
+  var
+  {
+    integer x;
+    integer[2] y;
+  }
+  x = 1;
+  y = x;
+  write(y);
+  write(f(-2.0));
+  write(x + 1.5);
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic error: 18:13 Type error: types of binary operation do not match \"integer\", \"float\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_lost_locals() {
+        let declaration = |id: ast::Node| {
+            ast::Node::new(
+                "varDecl",
+                Data::Children(vec![
+                    ast::Node::new("type", Data::String("integer".to_string()), 3, 5),
+                    id,
+                    ast::Node::error(3, 15),
+                ]),
+                3,
+                5,
+            )
+        };
+        let mut var_list = ast::Node::new(
+            "varList",
+            Data::Children(vec![
+                declaration(ast::Node::new("id", Data::String("y".to_string()), 3, 13)),
+                declaration(ast::Node::error(3, 13)),
+            ]),
+            2,
+            3,
+        );
+
+        assert!(skip_syntax_errors(&mut var_list));
+        assert_eq!(var_list.data(), &Data::Epsilon);
+        assert_eq!(var_list.attribute(LOST_LOCALS).unwrap(), "y ?");
+    }
 }
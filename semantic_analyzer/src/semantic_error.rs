@@ -1,3 +1,4 @@
+use output_manager::{Lint, LintLevel};
 use std::fmt;
 
 pub enum SemanticError {
@@ -16,6 +17,14 @@ pub enum SemanticError {
     IncorrectNumberOfArguments(usize, usize, String),
     NoMatchingOverload(usize, usize, String),
     InvalidRelOp(usize, usize, String),
+    PrivateMemberAccess(usize, usize, String),
+    IncompatibleOverride(usize, usize, String),
+    SecondaryBaseCall(usize, usize, String),
+    DeniedLint(usize, usize, String),
+    Lint(usize, usize, String),
+    MissingReturn(usize, usize, String),
+    PossiblyUninitialized(usize, usize, String),
+    UnreachableStatement(usize, usize, String),
 
     BinaryMismatchedTypes(usize, usize, String),
     UndefinedType(usize, usize, String),
@@ -40,8 +49,16 @@ impl fmt::Display for SemanticError {
             SemanticError::IncorrectNumberOfArguments(l, c, message) => (l, c, message),
             SemanticError::NoMatchingOverload(l, c, message) => (l, c, message),
             SemanticError::InvalidRelOp(l, c, message) => (l, c, message),
+            SemanticError::PrivateMemberAccess(l, c, message) => (l, c, message),
+            SemanticError::IncompatibleOverride(l, c, message) => (l, c, message),
+            SemanticError::SecondaryBaseCall(l, c, message) => (l, c, message),
+            SemanticError::DeniedLint(l, c, message) => (l, c, message),
+            SemanticError::MissingReturn(l, c, message) => (l, c, message),
 
-            SemanticError::FunctionOverload(l, c, message) => {
+            SemanticError::FunctionOverload(l, c, message)
+            | SemanticError::Lint(l, c, message)
+            | SemanticError::PossiblyUninitialized(l, c, message)
+            | SemanticError::UnreachableStatement(l, c, message) => {
                 return write!(f, "Semantic warning: {}:{} {}", l, c, message);
             }
         };
@@ -70,6 +87,14 @@ impl SemanticError {
             SemanticError::IncorrectNumberOfArguments(l, _, _) => *l,
             SemanticError::NoMatchingOverload(l, _, _) => *l,
             SemanticError::InvalidRelOp(l, _, _) => *l,
+            SemanticError::PrivateMemberAccess(l, _, _) => *l,
+            SemanticError::IncompatibleOverride(l, _, _) => *l,
+            SemanticError::SecondaryBaseCall(l, _, _) => *l,
+            SemanticError::DeniedLint(l, _, _) => *l,
+            SemanticError::Lint(l, _, _) => *l,
+            SemanticError::MissingReturn(l, _, _) => *l,
+            SemanticError::PossiblyUninitialized(l, _, _) => *l,
+            SemanticError::UnreachableStatement(l, _, _) => *l,
         }
     }
 
@@ -92,6 +117,14 @@ impl SemanticError {
             SemanticError::IncorrectNumberOfArguments(_, c, _) => *c,
             SemanticError::NoMatchingOverload(_, c, _) => *c,
             SemanticError::InvalidRelOp(_, c, _) => *c,
+            SemanticError::PrivateMemberAccess(_, c, _) => *c,
+            SemanticError::IncompatibleOverride(_, c, _) => *c,
+            SemanticError::SecondaryBaseCall(_, c, _) => *c,
+            SemanticError::DeniedLint(_, c, _) => *c,
+            SemanticError::Lint(_, c, _) => *c,
+            SemanticError::MissingReturn(_, c, _) => *c,
+            SemanticError::PossiblyUninitialized(_, c, _) => *c,
+            SemanticError::UnreachableStatement(_, c, _) => *c,
         }
     }
 
@@ -200,13 +233,34 @@ impl SemanticError {
         )
     }
 
-    pub fn new_override(line: &usize, column: &usize, function: &str) -> SemanticError {
+    pub fn new_override(
+        line: &usize,
+        column: &usize,
+        function: &str,
+        overridden: &str,
+    ) -> SemanticError {
         SemanticError::FunctionOverload(
             *line,
             *column,
             format!(
-                "Member function \"{}\" provides override for inherited method",
-                function
+                "Member function \"{}\" provides override for inherited method \"{}\"",
+                function, overridden
+            ),
+        )
+    }
+
+    pub fn new_inherited_overload(
+        line: &usize,
+        column: &usize,
+        function: &str,
+        overloaded: &str,
+    ) -> SemanticError {
+        SemanticError::FunctionOverload(
+            *line,
+            *column,
+            format!(
+                "Member function \"{}\" provides overload for inherited method \"{}\"",
+                function, overloaded
             ),
         )
     }
@@ -339,7 +393,113 @@ impl SemanticError {
         )
     }
 
+    pub fn new_private_member_access(
+        line: &usize,
+        column: &usize,
+        member: &str,
+        class: &str,
+    ) -> SemanticError {
+        SemanticError::PrivateMemberAccess(
+            *line,
+            *column,
+            format!(
+                "Private member \"{}\" of class \"{}\" is not accessible here",
+                member, class
+            ),
+        )
+    }
+
+    /// Create a new message about an override whose return type differs from the one of the
+    /// inherited member function it replaces in the virtual table
+    pub fn new_incompatible_override(
+        line: &usize,
+        column: &usize,
+        function: &str,
+        overridden: &str,
+    ) -> SemanticError {
+        SemanticError::IncompatibleOverride(
+            *line,
+            *column,
+            format!(
+                "Member function \"{}\" overrides \"{}\" with a different return type",
+                function, overridden
+            ),
+        )
+    }
+
+    /// Create a new message about a call of a member function of a class inherited after the
+    /// first one, on an object of a class it isn't laid out in
+    pub fn new_secondary_base_call(
+        line: &usize,
+        column: &usize,
+        function: &str,
+        class: &str,
+    ) -> SemanticError {
+        SemanticError::SecondaryBaseCall(
+            *line,
+            *column,
+            format!(
+                "Member function \"{}\" can't be called on an object of class \"{}\", which doesn't inherit it through its first inherited classes",
+                function, class
+            ),
+        )
+    }
+
+    pub fn new_undefined_member(
+        line: &usize,
+        column: &usize,
+        member: &str,
+        class: &str,
+    ) -> SemanticError {
+        SemanticError::UndefinedIdentifier(
+            *line,
+            *column,
+            format!("Undefined member \"{}\" in class \"{}\"", member, class),
+        )
+    }
+
     // pub fn write(&self, output_manager: &mut OutputConfig) {
     //     warn_write(&mut output_manager.semantic_error_file, &output_manager.semantic_error_path, &format!("{}\n", self));
     // }
+
+    /// Create a new message about a finding of a lint, tagged with its code, denied lints are errors
+    pub fn new_lint(
+        line: &usize,
+        column: &usize,
+        lint: Lint,
+        level: LintLevel,
+        message: &str,
+    ) -> SemanticError {
+        let message = format!("{} [{}]", message, lint);
+        match level {
+            LintLevel::Deny => SemanticError::DeniedLint(*line, *column, message),
+            _ => SemanticError::Lint(*line, *column, message),
+        }
+    }
+
+    pub fn new_missing_return(line: &usize, column: &usize, function: &str) -> SemanticError {
+        SemanticError::MissingReturn(
+            *line,
+            *column,
+            format!(
+                "Function \"{}\" may reach its end without returning a value",
+                function
+            ),
+        )
+    }
+
+    pub fn new_possibly_uninitialized(line: &usize, column: &usize, local: &str) -> SemanticError {
+        SemanticError::PossiblyUninitialized(
+            *line,
+            *column,
+            format!(
+                "Local variable \"{}\" may be read before it is assigned",
+                local
+            ),
+        )
+    }
+
+    pub fn new_unreachable_statement(line: &usize, column: &usize) -> SemanticError {
+        SemanticError::UnreachableStatement(*line, *column, "Statement can't be reached".to_owned())
+    }
 }
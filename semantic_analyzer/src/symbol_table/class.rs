@@ -1,7 +1,10 @@
 use crate::ast_validation::ClassDeclaration;
 use crate::format_table::FormatTable;
+use crate::symbol_table::object_layout::{DataSlot, VirtualSlot};
 use crate::symbol_table::SymbolTable;
 use derive_getters::Getters;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::default::Default;
 use std::fmt;
 
@@ -9,12 +12,15 @@ use std::fmt;
 // An identifier that names a new compound type type
 // A list of types that name other compound types
 // a nested symbol table that holds member functions and variables
+// The layout of its objects, including the inherited data members and virtual table slots
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Class {
     id: String,
     inheritance_list: Vec<String>,
     symbol_table: SymbolTable,
+    data_slots: Vec<DataSlot>,
+    virtual_table: Vec<VirtualSlot>,
     bytes: usize,
     line: usize,
     column: usize,
@@ -23,6 +29,15 @@ pub struct Class {
 impl FormatTable for Class {
     fn lines(&self, width: usize) -> Vec<String> {
         let mut result = vec![format!("class | {:10}| {:<10}", self.id, self.bytes)];
+        for (index, slot) in self.virtual_table.iter().enumerate() {
+            result.push(format!(
+                "   vtable | {:<7}| {}::{}({})",
+                VirtualSlot::offset(index),
+                slot.implementation(),
+                slot.id(),
+                slot.parameter_types().join(",")
+            ));
+        }
         for l in self.symbol_table.lines(width - 8) {
             result.push(format!("   {}", l));
         }
@@ -35,6 +50,8 @@ impl Class {
         Class {
             id: class_declaration.id().to_string(),
             symbol_table: SymbolTable::new(class_declaration.id()),
+            data_slots: Vec::new(),
+            virtual_table: Vec::new(),
             inheritance_list: class_declaration
                 .inheritance_list()
                 .id_list()
@@ -55,13 +72,43 @@ impl Class {
         &mut self.symbol_table
     }
 
-    pub fn computed_size(&mut self) -> usize {
-        let mut size = 0;
+    pub fn set_layout(
+        &mut self,
+        data_slots: Vec<DataSlot>,
+        virtual_table: Vec<VirtualSlot>,
+        bytes: usize,
+    ) {
+        self.data_slots = data_slots;
+        self.virtual_table = virtual_table;
+        self.bytes = bytes;
+    }
+
+    /// The label of the virtual table of the class
+    pub fn virtual_table_label(&self) -> String {
+        self.symbol_table.mangle("vtable")
+    }
+
+    /// The slot of a data member declared by the supplied class, inherited or not
+    pub fn data_slot(&self, id: &str, declaring_class: &str) -> Option<&DataSlot> {
+        self.data_slots
+            .iter()
+            .find(|slot| slot.id() == id && slot.class() == declaring_class)
+    }
+
+    /// The index in the virtual table of the member function with the supplied signature
+    pub fn virtual_slot(&self, id: &str, parameter_types: &[String]) -> Option<usize> {
+        self.virtual_table
+            .iter()
+            .position(|slot| slot.id() == id && slot.parameter_types() == parameter_types)
+    }
+
+    /// The size of the objects of the class, as laid out, the sizes of the member functions are
+    /// computed along
+    pub fn computed_size(&mut self, class_sizes: &HashMap<String, usize>) -> usize {
         for elem in self.symbol_table.values.iter_mut() {
-            size += elem.computed_size();
+            elem.computed_size(class_sizes);
         }
-        self.bytes = size;
-        size
+        self.bytes
     }
 }
 
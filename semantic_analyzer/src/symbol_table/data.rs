@@ -5,6 +5,8 @@ use crate::symbol_table::utils;
 use crate::visibility::Visibility;
 use derive_getters::Getters;
 use log::error;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::default::Default;
 use std::fmt;
 
@@ -15,7 +17,7 @@ use std::fmt;
 // with zero or more fully specified dimensions
 // with a visibility specifier
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Data {
     id: String,
     visibility: Visibility,
@@ -81,8 +83,8 @@ impl Data {
         utils::type_string(&self.data_type, &self.dimension)
     }
 
-    pub fn computed_size(&mut self) -> usize {
-        let size = sizes::size_of(&self.data_type, &self.dimension);
+    pub fn computed_size(&mut self, class_sizes: &HashMap<String, usize>) -> usize {
+        let size = sizes::size_of(&self.data_type, &self.dimension, class_sizes);
         self.bytes = size;
         size
     }
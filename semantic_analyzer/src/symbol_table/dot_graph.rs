@@ -0,0 +1,162 @@
+//! Render a symbol table as a DOT graph
+//!
+//! Each table is a cluster nested in the cluster of the table declaring its owner, the owning
+//! class or function heading it. Classes are linked to the classes they inherit.
+
+use crate::symbol_table::{Class, SymbolTable, SymbolTableEntry};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Default)]
+struct Graph {
+    body: String,
+    nodes: usize,
+    clusters: usize,
+    /// The node heading the cluster of each class, to draw the inheritance edges
+    classes: HashMap<String, String>,
+    inheritance: Vec<(String, String)>,
+}
+
+/// Return the DOT rendering of the table and of every nested table
+pub fn dot_graph(global_table: &SymbolTable) -> String {
+    let mut graph = Graph::default();
+    cluster(global_table, None, &mut graph, 1);
+
+    let mut result = String::from("digraph symbol_table {\n");
+    result.push_str("  node [shape=box fontname=monospace]\n");
+    result.push_str(&graph.body);
+    for (class, inherited) in &graph.inheritance {
+        // Undefined inherited classes were reported while assembling the symbol table
+        if let Some(inherited) = graph.classes.get(inherited) {
+            writeln!(
+                result,
+                "  {} -> {} [label=\"inherits\" style=dashed]",
+                graph.classes[class], inherited
+            )
+            .unwrap();
+        }
+    }
+    result.push_str("}\n");
+    result
+}
+
+fn cluster(table: &SymbolTable, owner: Option<&SymbolTableEntry>, graph: &mut Graph, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let title = match &table.scope {
+        Some(scope) => format!("{}::{}", scope, table.name),
+        None => table.name.clone(),
+    };
+    writeln!(
+        graph.body,
+        "{}subgraph cluster_{} {{",
+        indent, graph.clusters
+    )
+    .unwrap();
+    graph.clusters += 1;
+    writeln!(
+        graph.body,
+        "{}  label=\"table: {}\"",
+        indent,
+        escape(&title)
+    )
+    .unwrap();
+
+    if let Some(owner) = owner {
+        let node = new_node(owner, graph, depth + 1);
+        if let SymbolTableEntry::Class(class) = owner {
+            graph.classes.insert(class.id().clone(), node);
+        }
+    }
+
+    for entry in &table.values {
+        match entry {
+            SymbolTableEntry::Class(class) => {
+                for inherited in class.inheritance_list() {
+                    graph
+                        .inheritance
+                        .push((class.id().clone(), inherited.clone()));
+                }
+                cluster(class.symbol_table(), Some(entry), graph, depth + 1);
+            }
+            SymbolTableEntry::Function(function) => {
+                cluster(function.symbol_table(), Some(entry), graph, depth + 1)
+            }
+            _ => {
+                new_node(entry, graph, depth + 1);
+            }
+        }
+    }
+    writeln!(graph.body, "{}}}", indent).unwrap();
+}
+
+/// Add the node describing an entry and return its name
+fn new_node(entry: &SymbolTableEntry, graph: &mut Graph, depth: usize) -> String {
+    let node = format!("n{}", graph.nodes);
+    graph.nodes += 1;
+    writeln!(
+        graph.body,
+        "{}{} [label=\"{}\"]",
+        "  ".repeat(depth),
+        node,
+        escape(&describe(entry))
+    )
+    .unwrap();
+    node
+}
+
+/// The lines of the label of an entry, its kind and name followed by its type
+fn describe(entry: &SymbolTableEntry) -> String {
+    match entry {
+        SymbolTableEntry::Class(class) => describe_class(class),
+        SymbolTableEntry::Function(function) => {
+            let mut lines = format!(
+                "function {}\n{}: {}",
+                function.id(),
+                function.signature(),
+                function.return_type_as_string()
+            );
+            if let Some(visibility) = function.visibility() {
+                write!(lines, "\n{}", visibility).unwrap();
+            }
+            lines
+        }
+        SymbolTableEntry::Inherit(inherit) => format!("inherit\n{}", inherit.names().join(", ")),
+        SymbolTableEntry::Param(param) => format!("param {}\n{}", param.id(), param.type_string()),
+        SymbolTableEntry::Local(local) => format!("local {}\n{}", local.id(), local.type_string()),
+        SymbolTableEntry::Data(data) => format!(
+            "data {}\n{}\n{}",
+            data.id(),
+            data.type_string(),
+            data.visibility()
+        ),
+        SymbolTableEntry::Literal(literal) => {
+            format!("literal {}\n{}", literal.id(), literal.value())
+        }
+        SymbolTableEntry::Temporary(temporary) => {
+            format!("temp {}\n{}", temporary.id(), temporary.data_type())
+        }
+    }
+}
+
+fn describe_class(class: &Class) -> String {
+    let mut lines = format!("class {}\n{} bytes", class.id(), class.bytes());
+    for slot in class.virtual_table() {
+        write!(
+            lines,
+            "\nvirtual {}::{}({})",
+            slot.implementation(),
+            slot.id(),
+            slot.parameter_types().join(", ")
+        )
+        .unwrap();
+    }
+    lines
+}
+
+/// Escape the label for a double quoted DOT string, with its line breaks
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -7,10 +7,12 @@ use crate::symbol_table::symbol_table::SymbolTable;
 use crate::visibility::Visibility;
 
 use derive_getters::Getters;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::default::Default;
 use std::fmt;
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Function {
     id: String,
     scope: Option<String>,
@@ -139,6 +141,14 @@ impl Function {
         }
     }
 
+    /// The name of the function qualified by its class, with its parameters to tell overloads apart
+    pub fn qualified_name(&self) -> String {
+        match &self.scope {
+            Some(scope) => format!("{}::{}{}", scope, self.id, self.signature()),
+            None => format!("{}{}", self.id, self.signature()),
+        }
+    }
+
     pub fn signature(&self) -> String {
         let mut result = String::new();
         result.push_str("(");
@@ -157,10 +167,10 @@ impl Function {
         &mut self.symbol_table
     }
 
-    pub fn computed_size(&mut self) -> usize {
+    pub fn computed_size(&mut self, class_sizes: &HashMap<String, usize>) -> usize {
         let mut bytes = 0;
         for elem in self.symbol_table.values.iter_mut() {
-            bytes += elem.computed_size();
+            bytes += elem.computed_size(class_sizes);
         }
         self.bytes = bytes;
         bytes
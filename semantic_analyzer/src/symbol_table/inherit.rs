@@ -1,12 +1,13 @@
 use crate::format_table::FormatTable;
 use crate::utils::separated_list;
 use derive_getters::Getters;
+use serde::Serialize;
 use std::default::Default;
 use std::fmt;
 
 // This data can and should be completely derived from the class entry itself
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Inherit {
     names: Vec<String>,
     line: usize,
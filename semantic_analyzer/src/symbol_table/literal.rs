@@ -1,8 +1,10 @@
 use crate::format_table::FormatTable;
 use derive_getters::Getters;
+use serde::Serialize;
 use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LiteralValue {
     Integer(i32),
     Real(f32),
@@ -25,7 +27,7 @@ impl Default for LiteralValue {
     }
 }
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Literal {
     id: String,
     value: LiteralValue,
@@ -4,6 +4,8 @@ use crate::sizes;
 use crate::symbol_table::utils;
 use derive_getters::Getters;
 use log::error;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::default::Default;
 use std::fmt;
 
@@ -12,7 +14,7 @@ use std::fmt;
 // A data type that names a primitive or compound type
 // Zero or more fully specified dimensions
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Local {
     id: String,
     data_type: String,
@@ -68,8 +70,8 @@ impl Local {
         utils::type_string(&self.data_type, &self.dimension)
     }
 
-    pub fn computed_size(&mut self) -> usize {
-        let size = sizes::size_of(&self.data_type, &self.dimension);
+    pub fn computed_size(&mut self, class_sizes: &HashMap<String, usize>) -> usize {
+        let size = sizes::size_of(&self.data_type, &self.dimension, class_sizes);
         self.bytes = size;
         size
     }
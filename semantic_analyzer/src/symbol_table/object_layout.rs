@@ -0,0 +1,214 @@
+//! Memory layout of the objects of each class and of their virtual tables
+//!
+//! An object starts with a header word holding the address of the virtual table of its class,
+//! followed by the data members of the classes it inherits, in the order of the inheritance list,
+//! and then by its own. The first inherited class is laid out as a prefix of the object and of the
+//! virtual table, so that an object can stand in for any class along its chain of first inherited
+//! classes. The slots of a member function are taken over by the overrides in derived classes.
+
+use crate::sizes;
+use crate::symbol_table::{Class, SymbolTable, SymbolTableEntry};
+use derive_getters::Getters;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Size of the object header holding the address of the virtual table
+pub const HEADER_SIZE: usize = 4;
+
+/// Size of a slot of the virtual table, the address of a member function
+pub const SLOT_SIZE: usize = 4;
+
+/// A data member of a class or of one of the classes it inherits, at its offset in the object
+#[derive(Debug, Clone, Getters, Serialize)]
+pub struct DataSlot {
+    id: String,
+    class: String,
+    data_type: String,
+    dimension: Vec<i64>,
+    offset: usize,
+    bytes: usize,
+}
+
+/// A member function reachable through the virtual table and the class implementing it
+#[derive(Debug, Clone, Getters, Serialize)]
+pub struct VirtualSlot {
+    id: String,
+    parameter_types: Vec<String>,
+    return_type: Option<String>,
+    implementation: String,
+    /// The label of the code of the implementation
+    label: String,
+}
+
+impl VirtualSlot {
+    /// The offset of the slot in the virtual table
+    pub fn offset(index: usize) -> usize {
+        index * SLOT_SIZE
+    }
+
+    fn overridden_by(&self, other: &VirtualSlot) -> bool {
+        self.id == other.id && self.parameter_types == other.parameter_types
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Layout {
+    data: Vec<DataSlot>,
+    virtual_table: Vec<VirtualSlot>,
+    bytes: usize,
+}
+
+/// Lay out the objects of every class of the global table
+///
+/// Classes with a cyclic inheritance hierarchy or cyclically containing themselves are laid out
+/// without the offending classes, the cycles have been reported when assembling the symbol table.
+pub fn lay_out_classes(global_table: &mut SymbolTable) {
+    let mut layouts = HashMap::new();
+    for entry in &global_table.values {
+        if let SymbolTableEntry::Class(class) = entry {
+            lay_out(class, global_table, &mut layouts, &mut HashSet::new());
+        }
+    }
+
+    for entry in global_table.values.iter_mut() {
+        if let SymbolTableEntry::Class(class) = entry {
+            if let Some(layout) = layouts.remove(class.id()) {
+                class.set_layout(layout.data, layout.virtual_table, layout.bytes);
+            }
+        }
+    }
+}
+
+/// The size of the objects of each class of the global table, once laid out
+pub fn class_sizes(global_table: &SymbolTable) -> HashMap<String, usize> {
+    global_table
+        .values
+        .iter()
+        .filter_map(|entry| match entry {
+            SymbolTableEntry::Class(class) => Some((class.id().clone(), *class.bytes())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether objects of the derived class can stand in for the base class,
+/// that is the base class is on its chain of first inherited classes
+pub fn is_primary_base(base: &str, derived: &str, global_table: &SymbolTable) -> bool {
+    let mut visited = HashSet::new();
+    let mut current = derived.to_string();
+    while visited.insert(current.clone()) {
+        if current == base {
+            return true;
+        }
+        match global_table.get(&current) {
+            Some(SymbolTableEntry::Class(class)) => match class.inheritance_list().first() {
+                Some(inherited) => current = inherited.clone(),
+                None => return false,
+            },
+            _ => return false,
+        }
+    }
+    false
+}
+
+fn lay_out(
+    class: &Class,
+    global_table: &SymbolTable,
+    layouts: &mut HashMap<String, Layout>,
+    visiting: &mut HashSet<String>,
+) {
+    if layouts.contains_key(class.id()) || !visiting.insert(class.id().clone()) {
+        return;
+    }
+
+    let mut layout = Layout {
+        bytes: HEADER_SIZE,
+        ..Layout::default()
+    };
+
+    for (index, inherited) in class.inheritance_list().iter().enumerate() {
+        if let Some(SymbolTableEntry::Class(inherited)) = global_table.get(inherited) {
+            lay_out(inherited, global_table, layouts, visiting);
+        }
+        let inherited = match layouts.get(inherited) {
+            Some(inherited) => inherited,
+            None => continue,
+        };
+
+        if index == 0 {
+            layout = inherited.clone();
+            continue;
+        }
+
+        for slot in &inherited.data {
+            if !layout
+                .data
+                .iter()
+                .any(|s| s.id == slot.id && s.class == slot.class)
+            {
+                layout.data.push(DataSlot {
+                    offset: layout.bytes,
+                    ..slot.clone()
+                });
+                layout.bytes += slot.bytes;
+            }
+        }
+        for slot in &inherited.virtual_table {
+            if !layout.virtual_table.iter().any(|s| s.overridden_by(slot)) {
+                layout.virtual_table.push(slot.clone());
+            }
+        }
+    }
+
+    for entry in &class.symbol_table().values {
+        match entry {
+            SymbolTableEntry::Data(data) => {
+                // Objects contained by value must be laid out first
+                if let Some(SymbolTableEntry::Class(member_class)) =
+                    global_table.get(data.data_type())
+                {
+                    lay_out(member_class, global_table, layouts, visiting);
+                }
+                let class_sizes = layouts
+                    .iter()
+                    .map(|(id, layout)| (id.clone(), layout.bytes))
+                    .collect();
+                let bytes = sizes::size_of(data.data_type(), data.dimension(), &class_sizes);
+                layout.data.push(DataSlot {
+                    id: data.id().clone(),
+                    class: class.id().clone(),
+                    data_type: data.data_type().clone(),
+                    dimension: data.dimension().clone(),
+                    offset: layout.bytes,
+                    bytes,
+                });
+                layout.bytes += bytes;
+            }
+            SymbolTableEntry::Function(function) => {
+                let slot = VirtualSlot {
+                    id: function.id().clone(),
+                    parameter_types: function
+                        .parameter_types()
+                        .iter()
+                        .map(|p| p.data_type().clone())
+                        .collect(),
+                    return_type: function.return_type().clone(),
+                    implementation: class.id().clone(),
+                    label: function.symbol_table().entry_label(),
+                };
+                match layout
+                    .virtual_table
+                    .iter_mut()
+                    .find(|s| s.overridden_by(&slot))
+                {
+                    Some(overridden) => *overridden = slot,
+                    None => layout.virtual_table.push(slot),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    visiting.remove(class.id());
+    layouts.insert(class.id().clone(), layout);
+}
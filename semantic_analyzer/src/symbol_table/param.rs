@@ -2,6 +2,8 @@ use crate::ast_validation::FunctionParameter;
 use crate::format_table::FormatTable;
 use crate::sizes;
 use derive_getters::Getters;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::default::Default;
 use std::fmt;
 
@@ -16,7 +18,7 @@ use crate::symbol_table::utils;
 // A declaration introduces: A compound type, a variable declaration, a parameter, a member variable
 // a statement uses the elements of the previous declarations
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Param {
     id: String,
     data_type: String,
@@ -72,8 +74,8 @@ impl Param {
         utils::parameter_type_string(&self.data_type, &self.dimension)
     }
 
-    pub fn computed_size(&mut self) -> usize {
-        let size = sizes::size_of_optional(&self.data_type, &self.dimension);
+    pub fn computed_size(&mut self, class_sizes: &HashMap<String, usize>) -> usize {
+        let size = sizes::size_of_optional(&self.data_type, &self.dimension, class_sizes);
         self.bytes = size;
         size
     }
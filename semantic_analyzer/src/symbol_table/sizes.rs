@@ -1,26 +1,38 @@
+use std::collections::HashMap;
+
 const ADDR_SIZE: usize = 4; // When passing arrays or string around (???)
 
-pub fn size_of_optional(data_type: &str, dimensions: &Vec<Option<i64>>) -> usize {
-    if dimensions.len() == 0 {
-        return base_size_of(data_type);
+/// Objects are passed by address like arrays
+pub fn size_of_optional(
+    data_type: &str,
+    dimensions: &Vec<Option<i64>>,
+    class_sizes: &HashMap<String, usize>,
+) -> usize {
+    if dimensions.len() == 0 && !class_sizes.contains_key(data_type) {
+        return base_size_of(data_type, class_sizes);
     } else {
         return ADDR_SIZE;
     }
 }
 
-pub fn size_of(data_type: &str, dimensions: &Vec<i64>) -> usize {
-    let mut size = base_size_of(data_type);
+pub fn size_of(
+    data_type: &str,
+    dimensions: &Vec<i64>,
+    class_sizes: &HashMap<String, usize>,
+) -> usize {
+    let mut size = base_size_of(data_type, class_sizes);
     for dimension in dimensions {
         size *= *dimension as usize;
     }
     size
 }
 
-fn base_size_of(data_type: &str) -> usize {
+fn base_size_of(data_type: &str, class_sizes: &HashMap<String, usize>) -> usize {
     match data_type {
         "integer" => 4,
         "float" => 4,
         "string" => ADDR_SIZE,
-        _ => panic!(),
+        // Undefined types have been reported by the type check
+        class => *class_sizes.get(class).unwrap_or(&0),
     }
 }
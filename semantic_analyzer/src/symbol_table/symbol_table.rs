@@ -2,12 +2,13 @@ use crate::format_table::FormatTable;
 use crate::symbol_table::*;
 use crate::SemanticError;
 use output_manager::OutputConfig;
+use serde::Serialize;
 use std::default::Default;
 use std::fmt;
 
 use crate::symbol_table::Class;
 use maplit::hashset;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 const GLOBAL_TABLE_WIDTH: usize = 83;
 const TEMP_PREFIX: &str = "temp";
@@ -15,8 +16,13 @@ const ELSE_PREFIX: &str = "else";
 const ENDIF_PREFIX: &str = "endif";
 const GOWHILE_PREFIX: &str = "gowhile";
 const ENDWHILE_PREFIX: &str = "endwhile";
+const ENTRY_SUFFIX: &str = "entry";
+const LINK_SUFFIX: &str = "link";
+const THIS_SUFFIX: &str = "this";
 
-#[derive(Debug, Clone)]
+/// Serialized with its kind as a field alongside the fields of the entry
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
 pub enum SymbolTableEntry {
     Class(class::Class),
     Function(function::Function),
@@ -72,16 +78,16 @@ impl SymbolTableEntry {
         }
     }
 
-    pub fn computed_size(&mut self) -> usize {
+    pub fn computed_size(&mut self, class_sizes: &HashMap<String, usize>) -> usize {
         match self {
-            SymbolTableEntry::Class(class) => class.computed_size(),
-            SymbolTableEntry::Function(function) => function.computed_size(),
+            SymbolTableEntry::Class(class) => class.computed_size(class_sizes),
+            SymbolTableEntry::Function(function) => function.computed_size(class_sizes),
             SymbolTableEntry::Inherit(_) => 0,
-            SymbolTableEntry::Param(param) => param.computed_size(),
-            SymbolTableEntry::Local(local) => local.computed_size(),
-            SymbolTableEntry::Data(data) => data.computed_size(),
+            SymbolTableEntry::Param(param) => param.computed_size(class_sizes),
+            SymbolTableEntry::Local(local) => local.computed_size(class_sizes),
+            SymbolTableEntry::Data(data) => data.computed_size(class_sizes),
             SymbolTableEntry::Literal(literal) => literal.computed_size(),
-            SymbolTableEntry::Temporary(temporary) => temporary.computed_size(),
+            SymbolTableEntry::Temporary(temporary) => temporary.computed_size(class_sizes),
         }
     }
 
@@ -115,14 +121,17 @@ impl FormatTable for SymbolTableEntry {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SymbolTable {
     pub name: String,
     pub values: Vec<SymbolTableEntry>,
     pub scope: Option<String>,
 
+    #[serde(skip)]
     temp_var_count: usize,
+    #[serde(skip)]
     if_else_count: usize,
+    #[serde(skip)]
     while_count: usize,
 }
 
@@ -175,6 +184,16 @@ impl SymbolTable {
         self.values.last_mut().unwrap()
     }
 
+    /// Serialize the table and every nested table as JSON, for tools that can't read the text tables
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Symbol tables serialize to JSON")
+    }
+
+    /// Render the table and every nested table as a DOT graph
+    pub fn to_dot(&self) -> String {
+        dot_graph::dot_graph(self)
+    }
+
     /// Return the title string of this symbol table
     fn title(&self) -> String {
         let mut title = "".to_string();
@@ -197,25 +216,44 @@ impl SymbolTable {
     }
 
     pub fn get_next_if_else_label(&mut self) -> (String, String) {
-        let result1 = format!("{}__{}{}", self.name, ELSE_PREFIX, self.if_else_count);
-        let result2 = format!("{}__{}{}", self.name, ENDIF_PREFIX, self.if_else_count);
+        let result1 = self.mangle(&format!("{}{}", ELSE_PREFIX, self.if_else_count));
+        let result2 = self.mangle(&format!("{}{}", ENDIF_PREFIX, self.if_else_count));
         self.if_else_count += 1;
         return (result1, result2);
     }
 
     pub fn get_next_while_label(&mut self) -> (String, String) {
-        let result1 = format!("{}__{}{}", self.name, GOWHILE_PREFIX, self.while_count);
-        let result2 = format!("{}__{}{}", self.name, ENDWHILE_PREFIX, self.while_count);
+        let result1 = self.mangle(&format!("{}{}", GOWHILE_PREFIX, self.while_count));
+        let result2 = self.mangle(&format!("{}{}", ENDWHILE_PREFIX, self.while_count));
         self.while_count += 1;
         return (result1, result2);
     }
 
     pub fn get_previous_mangled_name(&self) -> String {
-        format!("{}__{}{}", self.name, TEMP_PREFIX, self.temp_var_count - 1)
+        self.mangle(&format!("{}{}", TEMP_PREFIX, self.temp_var_count - 1))
     }
 
+    /// Prefix the identifier with the names of the table and of its scope, to make it a unique label
     pub fn mangle(&self, id: &str) -> String {
-        format!("{}__{}", self.name, id)
+        match &self.scope {
+            Some(scope) => format!("{}__{}__{}", scope, self.name, id),
+            None => format!("{}__{}", self.name, id),
+        }
+    }
+
+    /// The label of the code of the function owning this table
+    pub fn entry_label(&self) -> String {
+        self.mangle(ENTRY_SUFFIX)
+    }
+
+    /// The label of the storage for the return address of the function owning this table
+    pub fn link_label(&self) -> String {
+        self.mangle(LINK_SUFFIX)
+    }
+
+    /// The label of the storage for the object address of the member function owning this table
+    pub fn this_label(&self) -> String {
+        self.mangle(THIS_SUFFIX)
     }
 
     // pub fn get_internal_variable_prefix(&mut self) -> String {
@@ -325,66 +363,96 @@ impl SymbolTable {
         }
     }
 
-    /// Return the list of entries that share the identifier from the supplied class' inheritance hierarchy
-    /// The purpose of this is to allow a check at the definition of a class member for shadowing, and overriding
-    /// Checking for redefinition and overloading should be done with get_all
+    /// Return the entries that share the identifier in the supplied class' inheritance hierarchy,
+    /// along with the class declaring each of them
+    /// Inherited classes are walked transitively, breadth first in the order of the inheritance lists
+    /// The purpose of this is to allow a check at the definition of a class member for shadowing, overriding
+    /// and overloading of inherited members. Checking within a single class should be done with get_all
     pub fn get_all_inherited<'a>(
         class: &'a Class,
         id: &str,
+        search: Search,
         global_table: &'a SymbolTable,
-    ) -> Vec<&'a SymbolTableEntry> {
-        let mut result = Vec::new();
-        let mut visited = hashset! {class.id().clone()};
+    ) -> Vec<(&'a Class, &'a SymbolTableEntry)> {
+        let (mut visited, mut queue) = Self::start_search(class, search, global_table);
 
-        // search the inherited classes recursively
-        for inherited in class.inheritance_list() {
-            if let Some(SymbolTableEntry::Class(class)) = global_table.get(inherited) {
-                if visited.contains(class.id()) {
-                    continue;
-                }
-                result.extend(class.symbol_table().get_all_inherited_aux(
-                    class,
-                    id,
-                    global_table,
-                    &mut visited,
-                ))
-            }
+        let mut result = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            result.extend(
+                current
+                    .symbol_table()
+                    .get_all(id)
+                    .into_iter()
+                    .map(|entry| (current, entry)),
+            );
+            Self::queue_inherited(current, global_table, &mut visited, &mut queue);
         }
         result
     }
 
-    /// Implementation of get_all_inherited
-    fn get_all_inherited_aux<'a>(
-        &'a self,
+    /// Return the nearest class of the supplied class' inheritance hierarchy declaring a member with the identifier,
+    /// along with the members it declares with that identifier
+    /// Inherited classes are searched breadth first, in the order of the inheritance lists
+    pub fn find_member<'a>(
         class: &'a Class,
         id: &str,
+        search: Search,
         global_table: &'a SymbolTable,
-        visited: &mut HashSet<String>,
-    ) -> Vec<&'a SymbolTableEntry> {
-        let mut result = Vec::new();
+    ) -> Option<(&'a Class, Vec<&'a SymbolTableEntry>)> {
+        let (mut visited, mut queue) = Self::start_search(class, search, global_table);
+
+        while let Some(current) = queue.pop_front() {
+            let members: Vec<_> = current
+                .symbol_table()
+                .get_all(id)
+                .into_iter()
+                .filter(|entry| {
+                    matches!(
+                        entry,
+                        SymbolTableEntry::Data(_) | SymbolTableEntry::Function(_)
+                    )
+                })
+                .collect();
+            if !members.is_empty() {
+                return Some((current, members));
+            }
+            Self::queue_inherited(current, global_table, &mut visited, &mut queue);
+        }
+        None
+    }
+
+    /// The classes to search first, the supplied class or the classes it inherits
+    fn start_search<'a>(
+        class: &'a Class,
+        search: Search,
+        global_table: &'a SymbolTable,
+    ) -> (HashSet<String>, VecDeque<&'a Class>) {
+        let mut visited = hashset! {class.id().clone()};
+        let mut queue = VecDeque::new();
 
-        // search the current class
-        result.extend(class.symbol_table().get_all(id));
-        visited.insert(class.id().clone());
+        match search {
+            Search::Inclusive => queue.push_back(class),
+            Search::Exclusive => {
+                Self::queue_inherited(class, global_table, &mut visited, &mut queue)
+            }
+        }
+        (visited, queue)
+    }
 
-        // search the inherited classes recursively
+    /// Queue the classes inherited by the supplied class that were not searched yet
+    fn queue_inherited<'a>(
+        class: &'a Class,
+        global_table: &'a SymbolTable,
+        visited: &mut HashSet<String>,
+        queue: &mut VecDeque<&'a Class>,
+    ) {
         for inherited in class.inheritance_list() {
             if let Some(SymbolTableEntry::Class(class)) = global_table.get(inherited) {
-                if visited.contains(class.id()) {
-                    continue;
-                    // Signal a cyclic inheritance
+                if visited.insert(class.id().clone()) {
+                    queue.push_back(class);
                 }
-
-                result.extend(class.symbol_table().get_all_inherited_aux(
-                    class,
-                    id,
-                    global_table,
-                    visited,
-                ))
             }
         }
-
-        result
     }
 
     pub fn inherit_list_has_cycles(&self, global_table: &SymbolTable) -> bool {
@@ -3,9 +3,11 @@
 use crate::format_table::FormatTable;
 use crate::sizes;
 use derive_getters::Getters;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fmt;
 
-#[derive(Debug, Clone, Default, Getters)]
+#[derive(Debug, Clone, Default, Getters, Serialize)]
 pub struct Temporary {
     id: String,
     data_type: String,
@@ -40,8 +42,8 @@ impl Temporary {
         }
     }
 
-    pub fn computed_size(&mut self) -> usize {
-        let size = sizes::size_of(&self.data_type, &Vec::new());
+    pub fn computed_size(&mut self, class_sizes: &HashMap<String, usize>) -> usize {
+        let size = sizes::size_of(&self.data_type, &Vec::new(), class_sizes);
         self.bytes = size;
         size
     }
@@ -5,7 +5,8 @@ use crate::ast_validation::{ClassDeclaration, FunctionDefinition, ProgramRoot, T
 use crate::semantic_analyzer::SemanticAnalysisResults;
 use crate::semantic_error::SemanticError;
 
-use crate::symbol_table::symbol_table::{SymbolTable, SymbolTableEntry};
+use crate::symbol_table::class::Class;
+use crate::symbol_table::symbol_table::{Search, SymbolTable, SymbolTableEntry};
 
 use ast::Node;
 use output_manager::OutputConfig;
@@ -31,6 +32,7 @@ pub fn end_of_phase(current_data: &mut SemanticAnalysisResults, output_config: &
     //      - warn for overloads of functions higher in the class hierarchy
     //      - warn for overrides of functions higher in the class hierarchy
     //      - warn for shadowed functions and variables higher in the class hierarchy
+    // The whole hierarchy is walked, each warning names the member and the inherited member it hides
     //
     for entry in &current_data.symbol_table.values {
         if let SymbolTableEntry::Class(class) = entry {
@@ -93,26 +95,50 @@ pub fn end_of_phase(current_data: &mut SemanticAnalysisResults, output_config: &
                         let matches = SymbolTable::get_all_inherited(
                             class,
                             function.id(),
+                            Search::Exclusive,
                             &current_data.symbol_table,
                         );
-                        for matching_entry in matches {
+                        for (base, matching_entry) in matches {
+                            let member = qualified_name(class, class_entry);
+                            let hidden = qualified_name(base, matching_entry);
                             match matching_entry {
-                                SymbolTableEntry::Function(matching_function) => {
-                                    if matching_function == function {
-                                        let err = SemanticError::new_override(
+                                SymbolTableEntry::Function(matching_function)
+                                    if matching_function == function =>
+                                {
+                                    let err = SemanticError::new_override(
+                                        function.line(),
+                                        function.column(),
+                                        &member,
+                                        &hidden,
+                                    );
+                                    output_config.add(&err.to_string(), err.line(), err.col());
+
+                                    // Calls through the virtual table must agree on the returned value
+                                    if matching_function.return_type() != function.return_type() {
+                                        let err = SemanticError::new_incompatible_override(
                                             function.line(),
                                             function.column(),
-                                            &function.to_string(),
+                                            &member,
+                                            &hidden,
                                         );
                                         output_config.add(&err.to_string(), err.line(), err.col());
                                     }
                                 }
-                                SymbolTableEntry::Data(matching_variable) => {
+                                SymbolTableEntry::Function(_) => {
+                                    let err = SemanticError::new_inherited_overload(
+                                        function.line(),
+                                        function.column(),
+                                        &member,
+                                        &hidden,
+                                    );
+                                    output_config.add(&err.to_string(), err.line(), err.col());
+                                }
+                                SymbolTableEntry::Data(_) => {
                                     let err = SemanticError::new_shadowing(
                                         function.line(),
                                         function.column(),
-                                        &function.to_string(),
-                                        &matching_variable.to_string(),
+                                        &member,
+                                        &hidden,
                                     );
                                     output_config.add(&err.to_string(), err.line(), err.col());
                                 }
@@ -124,29 +150,20 @@ pub fn end_of_phase(current_data: &mut SemanticAnalysisResults, output_config: &
                         let matches = SymbolTable::get_all_inherited(
                             class,
                             variable.id(),
+                            Search::Exclusive,
                             &current_data.symbol_table,
                         );
-                        for matching_entry in matches {
-                            match matching_entry {
-                                SymbolTableEntry::Function(matching_function) => {
-                                    let err = SemanticError::new_shadowing(
-                                        variable.line(),
-                                        variable.column(),
-                                        &variable.to_string(),
-                                        &matching_function.to_string(),
-                                    );
-                                    output_config.add(&err.to_string(), err.line(), err.col());
-                                }
-                                SymbolTableEntry::Data(matching_variable) => {
-                                    let err = SemanticError::new_shadowing(
-                                        variable.line(),
-                                        variable.column(),
-                                        &variable.to_string(),
-                                        &matching_variable.to_string(),
-                                    );
-                                    output_config.add(&err.to_string(), err.line(), err.col());
-                                }
-                                _ => (),
+                        for (base, matching_entry) in matches {
+                            if let SymbolTableEntry::Function(_) | SymbolTableEntry::Data(_) =
+                                matching_entry
+                            {
+                                let err = SemanticError::new_shadowing(
+                                    variable.line(),
+                                    variable.column(),
+                                    &qualified_name(class, class_entry),
+                                    &qualified_name(base, matching_entry),
+                                );
+                                output_config.add(&err.to_string(), err.line(), err.col());
                             }
                         }
                     }
@@ -213,3 +230,131 @@ fn buffer_any_message(result: Result<(), SemanticError>, output: &mut OutputConf
         output.add(&err.to_string(), err.line(), err.col());
     }
 }
+
+/// The name of a class member qualified by its class, with the parameters of member functions
+fn qualified_name(class: &Class, member: &SymbolTableEntry) -> String {
+    match member {
+        SymbolTableEntry::Function(function) => {
+            format!("{}::{}{}", class.id(), function.id(), function.signature())
+        }
+        SymbolTableEntry::Data(data) => format!("{}::{}", class.id(), data.id()),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic_analyzer::tests::analyze_source;
+
+    #[test]
+    fn test_override_return_type_mismatch() {
+        let messages = analyze_source(
+            "override.src",
+            r#"
+class SHAPE {
+  public func area() : float;
+  public func sides() : integer;
+};
+
+class SQUARE inherits SHAPE {
+  public func area() : integer;
+  public func sides() : integer;
+};
+
+func SHAPE::area() : float
+{
+  return (0.0);
+}
+
+func SHAPE::sides() : integer
+{
+  return (0);
+}
+
+func SQUARE::area() : integer
+{
+  return (1);
+}
+
+func SQUARE::sides() : integer
+{
+  return (4);
+}
+
+main
+{
+  var
+  {
+    SHAPE b;
+    SQUARE s;
+  }
+  write(s.area());
+  write(s.sides());
+  write(b.area());
+  write(b.sides());
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic warning: 8:15 Member function \"SQUARE::area()\" provides override for inherited method \"SHAPE::area()\"",
+                "Semantic error: 8:15 Member function \"SQUARE::area()\" overrides \"SHAPE::area()\" with a different return type",
+                "Semantic warning: 9:15 Member function \"SQUARE::sides()\" provides override for inherited method \"SHAPE::sides()\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shadowing_through_the_hierarchy() {
+        // C only reaches A's members through B
+        let messages = analyze_source(
+            "shadowing.src",
+            r#"
+class A {
+  public integer size;
+  public func scale(integer factor) : integer;
+};
+
+class B inherits A {
+};
+
+class C inherits B {
+  public float size;
+  public func scale(float factor) : float;
+};
+
+func A::scale(integer factor) : integer
+{
+  return (factor);
+}
+
+func C::scale(float factor) : float
+{
+  return (factor);
+}
+
+main
+{
+  var
+  {
+    A a;
+    C c;
+  }
+  a.size = 1;
+  c.size = 1.0;
+  write(a.size + a.scale(2));
+  write(c.size);
+  write(c.scale(2.0));
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic warning: 11:10 \"C::size\" shadows inherited member \"A::size\"",
+                "Semantic warning: 12:15 Member function \"C::scale(float)\" provides overload for inherited method \"A::scale(integer)\"",
+            ]
+        );
+    }
+}
@@ -1,9 +1,13 @@
 //! Find the sizes needed to reserve adequate memory for the program to run
 //! Add literal and temporary values to the symbol table
 
+use crate::semantic_analyzer::{LOST_IDENTIFIER, LOST_LOCALS};
+use crate::symbol_table::object_layout::is_primary_base;
 use crate::SemanticAnalysisResults;
 use crate::SemanticError;
-use crate::{Function, Literal, LiteralValue, SymbolTable, SymbolTableEntry, Temporary};
+use crate::{
+    Function, Literal, LiteralValue, Search, SymbolTable, SymbolTableEntry, Temporary, Visibility,
+};
 use ast::{Data, Node};
 use log::info;
 use output_manager::OutputConfig;
@@ -22,13 +26,26 @@ pub fn process(
     visit(
         node,
         &mut current_results.symbol_table.clone(),
-        &mut State {},
+        &mut State {
+            class: None,
+            return_type: None,
+            lost_locals: Vec::new(),
+        },
         &mut current_results.symbol_table,
         output,
     )
 }
 
-pub struct State {}
+pub struct State {
+    /// The class of the member function being checked, its members are in scope without a dot
+    /// and its private members are accessible
+    class: Option<String>,
+    /// The return type of the function being checked, which the return statements must have
+    return_type: Option<String>,
+    /// The local variables of the function being checked whose declaration was lost to a syntax
+    /// error, their uses are left untyped without being reported as undefined
+    lost_locals: Vec<String>,
+}
 
 // Pass the global context around as a clone
 // When a node arrives that mutates a single table, it must replace the
@@ -86,6 +103,7 @@ pub fn visit(
         "addOp" => add_op(node, current_context, state, global_table, output),
         "mulOp" => mul_op(node, current_context, state, global_table, output),
         "relOp" => rel_op(node, current_context, state, global_table, output),
+        "sign" | "not" => unary_op(node, current_context, state, global_table, output),
         _ => {}
     }
 }
@@ -177,7 +195,11 @@ fn var_list(
     global_table: &mut SymbolTable,
     output: &mut OutputConfig,
 ) {
-    
+    // Every function body has a variable list, even if empty, which precedes its statements
+    state.lost_locals = node
+        .attribute(LOST_LOCALS)
+        .map(|ids| ids.split(' ').map(|id| id.to_string()).collect())
+        .unwrap_or_default();
 
     if let Data::Children(children) = node.data_mut() {
         for child in children {
@@ -311,38 +333,18 @@ fn var(
     // The node the precedes a . is a class type
     // the node that follows a . is a member of the class
 
-    // That means we should process them as a windows(2)
+    let line = *node.line();
+    let column = *node.column();
 
     if let Data::Children(children) = node.data_mut() {
-        for child in children.iter_mut() {
-            visit(child, context, state, global_table, output);
-        }
-
-        // TODO: This is very good! Come back to it in a bit
-        // for pair in children.windows(2) {
-
-        //     // left one needs to name a class
-        //     if let (Some(ld_type), Some(rd_type)) = (pair[0].data_type(), pair[1].data_type()) {
-        //         match global_table.get(&ld_type) {
-        //             Some(SymbolTableEntry::Class(class)) => {
-        //                 // Now we must be sure that the id of the next node is valid in the scope of the class
-
-        //             }, // Ok!
-        //             Some(entry) => (), // trying to use a dot operator on a non composite type
-        //             None => (), // Undefined identifer? Shouldn't happen since the dataMember or fCall needs to look up the id to be valid and the id must be a local
-        //         }
-        //     } else {
-        //         // There's a problem
-        //     }
+        visit(&mut children[0], context, state, global_table, output);
+        dot_chain(children, context, state, global_table, output);
 
-        // }
+        let label = variable_label(children, context, line, column);
+        let last = children.last().unwrap();
+        let dim = last.dimensions();
 
-        // if children.len() > 1 {
-        //     panic!("My assumption was wrong");
-        // }
-        let dim = children[0].dimensions();
-
-        if let (Some(d_type), Some(label)) = (children[0].data_type(), children[0].label()) {
+        if let (Some(d_type), Some(label)) = (last.data_type(), label) {
             node.set_type(&d_type);
             node.set_label(&label);
         } else {
@@ -355,6 +357,295 @@ fn var(
     }
 }
 
+/// Check the elements of a variable following the first one, each must be a member of the
+/// class type of the element preceding its dot
+fn dot_chain(
+    children: &mut [Node],
+    context: &mut SymbolTable,
+    state: &mut State,
+    global_table: &mut SymbolTable,
+    output: &mut OutputConfig,
+) {
+    for index in 1..children.len() {
+        let (preceding, following) = children.split_at_mut(index);
+        let class_type = preceding[index - 1].data_type();
+        dot_member(&mut following[0], class_type, context, state, global_table, output);
+    }
+}
+
+/// Whether the value of a variable is found through the address of an object rather than at a label,
+/// that is a data member following a dot or a member of the object of the member function
+fn is_indirect(children: &[Node]) -> bool {
+    let last = children.last().unwrap();
+    last.name() == "dataMember"
+        && (children.len() > 1 || children[0].attribute("member").is_some())
+}
+
+/// The label holding the value of a variable, the values found through the address of an object
+/// are loaded in a new temporary
+fn variable_label(
+    children: &[Node],
+    context: &mut SymbolTable,
+    line: usize,
+    column: usize,
+) -> Option<String> {
+    let last = children.last().unwrap();
+    match last.data_type() {
+        Some(d_type) if is_indirect(children) && d_type != "error-type" => {
+            Some(new_temporary(context, &d_type, line, column))
+        }
+        _ => last.label(),
+    }
+}
+
+/// Add a temporary of the supplied type to the context and return its label
+fn new_temporary(context: &mut SymbolTable, d_type: &str, line: usize, column: usize) -> String {
+    let new_name = context.get_next_temporary();
+    let temp = Temporary::new(&new_name, d_type, line, column);
+    context.add_entry(SymbolTableEntry::Temporary(temp));
+    context.get_previous_mangled_name()
+}
+
+/// Record the slot of the virtual table through which the code generation calls a member function
+/// on an object of the class, the override of the class of the object is reached this way
+///
+/// The data members of the classes inherited after the first are copied into the objects of the
+/// class rather than laid out as in their own objects, so the member functions of these classes
+/// can't be called on them.
+fn set_virtual_call(
+    node: &mut Node,
+    class_id: &str,
+    function: &Function,
+    global_table: &SymbolTable,
+    output: &mut OutputConfig,
+) {
+    if let Some(SymbolTableEntry::Class(class)) = global_table.get(class_id) {
+        let parameter_types: Vec<String> = function
+            .parameter_types()
+            .iter()
+            .map(|p| p.data_type().clone())
+            .collect();
+        if let Some(slot) = class.virtual_slot(function.id(), &parameter_types) {
+            let implementation = class.virtual_table()[slot].implementation();
+            if !is_primary_base(implementation, class_id, global_table) {
+                let err = SemanticError::new_secondary_base_call(
+                    node.line(),
+                    node.column(),
+                    &function.qualified_name(),
+                    class_id,
+                );
+                output.add(&err.to_string(), err.line(), err.col());
+            }
+            node.set_attribute("class", class_id);
+            node.set_attribute("slot", &slot.to_string());
+        }
+    }
+}
+
+/// Check a dataMember or fCall following a dot against the members of the class type
+/// Its indices and arguments are evaluated in the context of the enclosing function
+fn dot_member(
+    node: &mut Node,
+    class_type: Option<String>,
+    context: &mut SymbolTable,
+    state: &mut State,
+    global_table: &mut SymbolTable,
+    output: &mut OutputConfig,
+) {
+    let line = *node.line();
+    let column = *node.column();
+    let name = node.name().clone();
+
+    let class_id = match class_type {
+        Some(class_id) => match global_table.get(&class_id) {
+            Some(SymbolTableEntry::Class(_)) => class_id,
+            _ => {
+                // Undefined types were reported where they were declared
+                if let INTEGER | FLOAT | STRING = class_id.as_str() {
+                    let err = SemanticError::new_incorrect_type(line, column, &class_id, "class");
+                    output.add(&err.to_string(), err.line(), err.col());
+                }
+                node.set_type("error-type");
+                return;
+            }
+        },
+        None => {
+            node.set_type("error-type");
+            return;
+        }
+    };
+
+    let mut member_type = None;
+    let mut member_label = None;
+    let mut declaring = None;
+    let mut called = None;
+    if let Data::Children(children) = node.data_mut() {
+        let member_id = if let Data::String(id) = children[0].data() {
+            id.clone()
+        } else {
+            panic!();
+        };
+
+        if name == "dataMember" {
+            visit(&mut children[1], context, state, global_table, output);
+
+            match member_data(&class_id, &member_id, global_table) {
+                Some((declaring_class, data)) => {
+                    check_access(
+                        data.visibility(),
+                        &member_id,
+                        &declaring_class,
+                        line,
+                        column,
+                        state,
+                        output,
+                    );
+                    if let Some(dimensions) = children[1].dimensions() {
+                        if data.dimension().len() != dimensions {
+                            let err = SemanticError::new_invalid_array_dimension(
+                                &line,
+                                &column,
+                                &dimensions,
+                                &data.dimension().len(),
+                            );
+                            output.add(&err.to_string(), err.line(), err.col());
+                        }
+                    }
+                    let label = format!("{}__{}", declaring_class, member_id);
+                    children[0].set_type(data.data_type());
+                    children[0].set_label(&label);
+                    member_type = Some(data.data_type().clone());
+                    member_label = Some(label);
+                    declaring = Some(declaring_class);
+                }
+                None => {
+                    let err =
+                        SemanticError::new_undefined_member(&line, &column, &member_id, &class_id);
+                    output.add(&err.to_string(), err.line(), err.col());
+                }
+            }
+        } else {
+            a_params_children(&mut children[1], context, state, global_table, output);
+
+            let parameters = if let Data::Children(parameters) = children[1].data() {
+                parameters.clone()
+            } else {
+                Vec::new()
+            };
+
+            match select_member_overload(&class_id, &member_id, &parameters, global_table) {
+                Ok((declaring_class, function)) => {
+                    if let Some(visibility) = function.visibility() {
+                        check_access(
+                            visibility,
+                            &member_id,
+                            &declaring_class,
+                            line,
+                            column,
+                            state,
+                            output,
+                        );
+                    }
+                    function_id(&mut children[0], context, state, global_table, output, &function);
+                    a_params_correct(
+                        &mut children[1],
+                        context,
+                        state,
+                        global_table,
+                        output,
+                        &function,
+                    );
+                    member_type = function.return_type().clone();
+                    called = Some(function);
+                }
+                Err(Some(_)) => {
+                    let parameter_str = argument_types(&parameters);
+
+                    let err =
+                        SemanticError::new_no_overload(line, column, &member_id, &parameter_str);
+                    output.add(&err.to_string(), err.line(), err.col());
+                }
+                Err(None) => {
+                    let err =
+                        SemanticError::new_undefined_member(&line, &column, &member_id, &class_id);
+                    output.add(&err.to_string(), err.line(), err.col());
+                }
+            }
+        }
+    }
+
+    if let Some(declaring_class) = declaring {
+        node.set_attribute("member", &declaring_class);
+    }
+    if let Some(function) = called {
+        node.set_attribute("definition", &function.qualified_name());
+        set_virtual_call(node, &class_id, &function, global_table, output);
+        if let Some(d_type) = function.return_type() {
+            member_label = Some(new_temporary(context, d_type, line, column));
+        }
+    }
+
+    match member_type {
+        Some(d_type) => node.set_type(&d_type),
+        None => node.set_type("error-type"),
+    }
+    if let Some(label) = member_label {
+        node.set_label(&label);
+    }
+}
+
+/// Report an access to a private member from outside the member functions of the class declaring it,
+/// following the rules described on Visibility
+fn check_access(
+    visibility: &Visibility,
+    member: &str,
+    declaring_class: &str,
+    line: usize,
+    column: usize,
+    state: &State,
+    output: &mut OutputConfig,
+) {
+    if let Visibility::Private = visibility {
+        if state.class.as_deref() != Some(declaring_class) {
+            let err = SemanticError::new_private_member_access(
+                &line,
+                &column,
+                member,
+                declaring_class,
+            );
+            output.add(&err.to_string(), err.line(), err.col());
+        }
+    }
+}
+
+/// Find a data member of the class or of the nearest class it inherits it from,
+/// along with the class declaring it
+fn member_data(
+    class_id: &str,
+    member_id: &str,
+    global_table: &SymbolTable,
+) -> Option<(String, crate::Data)> {
+    if let Some(SymbolTableEntry::Class(class)) = global_table.get(class_id) {
+        let (declaring_class, members) =
+            SymbolTable::find_member(class, member_id, Search::Inclusive, global_table)?;
+        members.into_iter().find_map(|member| match member {
+            SymbolTableEntry::Data(data) => Some((declaring_class.id().clone(), data.clone())),
+            _ => None,
+        })
+    } else {
+        None
+    }
+}
+
+/// Find a data member of the class of the member function being checked
+fn implicit_member_data(
+    member_id: &str,
+    state: &State,
+    global_table: &SymbolTable,
+) -> Option<(String, crate::Data)> {
+    member_data(state.class.as_ref()?, member_id, global_table)
+}
+
 fn data_member(
     node: &mut Node,
     context: &mut SymbolTable,
@@ -422,12 +713,41 @@ fn data_member(
                     "Id \"{}\" is naming something it shouldn't \"{}\"",
                     id, entry
                 ), // Bad, but this shouldn't happen (likely culprit is collision with temporary)
-                None => {
-                    let err =
-                        SemanticError::new_undefined_identifier(node.line(), node.column(), &id);
-                    output.add(&err.to_string(), err.line(), err.col());
-                    node.set_type("error-type");
-                }
+                None => match implicit_member_data(&id, state, global_table) {
+                    Some((declaring_class, data)) => {
+                        node.set_attribute("member", &declaring_class);
+                        check_access(
+                            data.visibility(),
+                            &id,
+                            &declaring_class,
+                            *node.line(),
+                            *node.column(),
+                            state,
+                            output,
+                        );
+                        if let Some(dimensions) = index_list_clone.dimensions() {
+                            if data.dimension().len() != dimensions {
+                                let err = SemanticError::new_invalid_array_dimension(
+                                    node.line(),
+                                    node.column(),
+                                    &dimensions,
+                                    &data.dimension().len(),
+                                );
+                                output.add(&err.to_string(), err.line(), err.col());
+                            }
+                        }
+                    }
+                    None if is_lost_local(&id, state) => node.set_type("error-type"),
+                    None => {
+                        let err = SemanticError::new_undefined_identifier(
+                            node.line(),
+                            node.column(),
+                            &id,
+                        );
+                        output.add(&err.to_string(), err.line(), err.col());
+                        node.set_type("error-type");
+                    }
+                },
             }
         }
 
@@ -468,6 +788,27 @@ fn add_op(
     }
 }
 
+/// The sign and the negation have the type of their operand
+fn unary_op(
+    node: &mut Node,
+    context: &mut SymbolTable,
+    state: &mut State,
+    global_table: &mut SymbolTable,
+    output: &mut OutputConfig,
+) {
+    if let Data::Children(children) = node.data_mut() {
+        for child in children.iter_mut() {
+            visit(child, context, state, global_table, output);
+        }
+
+        // The operand follows the sign
+        match children.last().and_then(|operand| operand.data_type()) {
+            Some(d_type) => node.set_type(&d_type),
+            None => node.set_type("error-type"),
+        }
+    }
+}
+
 fn mul_op(
     node: &mut Node,
     context: &mut SymbolTable,
@@ -717,8 +1058,8 @@ fn mandatory_indexlist(
 fn id(
     node: &mut Node,
     context: &mut SymbolTable,
-    _state: &mut State,
-    _global_table: &mut SymbolTable,
+    state: &mut State,
+    global_table: &mut SymbolTable,
     output: &mut OutputConfig,
 ) {
     // Fetch the type from the context and set the node to the type
@@ -746,11 +1087,20 @@ fn id(
                 "Id \"{}\" is colliding with something it shouldn't \"{}\"",
                 id, entry
             ), // Bad, but this shouldn't happen (likely culprit is collision with temporary)
-            None => {
-                let err = SemanticError::new_undefined_identifier(node.line(), node.column(), id);
-                output.add(&err.to_string(), err.line(), err.col());
-                node.set_type("error-type");
-            }
+            None => match implicit_member_data(id, state, global_table) {
+                Some((declaring_class, data)) => {
+                    let label = format!("{}__{}", declaring_class, id);
+                    node.set_type(data.data_type());
+                    node.set_label(&label);
+                }
+                None if is_lost_local(&id, state) => node.set_type("error-type"),
+                None => {
+                    let err =
+                        SemanticError::new_undefined_identifier(node.line(), node.column(), id);
+                    output.add(&err.to_string(), err.line(), err.col());
+                    node.set_type("error-type");
+                }
+            },
         }
     }
 }
@@ -841,7 +1191,32 @@ fn f_call(
         // Now that the parameters have had their types determined
         // We should select the correct overload based on them and supply that to both
         // following functions
-        let f = select_free_overload(&function_id_str, &parameters, global_table);
+        // Inside a member function, the members of its class hide the free functions
+        let member = match &state.class {
+            Some(class_id) => {
+                select_member_overload(class_id, &function_id_str, &parameters, global_table)
+            }
+            None => Err(None),
+        };
+        let receiver_class = if member.is_ok() { state.class.clone() } else { None };
+        let f = match member {
+            Ok((declaring_class, matching_function)) => {
+                if let Some(visibility) = matching_function.visibility() {
+                    check_access(
+                        visibility,
+                        &function_id_str,
+                        &declaring_class,
+                        *nc.line(),
+                        *nc.column(),
+                        state,
+                        output,
+                    );
+                }
+                Ok(matching_function)
+            }
+            Err(None) => select_free_overload(&function_id_str, &parameters, global_table),
+            Err(Some(_)) => Err(Some(())),
+        };
         match f {
             Ok(matching_function) => {
                 // println!("Selected overload for {:?} is {:?}", nc, matching_function);
@@ -864,14 +1239,25 @@ fn f_call(
                 );
                 if let Some(d_type) = matching_function.return_type() {
                     node.set_type(d_type);
+                    let label = new_temporary(context, d_type, *nc.line(), *nc.column());
+                    node.set_label(&label);
+                }
+                // The function called, for the cross references
+                node.set_attribute("definition", &matching_function.qualified_name());
+
+                match receiver_class {
+                    // Called on the object of the member function being checked
+                    Some(class_id) => {
+                        set_virtual_call(node, &class_id, &matching_function, global_table, output)
+                    }
+                    None => node.set_attribute(
+                        "function",
+                        &matching_function.symbol_table().entry_label(),
+                    ),
                 }
             }
             Err(Some(_)) => {
-                let parameter_str = parameters
-                    .iter()
-                    .map(|n| n.data_type().unwrap())
-                    .collect::<Vec<_>>()
-                    .join(", ");
+                let parameter_str = argument_types(&parameters);
 
                 let err = SemanticError::new_no_overload(
                     *node.line(),
@@ -949,28 +1335,36 @@ fn parameter_var_exception(
 ) {
     // This is an exceptional path that need to dispatch dataMembers to a special case
     // ONLY to be used for visiting nodes of a parameter list
+    let line = *node.line();
+    let column = *node.column();
+
     if let Data::Children(children) = node.data_mut() {
-        for child in children.iter_mut() {
-            match child.name().as_str() {
-                "dataMember" => {
-                    parameter_data_member_exception(child, context, state, global_table, output)
-                }
-                _ => visit(child, context, state, global_table, output),
-            }
+        match children[0].name().as_str() {
+            "dataMember" => parameter_data_member_exception(
+                &mut children[0],
+                context,
+                state,
+                global_table,
+                output,
+            ),
+            _ => visit(&mut children[0], context, state, global_table, output),
         }
 
-        if children.len() != 1 {
-            panic!("Don't forget dot expressions");
-            // in which case this node takes the type of the last determined child
-        }
+        // In a dot expression this node takes the type of the last determined child
+        dot_chain(children, context, state, global_table, output);
 
-        let dim = children[0].dimensions();
+        let label = variable_label(children, context, line, column);
+        let last = children.last().unwrap();
+        let dim = last.dimensions();
 
-        if let Some(d_type) = children[0].data_type() {
+        if let Some(d_type) = last.data_type() {
             node.set_type(&d_type);
         } else {
             node.set_type("error-type");
         }
+        if let Some(label) = label {
+            node.set_label(&label);
+        }
 
         if let Some(dimension) = dim {
             node.set_dimensions(&dimension);
@@ -1064,12 +1458,45 @@ fn parameter_data_member_exception(
                     "Id \"{}\" is naming something it shouldn't \"{}\"",
                     id, entry
                 ), // Bad, but this shouldn't happen (likely culprit is collision with temporary)
-                None => {
-                    let err =
-                        SemanticError::new_undefined_identifier(node.line(), node.column(), &id);
-                    output.add(&err.to_string(), err.line(), err.col());
-                    node.set_type("error-type");
-                }
+                None => match implicit_member_data(&id, state, global_table) {
+                    Some((declaring_class, data)) => {
+                        node.set_attribute("member", &declaring_class);
+                        check_access(
+                            data.visibility(),
+                            &id,
+                            &declaring_class,
+                            *node.line(),
+                            *node.column(),
+                            state,
+                            output,
+                        );
+                        match index_list_clone.dimensions() {
+                            Some(dimensions)
+                                if dimensions != 0 && data.dimension().len() != dimensions =>
+                            {
+                                let err = SemanticError::new_invalid_array_dimension(
+                                    node.line(),
+                                    node.column(),
+                                    &dimensions,
+                                    &data.dimension().len(),
+                                );
+                                output.add(&err.to_string(), err.line(), err.col());
+                            }
+                            Some(dimensions) if dimensions != 0 => (),
+                            _ => node.set_dimensions(&data.dimension().len()),
+                        }
+                    }
+                    None if is_lost_local(&id, state) => node.set_type("error-type"),
+                    None => {
+                        let err = SemanticError::new_undefined_identifier(
+                            node.line(),
+                            node.column(),
+                            &id,
+                        );
+                        output.add(&err.to_string(), err.line(), err.col());
+                        node.set_type("error-type");
+                    }
+                },
             }
         }
     }
@@ -1196,29 +1623,33 @@ fn func_def(
         parameter_list.push(n);
     }
 
-    let function_id_str = validated_node.id().to_owned();
-    let mut return_type = None;
+    let (function_id, scope) = validated_node.get_corrected_scoped_id();
+    let function_id_str = function_id.to_owned();
+    let scope = scope.map(|scope| scope.to_owned());
 
     if let Data::Children(children) = node.data_mut() {
         // I think all of the checking has already been done in the symbol table assembly
         // so we just need to get the right context by supplying
 
-        match select_free_overload_mut(&function_id_str, &parameter_list, context) {
+        // Member functions are found in the table of their class
+        let function_table = match &scope {
+            Some(scope) => match context.get_mut(scope) {
+                Some(SymbolTableEntry::Class(class)) => class.symbol_table_mut(),
+                _ => return, // Already reported while assembling the symbol table
+            },
+            None => &mut *context,
+        };
+
+        match select_free_overload_mut(&function_id_str, &parameter_list, function_table) {
             Ok(matching_function) => {
+                let enclosing_class = std::mem::replace(&mut state.class, scope);
+                let enclosing_return_type = std::mem::replace(
+                    &mut state.return_type,
+                    matching_function.return_type().clone(),
+                );
                 for child in children.iter_mut() {
                     match child.name().as_str() {
                         "id" => (),
-                        "funcBody" => {
-                            // This will set the return type of the function
-                            visit(
-                                child,
-                                matching_function.symbol_table_mut(),
-                                state,
-                                global_table,
-                                output,
-                            );
-                            return_type = child.data_type();
-                        }
                         _ => visit(
                             child,
                             matching_function.symbol_table_mut(),
@@ -1230,28 +1661,14 @@ fn func_def(
                     // We don't actually need to visit the id of the function
                     // since all the verification was done for it already
                 }
-
-                if return_type != *matching_function.return_type() {
-                    let err = SemanticError::new_incorrect_type(
-                        *node.line(),
-                        *node.column(),
-                        &return_type.clone().unwrap_or("void".to_owned()),
-                        &matching_function
-                            .return_type()
-                            .clone()
-                            .unwrap_or("void".to_owned()),
-                    );
-                    output.add(&err.to_string(), err.line(), err.col());
-                }
+                state.class = enclosing_class;
+                state.return_type = enclosing_return_type;
+                // The functions that may end without returning are reported by the flow analysis
 
                 *global_table = context.clone();
             }
             Err(Some(_)) => {
-                let parameter_str = parameter_list
-                    .iter()
-                    .map(|n| n.data_type().unwrap())
-                    .collect::<Vec<_>>()
-                    .join(", ");
+                let parameter_str = argument_types(&parameter_list);
 
                 let err =
                     SemanticError::new_no_overload(line, column, &function_id_str, &parameter_str);
@@ -1279,6 +1696,15 @@ fn return_stat(
             r_type = child.data_type();
         }
     }
+    if r_type != state.return_type {
+        let err = SemanticError::new_incorrect_type(
+            *node.line(),
+            *node.column(),
+            r_type.as_deref().unwrap_or("void"),
+            state.return_type.as_deref().unwrap_or("void"),
+        );
+        output.add(&err.to_string(), err.line(), err.col());
+    }
     if let Some(r_type) = r_type {
         node.set_type(&r_type);
     }
@@ -1333,7 +1759,10 @@ fn check_binary_types(
         panic!();
     };
 
-    if lht != rht {
+    if lht == "error-type" || rht == "error-type" {
+        // The operand's error was reported already
+        Err(())
+    } else if lht != rht {
         let err = SemanticError::new_binary_type_error(rhs.line(), rhs.column(), &lht, &rht);
         output.add(&err.to_string(), err.line(), err.col());
         
@@ -1346,52 +1775,120 @@ fn check_binary_types(
     }
 }
 
+fn is_lost_local(id: &str, state: &State) -> bool {
+    state
+        .lost_locals
+        .iter()
+        .any(|lost| lost == id || lost == LOST_IDENTIFIER)
+}
+
+/// The types of the arguments of a call, listing those that couldn't be typed as errors
+fn argument_types(arguments: &[Node]) -> String {
+    arguments
+        .iter()
+        .map(|n| n.data_type().unwrap_or_else(|| "error-type".to_string()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn select_free_overload(
     function_id: &str,
     parameters: &[Node],
     global_table: &SymbolTable,
+) -> Result<Function, Option<()>> {
+    select_overload(
+        function_id,
+        parameters,
+        global_table.get_all(function_id),
+        global_table,
+    )
+}
+
+/// Select the overload of a member function of the class or of the nearest class it inherits it from,
+/// along with the class declaring it
+fn select_member_overload(
+    class_id: &str,
+    function_id: &str,
+    parameters: &[Node],
+    global_table: &SymbolTable,
+) -> Result<(String, Function), Option<()>> {
+    if let Some(SymbolTableEntry::Class(class)) = global_table.get(class_id) {
+        if let Some((declaring_class, members)) =
+            SymbolTable::find_member(class, function_id, Search::Inclusive, global_table)
+        {
+            let candidates = members
+                .into_iter()
+                .filter(|member| matches!(member, SymbolTableEntry::Function(_)))
+                .collect();
+            return select_overload(function_id, parameters, candidates, global_table)
+                .map(|function| (declaring_class.id().clone(), function));
+        }
+    }
+    Err(None)
+}
+
+/// Select the overload whose parameters have the types of the arguments, or else the one whose
+/// parameters are base classes of the arguments objects can stand in for
+fn select_overload(
+    function_id: &str,
+    parameters: &[Node],
+    matches: Vec<&SymbolTableEntry>,
+    global_table: &SymbolTable,
 ) -> Result<Function, Option<()>> {
     // println!(
     //     "Trying to find correct overload of {}{:?}",
     //     function_id, parameters
     // );
 
-    let matches = global_table.get_all(&function_id);
-    if matches.len() == 0 {
+    if matches.is_empty() {
         return Err(None);
     }
 
-    for matching_entry in matches {
-        match matching_entry {
-            SymbolTableEntry::Function(function) => {
-                //println!("Checking candidate {:?}", function);
+    for exact in &[true, false] {
+        for matching_entry in &matches {
+            match matching_entry {
+                SymbolTableEntry::Function(function) => {
+                    //println!("Checking candidate {:?}", function);
 
-                // we must be sure the data type of the params are the same as for the function
-                if function.parameter_types().len() != parameters.len() {
-                    continue; // bad candidate, length mismatch
-                }
+                    // we must be sure the data type of the params are the same as for the function
+                    if function.parameter_types().len() != parameters.len() {
+                        continue; // bad candidate, length mismatch
+                    }
 
-                let mut parameter_failure = false;
+                    let mut parameter_failure = false;
 
-                for (param_node, st_entry) in parameters.iter().zip(function.parameter_types()) {
-                    //println!("Checking parameter n:{:?} st:{:?}", param_node, st_entry);
+                    for (param_node, st_entry) in parameters.iter().zip(function.parameter_types())
+                    {
+                        //println!("Checking parameter n:{:?} st:{:?}", param_node, st_entry);
 
-                    // The specifically ignores the array dimensionality
-                    if param_node.data_type().unwrap() != *st_entry.data_type() {
-                        //println!("Skipping candidate because type mismatch");
-                        parameter_failure = true;
+                        // The specifically ignores the array dimensionality
+                        let d_type = match param_node.data_type() {
+                            Some(d_type) => d_type,
+                            None => {
+                                // Left untyped by an error, no overload matches it
+                                parameter_failure = true;
+                                continue;
+                            }
+                        };
+                        if d_type != *st_entry.data_type()
+                            && (*exact
+                                || !is_primary_base(st_entry.data_type(), &d_type, global_table))
+                        {
+                            //println!("Skipping candidate because type mismatch");
+                            parameter_failure = true;
+                        }
                     }
-                }
 
-                if !parameter_failure {
-                    //println!("Candidate confirmed");
-                    return Ok(function.clone());
+                    if !parameter_failure {
+                        //println!("Candidate confirmed");
+                        return Ok(function.clone());
+                    }
                 }
+                entry => panic!(
+                    "Id \"{}\" is colliding with something it shouldn't \"{}\"",
+                    function_id, entry
+                ), // Bad, but this shouldn't happen (likely culprit is collision with temporary)
             }
-            entry => panic!(
-                "Id \"{}\" is colliding with something it shouldn't \"{}\"",
-                function_id, entry
-            ), // Bad, but this shouldn't happen (likely culprit is collision with temporary)
         }
     }
     Err(Some(()))
@@ -1428,7 +1925,7 @@ fn select_free_overload_mut<'a>(
                     //println!("Checking parameter n:{:?} st:{:?}", param_node, st_entry);
 
                     // The specifically ignores the array dimensionality
-                    if param_node.data_type().unwrap() != *st_entry.data_type() {
+                    if param_node.data_type().as_ref() != Some(st_entry.data_type()) {
                         //println!("Skipping candidate because type mismatch");
                         parameter_failure = true;
                     }
@@ -1439,6 +1936,8 @@ fn select_free_overload_mut<'a>(
                     return Ok(function);
                 }
             }
+            // Member variables sharing the name were reported while assembling the class table
+            SymbolTableEntry::Data(_) => (),
             entry => panic!(
                 "Id \"{}\" is colliding with something it shouldn't \"{}\"",
                 function_id, entry
@@ -1447,3 +1946,62 @@ fn select_free_overload_mut<'a>(
     }
     Err(Some(()))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic_analyzer::tests::analyze_source;
+
+    #[test]
+    fn test_private_member_access() {
+        let messages = analyze_source(
+            "private_access.src",
+            r#"
+class BASE {
+  private integer secret;
+  public integer open;
+  private func hidden() : integer;
+  public func reveal() : integer;
+};
+
+class DERIVED inherits BASE {
+  public func peek() : integer;
+};
+
+func BASE::hidden() : integer
+{
+  return (secret);
+}
+
+func BASE::reveal() : integer
+{
+  return (hidden());
+}
+
+func DERIVED::peek() : integer
+{
+  return (secret);
+}
+
+main
+{
+  var
+  {
+    DERIVED d;
+  }
+  d.open = 1;
+  write(d.secret);
+  write(d.hidden());
+  write(d.reveal() + d.peek() + d.open);
+}
+"#,
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "Semantic error: 25:11 Private member \"secret\" of class \"BASE\" is not accessible here",
+                "Semantic error: 35:11 Private member \"secret\" of class \"BASE\" is not accessible here",
+                "Semantic error: 36:11 Private member \"hidden\" of class \"BASE\" is not accessible here",
+            ]
+        );
+    }
+}
@@ -1,6 +1,13 @@
+use serde::Serialize;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+/// Visibility of a class member
+///
+/// Public members are accessible wherever the class is. Private members are accessible only
+/// in the member functions of the class declaring them, so not in those of its derived classes.
+/// Inherited members keep the visibility they were declared with.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Visibility {
     Public,
     Private,
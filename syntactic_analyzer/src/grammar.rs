@@ -5,7 +5,7 @@ use std::io::Read;
 use std::io::{BufRead, BufReader};
 
 const MAX_FOLLOW_EXPANSIONS: usize = 1000000;
-type Sentence = Vec<Symbol>;
+pub type Sentence = Vec<Symbol>;
 
 #[derive(Debug, PartialEq)]
 pub struct Grammar {
@@ -17,6 +17,23 @@ pub struct Grammar {
 
 impl Grammar {
     pub fn new(productions: &HashMap<Symbol, Vec<Sentence>>, start_symbol: &Symbol) -> Grammar {
+        Grammar::build(productions, start_symbol, None)
+    }
+
+    /// Create a grammar whose follow sets were computed beforehand, see table_cache
+    pub fn with_follow_sets(
+        productions: &HashMap<Symbol, Vec<Sentence>>,
+        start_symbol: &Symbol,
+        follow_sets: HashMap<Symbol, HashSet<Symbol>>,
+    ) -> Grammar {
+        Grammar::build(productions, start_symbol, Some(follow_sets))
+    }
+
+    fn build(
+        productions: &HashMap<Symbol, Vec<Sentence>>,
+        start_symbol: &Symbol,
+        precomputed_follow_sets: Option<HashMap<Symbol, HashSet<Symbol>>>,
+    ) -> Grammar {
         // Check if a non terminal appears on a RHS and not any LHS
         // Check if any RHS are empty (auto remove with warning)
         if !productions.contains_key(start_symbol) {
@@ -27,6 +44,9 @@ impl Grammar {
             panic!();
         }
 
+        // Report every undefined symbol before giving up, a detailed report is
+        // available through check_grammar
+        let mut undefined_symbols = false;
         for (producing_symbol, production) in productions {
             for option in production {
                 if option.is_empty() {
@@ -40,12 +60,16 @@ impl Grammar {
                 {
                     if !productions.contains_key(s) {
                         error!("Non terminal symbol {:?} does not appear on the left hand side of any productions", s);
-                        panic!();
+                        undefined_symbols = true;
                     }
                 }
             }
         }
 
+        if undefined_symbols {
+            panic!();
+        }
+
         let mut productions_without_semantics: HashMap<Symbol, Vec<Sentence>> = HashMap::new();
         for (producing_symbol, options) in productions
             .iter()
@@ -71,7 +95,8 @@ impl Grammar {
             }
         }
 
-        let follow_sets = follow_sets(&productions_without_semantics, start_symbol);
+        let follow_sets = precomputed_follow_sets
+            .unwrap_or_else(|| follow_sets(&productions_without_semantics, start_symbol));
 
         Grammar {
             productions: productions.clone(),
@@ -89,6 +114,10 @@ impl Grammar {
         &self.productions
     }
 
+    pub fn follow_sets(&self) -> &HashMap<Symbol, HashSet<Symbol>> {
+        &self.follow_sets
+    }
+
     pub fn follow(&self, s: &Symbol) -> &HashSet<Symbol> {
         if matches!(s, Symbol::SemanticAction(_)) {
             error!("Follow set requested for semantic action");
@@ -116,80 +145,130 @@ impl Grammar {
         &self.productions[non_terminal][option]
     }
 
-    pub fn from_reader<R: Read>(stream: R) -> std::io::Result<Self> {
-        let buf_reader = BufReader::new(stream);
-        let mut productions: HashMap<Symbol, Vec<Sentence>> = HashMap::new();
-        let mut first = true;
-        let mut start_symbol = Symbol::Eos;
-        let mut production_count = 0;
-
-        for line in buf_reader.lines() {
-            let line = match line {
-                Ok(line) => line,
-                Err(err) => {
-                    error!("Error while reading stream: {}", err);
-                    panic!();
+    /// The index of the production of a non-terminal deriving the empty string, if any
+    pub fn epsilon_production(&self, non_terminal: &Symbol) -> Option<usize> {
+        self.production_without_semantics
+            .get(non_terminal)?
+            .iter()
+            .position(|sentence| self.sentence_first(sentence).contains(&Symbol::Epsilon))
+    }
+
+    /// The terminals, or end of file, which may come right after a terminal
+    pub fn terminal_follow(&self, terminal: &Symbol) -> HashSet<Symbol> {
+        let mut result = HashSet::new();
+        for (non_terminal, sentences) in &self.production_without_semantics {
+            for sentence in sentences {
+                for (index, _) in sentence.iter().enumerate().filter(|(_, s)| *s == terminal) {
+                    let rest_first = self.sentence_first(&sentence[index + 1..].to_vec());
+                    if rest_first.is_empty() || rest_first.contains(&Symbol::Epsilon) {
+                        result.extend(self.follow(non_terminal).iter().cloned());
+                    }
+                    result.extend(rest_first.into_iter().filter(|s| *s != Symbol::Epsilon));
                 }
-            };
+            }
+        }
+        result
+    }
+
+    pub fn from_reader<R: Read>(stream: R) -> std::io::Result<Self> {
+        let (productions, start_symbol) = read_productions(stream)?;
+        Ok(Grammar::new(&productions, &start_symbol))
+    }
+}
 
-            if line.len() == 0 {
-                continue;
+/// Read the productions of a grammar file without validating them
+///
+/// The start symbol is the left-hand side of the first production. Invalid semantic
+/// actions are reported with their production and fail the reading.
+pub fn read_productions<R: Read>(
+    stream: R,
+) -> std::io::Result<(HashMap<Symbol, Vec<Sentence>>, Symbol)> {
+    let buf_reader = BufReader::new(stream);
+    let mut productions: HashMap<Symbol, Vec<Sentence>> = HashMap::new();
+    let mut first = true;
+    let mut start_symbol = Symbol::Eos;
+    let mut production_count = 0;
+    let mut invalid_actions = false;
+
+    for line in buf_reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Error while reading stream: {}", err);
+                panic!();
             }
+        };
 
-            // Split the line at the equals
-            let parts: Vec<&str> = line.split("::=").map(|x| x.trim()).collect();
-            if parts.len() > 1 {
-                let lhs = parts[0];
-                let rhs = parts[1]
-                    .split(" ")
-                    .map(|x| x.trim())
-                    .filter(|x| !x.is_empty());
-                let lhs_symbol;
-
-                match lhs.parse::<Symbol>() {
-                    Ok(symbol) => {
-                        if productions.contains_key(&symbol) {
-                            productions.get_mut(&symbol).unwrap().push(vec![]);
-                        }
-
-                        productions.entry(symbol.clone()).or_insert(vec![vec![]]);
-                        if first {
-                            start_symbol = symbol.clone();
-                            first = false;
-                        }
-                        lhs_symbol = symbol.clone();
+        if line.len() == 0 {
+            continue;
+        }
+
+        // Split the line at the equals
+        let parts: Vec<&str> = line.split("::=").map(|x| x.trim()).collect();
+        if parts.len() > 1 {
+            let lhs = parts[0];
+            let rhs = parts[1]
+                .split(" ")
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty());
+            let lhs_symbol;
+
+            match lhs.parse::<Symbol>() {
+                Ok(symbol) => {
+                    if productions.contains_key(&symbol) {
+                        productions.get_mut(&symbol).unwrap().push(vec![]);
                     }
-                    _ => {
-                        error!("Failed to parse {}", lhs);
-                        continue;
+
+                    productions.entry(symbol.clone()).or_insert(vec![vec![]]);
+                    if first {
+                        start_symbol = symbol.clone();
+                        first = false;
                     }
+                    lhs_symbol = symbol.clone();
                 }
+                _ => {
+                    error!("Failed to parse {}", lhs);
+                    continue;
+                }
+            }
 
-                for component in rhs {
-                    match component.parse::<Symbol>() {
-                        Ok(symbol) => {
-                            // TODO: Address these unwraps
-
-                            productions
-                                .get_mut(&lhs_symbol)
-                                .unwrap()
-                                .last_mut()
-                                .unwrap()
-                                .push(symbol);
-                        }
-                        _ => (),
+            for component in rhs {
+                match component.parse::<Symbol>() {
+                    Ok(symbol) => {
+                        // TODO: Address these unwraps
+
+                        productions
+                            .get_mut(&lhs_symbol)
+                            .unwrap()
+                            .last_mut()
+                            .unwrap()
+                            .push(symbol);
+                    }
+                    Err(err) => {
+                        error!(
+                            "Invalid semantic action in production {}: {}",
+                            line.trim(),
+                            err
+                        );
+                        invalid_actions = true;
                     }
                 }
-
-                production_count += 1;
             }
+
+            production_count += 1;
         }
-        info!(
-            "Successfully read {} productions from the grammar file",
-            production_count
-        );
-        Ok(Grammar::new(&productions, &start_symbol))
     }
+    if invalid_actions {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "the grammar contains invalid semantic actions",
+        ));
+    }
+    info!(
+        "Successfully read {} productions from the grammar file",
+        production_count
+    );
+    Ok((productions, start_symbol))
 }
 
 fn first(productions: &HashMap<Symbol, Vec<Sentence>>, s: &Symbol) -> HashSet<Symbol> {
@@ -204,39 +283,34 @@ fn first(productions: &HashMap<Symbol, Vec<Sentence>>, s: &Symbol) -> HashSet<Sy
     first_internal(productions, s, &mut HashSet::new())
 }
 
+// The description of the algorithm:
+// first(S1 S2 ... Sn)
+//      includes (first(S1) - epsilon)
+//      for every Si whose first(Si) includes epsilon
+//          includes (first(Si+1) - epsilon)
+//      if all first(S1), first(S2), ..., first(Sn) include epsilon
+//          includes epsilon
 fn sentence_first(
     productions: &HashMap<Symbol, Vec<Sentence>>,
     sentence: &Vec<Symbol>,
 ) -> HashSet<Symbol> {
     let mut result = HashSet::new();
-    if sentence
+    let symbols: Vec<_> = sentence
         .iter()
         .filter(|x| !matches!(x, Symbol::SemanticAction(_)))
-        .collect::<Vec<_>>()
-        .is_empty()
-    {
+        .collect();
+    if symbols.is_empty() {
         return result;
     }
 
-    // is_empty guards unwrap of first
-    result.extend(first(
-        productions,
-        sentence
-            .iter()
-            .filter(|x| !matches!(x, Symbol::SemanticAction(_)))
-            .collect::<Vec<_>>()
-            .first()
-            .unwrap(),
-    ));
-    for pair in sentence
-        .iter()
-        .filter(|x| !matches!(x, Symbol::SemanticAction(_)))
-        .collect::<Vec<_>>()
-        .windows(2)
-        .take_while(|pair| first(productions, &pair[0]).contains(&Symbol::Epsilon))
-    {
-        result.extend(first(productions, &pair[1]));
+    for symbol in symbols {
+        let symbol_first = first(productions, symbol);
+        result.extend(&symbol_first - &EPSILON_SET);
+        if !symbol_first.contains(&Symbol::Epsilon) {
+            return result;
+        }
     }
+    result.insert(Symbol::Epsilon);
     result
 }
 
@@ -637,6 +711,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_invalid_action_fails_reading() {
+        let string =
+            "<E> ::= 'id' @makenode~string~id@\n<E> ::= '(' <E> ')' @makefamily~one~parens@";
+        let error = read_productions(string.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
     #[test]
     fn test_grammar_from_stream() {
         let string = r#"
@@ -801,4 +883,32 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_sentence_first_with_nullable_prefix() {
+        // Only the first symbol of <S> ::= <A> 'b' derives epsilon
+        let string = r#"
+            <S> ::= <A> 'b'
+            <A> ::= 'a'
+            <A> ::= EPSILON
+        "#
+        .as_bytes();
+        let grammar = Grammar::from_reader(string).unwrap();
+        let sentence = vec![
+            Symbol::NonTerminal("A".to_string()),
+            Symbol::Terminal("b".to_string()),
+        ];
+
+        assert_eq!(
+            grammar.sentence_first(&sentence),
+            hashset! {
+                Symbol::Terminal("a".to_string()),
+                Symbol::Terminal("b".to_string())
+            }
+        );
+        assert_eq!(
+            *grammar.follow(&Symbol::NonTerminal("A".to_string())),
+            hashset! {Symbol::Terminal("b".to_string())}
+        );
+    }
 }
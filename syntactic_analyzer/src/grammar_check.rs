@@ -0,0 +1,709 @@
+//! Validation of a grammar's productions for use by a table-driven LL(1) parser
+//!
+//! Unlike Grammar::new, which stops at the first problem, every issue found in
+//! the productions is reported. FIRST and FOLLOW sets are computed here by
+//! iterating to a fixed point so that left recursive grammars can be analyzed.
+//! The semantic actions are checked by simulating their effect on the semantic
+//! stack, since a wrong node count only shows up as a malformed AST otherwise.
+use crate::grammar::Sentence;
+use crate::semantic_action::Action;
+use crate::stack_effect::{non_terminal_stack_effects, sentence_stack_effect};
+use crate::symbol::Symbol;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrammarIssue {
+    /// The start symbol has no production
+    UndefinedStart(Symbol),
+
+    /// A non-terminal used on a right-hand side without a production, and the productions using it
+    UndefinedNonTerminal(Symbol, Vec<Production>),
+
+    /// A production with nothing on its right-hand side
+    EmptyProduction(Production),
+
+    /// A non-terminal that can't be derived from the start symbol
+    UnreachableNonTerminal(Symbol),
+
+    /// A non-terminal that can't derive a string of terminals
+    UnproductiveNonTerminal(Symbol),
+
+    /// A cycle of non-terminals that derive themselves as their leftmost symbol
+    LeftRecursion(Vec<Symbol>),
+
+    /// Two or more productions of a non-terminal start with the same terminals
+    FirstFirstConflict(Symbol, Vec<Symbol>, Vec<Production>),
+
+    /// A production starts with terminals that can follow another production deriving epsilon
+    FirstFollowConflict(Symbol, Vec<Symbol>, Vec<Production>),
+
+    /// Productions of a non-terminal leaving different numbers of nodes on the semantic stack
+    InconsistentStackEffect(Symbol, Vec<(Production, isize)>),
+
+    /// A semantic action popping more nodes than the semantic stack can hold when
+    /// the production is used, with the number of nodes available
+    StackUnderflow(Production, Action, usize),
+}
+
+/// A production as written in the grammar file, minus its semantic actions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Production {
+    pub lhs: Symbol,
+    pub rhs: Sentence,
+}
+
+impl fmt::Display for Production {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ::=", self.lhs)?;
+        for symbol in self
+            .rhs
+            .iter()
+            .filter(|s| !matches!(s, Symbol::SemanticAction(_)))
+        {
+            write!(f, " {}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+impl GrammarIssue {
+    /// Issues that prevent building a parse table, as opposed to dead productions
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            GrammarIssue::UnreachableNonTerminal(_) | GrammarIssue::UnproductiveNonTerminal(_)
+        )
+    }
+}
+
+impl fmt::Display for GrammarIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrammarIssue::UndefinedStart(symbol) => write!(
+                f,
+                "Start symbol {} is not defined by any production",
+                symbol
+            ),
+            GrammarIssue::UndefinedNonTerminal(symbol, productions) => {
+                write!(f, "Non-terminal {} is never defined, used in:", symbol)?;
+                write_productions(f, productions)
+            }
+            GrammarIssue::EmptyProduction(production) => write!(
+                f,
+                "Production has an empty right-hand side, use EPSILON instead:\n    {}",
+                production
+            ),
+            GrammarIssue::UnreachableNonTerminal(symbol) => write!(
+                f,
+                "Non-terminal {} is unreachable from the start symbol",
+                symbol
+            ),
+            GrammarIssue::UnproductiveNonTerminal(symbol) => write!(
+                f,
+                "Non-terminal {} can't derive a string of terminals",
+                symbol
+            ),
+            GrammarIssue::LeftRecursion(cycle) => {
+                write!(f, "Left recursion: ")?;
+                for symbol in cycle {
+                    write!(f, "{} => ", symbol)?;
+                }
+                write!(f, "{}", cycle[0])
+            }
+            GrammarIssue::FirstFirstConflict(symbol, terminals, productions) => {
+                write!(f, "FIRST/FIRST conflict in {} on ", symbol)?;
+                write_symbols(f, terminals)?;
+                write!(f, " between:")?;
+                write_productions(f, productions)
+            }
+            GrammarIssue::FirstFollowConflict(symbol, terminals, productions) => {
+                write!(f, "FIRST/FOLLOW conflict in {} on ", symbol)?;
+                write_symbols(f, terminals)?;
+                write!(f, " between:")?;
+                write_productions(f, productions)
+            }
+            GrammarIssue::InconsistentStackEffect(symbol, productions) => {
+                write!(
+                    f,
+                    "Productions of {} leave different numbers of nodes on the semantic stack:",
+                    symbol
+                )?;
+                for (production, effect) in productions {
+                    write!(f, "\n    {:+} {}", effect, production)?;
+                }
+                Ok(())
+            }
+            GrammarIssue::StackUnderflow(production, action, available) => write!(
+                f,
+                "Semantic action @{}@ pops {} nodes but the semantic stack can hold only {} in:\n    {}",
+                action.grm_notation(),
+                action.stack_effect().0,
+                available,
+                production
+            ),
+        }
+    }
+}
+
+fn write_symbols(f: &mut fmt::Formatter<'_>, symbols: &[Symbol]) -> fmt::Result {
+    let symbols: Vec<String> = symbols.iter().map(|s| s.to_string()).collect();
+    write!(f, "{}", symbols.join(", "))
+}
+
+fn write_productions(f: &mut fmt::Formatter<'_>, productions: &[Production]) -> fmt::Result {
+    for production in productions {
+        write!(f, "\n    {}", production)?;
+    }
+    Ok(())
+}
+
+/// Report every issue preventing the productions from forming an LL(1) grammar
+///
+/// Issues are sorted by the name of the non-terminal they concern
+pub fn check_grammar(
+    productions: &HashMap<Symbol, Vec<Sentence>>,
+    start: &Symbol,
+) -> Vec<GrammarIssue> {
+    let mut issues = Vec::new();
+    let non_terminals = sorted(productions.keys());
+
+    if !productions.contains_key(start) {
+        issues.push(GrammarIssue::UndefinedStart(start.clone()));
+    }
+
+    // Undefined non-terminals and empty productions
+    let mut undefined: BTreeMap<String, (Symbol, Vec<Production>)> = BTreeMap::new();
+    for lhs in &non_terminals {
+        for rhs in &productions[*lhs] {
+            let production = Production {
+                lhs: (*lhs).clone(),
+                rhs: rhs.clone(),
+            };
+            if rhs.iter().all(|s| matches!(s, Symbol::SemanticAction(_))) {
+                issues.push(GrammarIssue::EmptyProduction(production.clone()));
+            }
+            for symbol in grammar_symbols(rhs) {
+                if matches!(symbol, Symbol::NonTerminal(_)) && !productions.contains_key(symbol) {
+                    let (_, users) = undefined
+                        .entry(symbol.to_string())
+                        .or_insert_with(|| (symbol.clone(), Vec::new()));
+                    if !users.contains(&production) {
+                        users.push(production.clone());
+                    }
+                }
+            }
+        }
+    }
+    for (symbol, users) in undefined.into_values() {
+        issues.push(GrammarIssue::UndefinedNonTerminal(symbol, users));
+    }
+
+    // Unreachable non-terminals
+    let mut reachable = HashSet::new();
+    let mut queue = VecDeque::new();
+    if productions.contains_key(start) {
+        reachable.insert(start);
+        queue.push_back(start);
+    }
+    while let Some(lhs) = queue.pop_front() {
+        for symbol in productions[lhs].iter().flat_map(grammar_symbols) {
+            if productions.contains_key(symbol) && reachable.insert(symbol) {
+                queue.push_back(symbol);
+            }
+        }
+    }
+    for lhs in non_terminals.iter().filter(|s| !reachable.contains(**s)) {
+        issues.push(GrammarIssue::UnreachableNonTerminal((*lhs).clone()));
+    }
+
+    // Unproductive non-terminals
+    let mut productive: HashSet<&Symbol> = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for lhs in &non_terminals {
+            if !productive.contains(*lhs)
+                && productions[*lhs].iter().any(|rhs| {
+                    grammar_symbols(rhs).all(|s| match s {
+                        Symbol::NonTerminal(_) => productive.contains(s),
+                        _ => true,
+                    })
+                })
+            {
+                productive.insert(lhs);
+                changed = true;
+            }
+        }
+    }
+    for lhs in non_terminals.iter().filter(|s| !productive.contains(**s)) {
+        issues.push(GrammarIssue::UnproductiveNonTerminal((*lhs).clone()));
+    }
+
+    let sets = Sets::new(productions, start);
+
+    // Left recursion, each cycle is reported once starting from its smallest symbol
+    let mut cycles = BTreeSet::new();
+    for lhs in &non_terminals {
+        if let Some(cycle) = sets.left_recursive_cycle(lhs) {
+            let smallest = (0..cycle.len()).min_by_key(|i| &cycle[*i]).unwrap();
+            let mut cycle = cycle;
+            cycle.rotate_left(smallest);
+            cycles.insert(cycle);
+        }
+    }
+    for cycle in cycles {
+        issues.push(GrammarIssue::LeftRecursion(
+            cycle.into_iter().map(Symbol::NonTerminal).collect(),
+        ));
+    }
+
+    // LL(1) table conflicts, grouped by the productions involved
+    for lhs in &non_terminals {
+        let mut first_entries: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+        let mut follow_entries: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+        for (index, rhs) in productions[*lhs].iter().enumerate() {
+            let (first, nullable) = sets.sentence_first(rhs);
+            for terminal in first {
+                first_entries.entry(terminal).or_default().insert(index);
+            }
+            if nullable {
+                for terminal in &sets.follow[*lhs] {
+                    follow_entries
+                        .entry(terminal.clone())
+                        .or_default()
+                        .insert(index);
+                }
+            }
+        }
+
+        let mut first_first: BTreeMap<BTreeSet<usize>, Vec<String>> = BTreeMap::new();
+        let mut first_follow: BTreeMap<BTreeSet<usize>, Vec<String>> = BTreeMap::new();
+        for (terminal, indices) in &first_entries {
+            if indices.len() > 1 {
+                first_first
+                    .entry(indices.clone())
+                    .or_default()
+                    .push(terminal.clone());
+            }
+        }
+        for (terminal, nullable_indices) in &follow_entries {
+            let mut indices = nullable_indices.clone();
+            if let Some(first_indices) = first_entries.get(terminal) {
+                indices.extend(first_indices);
+            }
+            if indices.len() > 1 {
+                first_follow
+                    .entry(indices)
+                    .or_default()
+                    .push(terminal.clone());
+            }
+        }
+
+        let conflict_productions = |indices: &BTreeSet<usize>| -> Vec<Production> {
+            indices
+                .iter()
+                .map(|index| Production {
+                    lhs: (*lhs).clone(),
+                    rhs: productions[*lhs][*index].clone(),
+                })
+                .collect()
+        };
+        for (indices, terminals) in first_first {
+            issues.push(GrammarIssue::FirstFirstConflict(
+                (*lhs).clone(),
+                terminals.iter().map(|t| terminal_symbol(t)).collect(),
+                conflict_productions(&indices),
+            ));
+        }
+        for (indices, terminals) in first_follow {
+            issues.push(GrammarIssue::FirstFollowConflict(
+                (*lhs).clone(),
+                terminals.iter().map(|t| terminal_symbol(t)).collect(),
+                conflict_productions(&indices),
+            ));
+        }
+    }
+
+    check_stack_effects(productions, start, &non_terminals, &mut issues);
+    issues
+}
+
+/// Report the non-terminals whose productions disagree on their semantic stack
+/// effect and the semantic actions popping nodes that were never pushed
+///
+/// Each non-terminal is simulated with the fewest nodes it can find on the stack
+/// when it is derived, starting from an empty stack for the start symbol.
+fn check_stack_effects(
+    productions: &HashMap<Symbol, Vec<Sentence>>,
+    start: &Symbol,
+    non_terminals: &[&Symbol],
+    issues: &mut Vec<GrammarIssue>,
+) {
+    let effects = non_terminal_stack_effects(productions);
+    for lhs in non_terminals {
+        let alternatives: Vec<(Production, isize)> = productions[*lhs]
+            .iter()
+            .filter_map(|rhs| {
+                let effect = sentence_stack_effect(rhs, &effects)?;
+                let production = Production {
+                    lhs: (*lhs).clone(),
+                    rhs: rhs.clone(),
+                };
+                Some((production, effect))
+            })
+            .collect();
+        if alternatives.iter().any(|(_, e)| *e != alternatives[0].1) {
+            issues.push(GrammarIssue::InconsistentStackEffect(
+                (*lhs).clone(),
+                alternatives,
+            ));
+        }
+    }
+
+    // Fewest nodes on the stack when each reachable non-terminal is derived
+    let mut entry_depths: HashMap<&Symbol, usize> = HashMap::new();
+    if productions.contains_key(start) {
+        entry_depths.insert(start, 0);
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for lhs in non_terminals {
+            let depth = match entry_depths.get(*lhs) {
+                Some(depth) => *depth,
+                None => continue,
+            };
+            for rhs in &productions[*lhs] {
+                simulate_stack(rhs, depth, &effects, |symbol, depth| {
+                    if let Symbol::NonTerminal(_) = symbol {
+                        let entry = entry_depths.entry(symbol).or_insert(usize::MAX);
+                        if depth < *entry {
+                            *entry = depth;
+                            changed = true;
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    for lhs in non_terminals {
+        let depth = match entry_depths.get(*lhs) {
+            Some(depth) => *depth,
+            None => continue,
+        };
+        for rhs in &productions[*lhs] {
+            let mut underflow = None;
+            simulate_stack(rhs, depth, &effects, |symbol, depth| {
+                if let Symbol::SemanticAction(action) = symbol {
+                    if underflow.is_none() && action.stack_effect().0 > depth {
+                        underflow = Some((action.clone(), depth));
+                    }
+                }
+            });
+            if let Some((action, available)) = underflow {
+                issues.push(GrammarIssue::StackUnderflow(
+                    Production {
+                        lhs: (*lhs).clone(),
+                        rhs: rhs.clone(),
+                    },
+                    action,
+                    available,
+                ));
+            }
+        }
+    }
+}
+
+/// Walk a sentence from the given stack depth, calling visit with each symbol and
+/// the depth before it
+///
+/// Missing nodes are not counted twice, the depth stops at zero after an underflow.
+/// The walk ends at a non-terminal whose effect is unknown.
+fn simulate_stack<'s>(
+    sentence: &'s Sentence,
+    mut depth: usize,
+    effects: &HashMap<Symbol, isize>,
+    mut visit: impl FnMut(&'s Symbol, usize),
+) {
+    for symbol in sentence {
+        let effect = match symbol {
+            Symbol::SemanticAction(action) => {
+                let (pops, pushes) = action.stack_effect();
+                pushes as isize - pops.min(depth) as isize
+            }
+            Symbol::NonTerminal(_) => match effects.get(symbol) {
+                Some(effect) => *effect,
+                None => return,
+            },
+            _ => continue,
+        };
+        visit(symbol, depth);
+        depth = (depth as isize + effect).max(0) as usize;
+    }
+}
+
+/// The end of stream marker is kept among the terminal names of the sets
+const EOS: &str = "$";
+
+fn terminal_symbol(name: &str) -> Symbol {
+    if name == EOS {
+        Symbol::Eos
+    } else {
+        Symbol::Terminal(name.to_string())
+    }
+}
+
+/// The symbols of a sentence relevant to parsing, without semantic actions and epsilon
+fn grammar_symbols(sentence: &Sentence) -> impl Iterator<Item = &Symbol> {
+    sentence
+        .iter()
+        .filter(|s| !matches!(s, Symbol::SemanticAction(_) | Symbol::Epsilon))
+}
+
+fn sorted<'a>(symbols: impl Iterator<Item = &'a Symbol>) -> Vec<&'a Symbol> {
+    let mut symbols: Vec<&Symbol> = symbols.collect();
+    symbols.sort_by_key(|s| s.to_string());
+    symbols
+}
+
+/// FIRST and FOLLOW sets of the defined non-terminals, with terminals stored by name
+struct Sets<'a> {
+    productions: &'a HashMap<Symbol, Vec<Sentence>>,
+    nullable: HashSet<Symbol>,
+    first: HashMap<Symbol, BTreeSet<String>>,
+    follow: HashMap<Symbol, BTreeSet<String>>,
+}
+
+impl<'a> Sets<'a> {
+    fn new(productions: &'a HashMap<Symbol, Vec<Sentence>>, start: &Symbol) -> Self {
+        let mut sets = Sets {
+            productions,
+            nullable: HashSet::new(),
+            first: productions
+                .keys()
+                .map(|s| (s.clone(), BTreeSet::new()))
+                .collect(),
+            follow: productions
+                .keys()
+                .map(|s| (s.clone(), BTreeSet::new()))
+                .collect(),
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (lhs, options) in productions {
+                for rhs in options {
+                    let (first, nullable) = sets.sentence_first(rhs);
+                    if nullable && sets.nullable.insert(lhs.clone()) {
+                        changed = true;
+                    }
+                    let lhs_first = sets.first.get_mut(lhs).unwrap();
+                    let size = lhs_first.len();
+                    lhs_first.extend(first);
+                    changed |= lhs_first.len() != size;
+                }
+            }
+        }
+
+        if let Some(start_follow) = sets.follow.get_mut(start) {
+            start_follow.insert(EOS.to_string());
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (lhs, options) in productions {
+                for rhs in options {
+                    let symbols: Vec<Symbol> = grammar_symbols(rhs).cloned().collect();
+                    for (position, symbol) in symbols.iter().enumerate() {
+                        if !sets.follow.contains_key(symbol) {
+                            continue;
+                        }
+                        let (mut follow, nullable) =
+                            sets.sentence_first(&symbols[position + 1..].to_vec());
+                        if nullable {
+                            follow.extend(sets.follow[lhs].iter().cloned());
+                        }
+                        let symbol_follow = sets.follow.get_mut(symbol).unwrap();
+                        let size = symbol_follow.len();
+                        symbol_follow.extend(follow);
+                        changed |= symbol_follow.len() != size;
+                    }
+                }
+            }
+        }
+        sets
+    }
+
+    /// Terminals beginning the sentence and whether the sentence derives epsilon
+    fn sentence_first(&self, sentence: &Sentence) -> (BTreeSet<String>, bool) {
+        let mut result = BTreeSet::new();
+        for symbol in grammar_symbols(sentence) {
+            match symbol {
+                Symbol::Terminal(name) => {
+                    result.insert(name.clone());
+                    return (result, false);
+                }
+                Symbol::Eos => {
+                    result.insert(EOS.to_string());
+                    return (result, false);
+                }
+                // Undefined non-terminals are reported separately and derive nothing
+                Symbol::NonTerminal(_) if !self.first.contains_key(symbol) => {
+                    return (result, false)
+                }
+                _ => {
+                    result.extend(self.first[symbol].iter().cloned());
+                    if !self.nullable.contains(symbol) {
+                        return (result, false);
+                    }
+                }
+            }
+        }
+        (result, true)
+    }
+
+    /// Non-terminals that can appear as the leftmost symbol of a production of lhs
+    fn left_corners(&self, lhs: &Symbol) -> Vec<&'a Symbol> {
+        let mut corners = Vec::new();
+        for rhs in &self.productions[lhs] {
+            for symbol in grammar_symbols(rhs) {
+                if !self.productions.contains_key(symbol) {
+                    break;
+                }
+                corners.push(symbol);
+                if !self.nullable.contains(symbol) {
+                    break;
+                }
+            }
+        }
+        corners
+    }
+
+    /// The shortest chain of left corners leading from a non-terminal back to itself
+    fn left_recursive_cycle(&self, start: &Symbol) -> Option<Vec<String>> {
+        let mut parents: HashMap<&Symbol, &Symbol> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(lhs) = queue.pop_front() {
+            for corner in self.left_corners(lhs) {
+                if corner == start {
+                    let mut cycle = vec![lhs];
+                    while let Some(parent) = parents.get(cycle.last().unwrap()) {
+                        cycle.push(parent);
+                    }
+                    cycle.reverse();
+                    return Some(
+                        cycle
+                            .iter()
+                            .map(|s| match s {
+                                Symbol::NonTerminal(name) => name.clone(),
+                                _ => s.to_string(),
+                            })
+                            .collect(),
+                    );
+                }
+                if !parents.contains_key(corner) && corner != start {
+                    parents.insert(corner, lhs);
+                    queue.push_back(corner);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::read_productions;
+
+    fn check(source: &str) -> Vec<GrammarIssue> {
+        let (productions, start) = read_productions(source.as_bytes()).unwrap();
+        check_grammar(&productions, &start)
+    }
+
+    #[test]
+    fn test_ll1_grammar_has_no_issues() {
+        let issues = check(
+            r#"
+            <E> ::= <T> <E'>
+            <E'> ::= '+' <T> <E'>
+            <E'> ::= EPSILON
+            <T> ::= '0'
+            <T> ::= '(' <E> ')'
+        "#,
+        );
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn test_grammar_issues() {
+        let issues = check(
+            r#"
+            <S> ::= <S> '+' <A>
+            <S> ::= <A> <B>
+            <A> ::= 'a' <Missing>
+            <A> ::= 'a'
+            <A> ::= EPSILON
+            <B> ::= 'b' <C> 'c'
+            <C> ::= 'c'
+            <C> ::= EPSILON
+            <Dead> ::= <Dead> 'c'
+        "#,
+        );
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+        assert!(messages.contains(
+            &"Non-terminal <Missing> is never defined, used in:\n    <A> ::= 'a' <Missing>"
+                .to_string()
+        ));
+        assert!(messages
+            .contains(&"Non-terminal <Dead> is unreachable from the start symbol".to_string()));
+        assert!(messages
+            .contains(&"Non-terminal <Dead> can't derive a string of terminals".to_string()));
+        assert!(messages.contains(&"Left recursion: <Dead> => <Dead>".to_string()));
+        assert!(messages.contains(&"Left recursion: <S> => <S>".to_string()));
+        assert!(messages.contains(
+            &"FIRST/FIRST conflict in <A> on 'a' between:\n    <A> ::= 'a' <Missing>\n    <A> ::= 'a'"
+                .to_string()
+        ));
+        assert!(messages.contains(
+            &"FIRST/FOLLOW conflict in <C> on 'c' between:\n    <C> ::= 'c'\n    <C> ::= EPSILON"
+                .to_string()
+        ));
+        assert!(issues.iter().any(|i| matches!(
+            i,
+            GrammarIssue::FirstFirstConflict(Symbol::NonTerminal(s), _, _) if s == "S"
+        )));
+    }
+
+    #[test]
+    fn test_stack_effect_issues() {
+        let issues = check(
+            r#"
+            <S> ::= @makenode~list~stats@ <L> @makefamily~2~prog@
+            <L> ::= <T> @makesibling@ <L>
+            <L> ::= EPSILON
+            <T> ::= 'a' @makenode~string~a@
+            <T> ::= 'b' @makenode~string~b@ @makenode~string~b@
+        "#,
+        );
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Productions of <T> leave different numbers of nodes on the semantic stack:\n    +1 <T> ::= 'a'\n    +2 <T> ::= 'b'",
+                "Semantic action @makefamily~2~prog@ pops 2 nodes but the semantic stack can hold only 1 in:\n    <S> ::= <L>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ll1_paquet_stack_effects() {
+        let (productions, start) =
+            read_productions(std::fs::File::open("../resources/LL1.paquet.grm").unwrap()).unwrap();
+        assert_eq!(check_grammar(&productions, &start), vec![]);
+    }
+}
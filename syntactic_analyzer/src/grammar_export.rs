@@ -0,0 +1,233 @@
+//! Rendering of a grammar's FIRST and FOLLOW sets and of its LL(1) parse table
+//! as documents for review, with productions written in the grammar file notation
+use crate::grammar::{Grammar, Sentence};
+use crate::parse_table::ParseTable;
+use crate::symbol::Symbol;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Markdown,
+    Csv,
+    Html,
+}
+
+pub const EXPORT_FORMATS: [ExportFormat; 3] = [
+    ExportFormat::Markdown,
+    ExportFormat::Csv,
+    ExportFormat::Html,
+];
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "csv" => Ok(ExportFormat::Csv),
+            "html" => Ok(ExportFormat::Html),
+            _ => Err(format!(
+                "Unknown export format \"{}\", should be markdown, csv or html",
+                s
+            )),
+        }
+    }
+}
+
+/// A production in the notation of the grammar file, semantic actions included
+pub fn production_grm_notation(lhs: &Symbol, rhs: &Sentence) -> String {
+    let rhs: Vec<String> = rhs.iter().map(|s| s.grm_notation()).collect();
+    format!("{} ::= {}", lhs.grm_notation(), rhs.join(" "))
+}
+
+/// One row per non-terminal with its nullability, FIRST set and FOLLOW set
+pub fn export_first_follow(grammar: &Grammar, format: ExportFormat) -> String {
+    let mut rows = vec![vec![
+        "Non-terminal".to_string(),
+        "Nullable".to_string(),
+        "FIRST".to_string(),
+        "FOLLOW".to_string(),
+    ]];
+    for non_terminal in sorted_symbols(grammar.productions().keys()) {
+        let first = grammar.first(non_terminal);
+        rows.push(vec![
+            non_terminal.grm_notation(),
+            (if first.contains(&Symbol::Epsilon) {
+                "yes"
+            } else {
+                "no"
+            })
+            .to_string(),
+            symbol_set(&first),
+            symbol_set(grammar.follow(non_terminal)),
+        ]);
+    }
+    render(&rows, "FIRST and FOLLOW sets", format)
+}
+
+/// The LL(1) parse table with a row per non-terminal and a column per terminal
+pub fn export_parse_table(grammar: &Grammar, table: &ParseTable, format: ExportFormat) -> String {
+    let non_terminals = sorted_symbols(grammar.productions().keys());
+    let mut terminals: Vec<&Symbol> = sorted_symbols(
+        table
+            .table
+            .values()
+            .flat_map(|row| row.keys())
+            .filter(|s| matches!(s, Symbol::Terminal(_)))
+            .collect::<HashSet<_>>()
+            .into_iter(),
+    );
+    terminals.push(&Symbol::Eos);
+
+    let mut header = vec!["".to_string()];
+    header.extend(terminals.iter().map(|t| t.grm_notation()));
+    let mut rows = vec![header];
+    for non_terminal in non_terminals {
+        let mut row = vec![non_terminal.grm_notation()];
+        for terminal in &terminals {
+            row.push(if table.contains(non_terminal, terminal) {
+                let option = table.get(non_terminal, terminal);
+                production_grm_notation(non_terminal, grammar.production(non_terminal, option))
+            } else {
+                "".to_string()
+            });
+        }
+        rows.push(row);
+    }
+    render(&rows, "LL(1) parse table", format)
+}
+
+fn sorted_symbols<'a>(symbols: impl Iterator<Item = &'a Symbol>) -> Vec<&'a Symbol> {
+    let mut symbols: Vec<&Symbol> = symbols.collect();
+    symbols.sort_by_key(|s| s.grm_notation());
+    symbols
+}
+
+fn symbol_set(symbols: &HashSet<Symbol>) -> String {
+    let symbols: Vec<String> = sorted_symbols(symbols.iter())
+        .iter()
+        .map(|s| s.grm_notation())
+        .collect();
+    format!("{{{}}}", symbols.join(", "))
+}
+
+/// Render rows of cells, the first row being the header
+fn render(rows: &[Vec<String>], title: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => render_markdown(rows, title),
+        ExportFormat::Csv => render_csv(rows),
+        ExportFormat::Html => render_html(rows, title),
+    }
+}
+
+fn render_markdown(rows: &[Vec<String>], title: &str) -> String {
+    // Code spans keep the angle brackets of non-terminals from being read as HTML
+    let cell = |s: &String| {
+        if s.is_empty() {
+            "".to_string()
+        } else {
+            format!("`{}`", s.replace("|", "\\|"))
+        }
+    };
+
+    let mut result = format!("# {}\n\n", title);
+    for (index, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = if index == 0 {
+            row.iter().map(|s| s.replace("|", "\\|")).collect()
+        } else {
+            row.iter().map(cell).collect()
+        };
+        result.push_str(&format!("| {} |\n", cells.join(" | ")));
+        if index == 0 {
+            result.push_str(&format!("|{}\n", " --- |".repeat(row.len())));
+        }
+    }
+    result
+}
+
+fn render_csv(rows: &[Vec<String>]) -> String {
+    let cell = |s: &String| {
+        if s.contains([',', '"', '\n']) {
+            format!("\"{}\"", s.replace("\"", "\"\""))
+        } else {
+            s.clone()
+        }
+    };
+
+    let mut result = String::new();
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(cell).collect();
+        result.push_str(&cells.join(","));
+        result.push('\n');
+    }
+    result
+}
+
+fn render_html(rows: &[Vec<String>], title: &str) -> String {
+    let escape = |s: &String| {
+        s.replace("&", "&amp;")
+            .replace("<", "&lt;")
+            .replace(">", "&gt;")
+            .replace("\"", "&quot;")
+    };
+
+    let mut result = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n<table border=\"1\">\n",
+        title
+    );
+    for (index, row) in rows.iter().enumerate() {
+        let tag = if index == 0 { "th" } else { "td" };
+        result.push_str("<tr>");
+        for cell in row {
+            if index == 0 || cell.is_empty() {
+                result.push_str(&format!("<{0}>{1}</{0}>", tag, escape(cell)));
+            } else {
+                result.push_str(&format!("<{0}><code>{1}</code></{0}>", tag, escape(cell)));
+            }
+        }
+        result.push_str("</tr>\n");
+    }
+    result.push_str("</table>\n</body>\n</html>\n");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_first_follow_csv() {
+        let grammar = Grammar::from_reader(
+            r#"
+            <E> ::= <T> <E'>
+            <E'> ::= ',' <T> @makesibling@ <E'>
+            <E'> ::= EPSILON
+            <T> ::= 'id' @makenode~string~id@
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            export_first_follow(&grammar, ExportFormat::Csv),
+            "Non-terminal,Nullable,FIRST,FOLLOW\n\
+             <E'>,yes,\"{',', EPSILON}\",{$}\n\
+             <E>,no,{'id'},{$}\n\
+             <T>,no,{'id'},\"{$, ','}\"\n"
+        );
+
+        let table = ParseTable::from_grammar(&grammar);
+        assert!(export_parse_table(&grammar, &table, ExportFormat::Markdown)
+            .contains("| `<T>` |  | `<T> ::= 'id' @makenode~string~id@` |  |"));
+    }
+}
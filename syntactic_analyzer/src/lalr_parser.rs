@@ -0,0 +1,233 @@
+use crate::lalr_table::{LalrAction, LalrTable};
+use crate::symbol::Symbol;
+use lexical_analyzer::{Lex, Token};
+use log::{error, trace};
+use output_manager::{warn_write, write_array, OutputConfig};
+
+/// Shift-reduce counterpart of `parse`, building the same AST from the same
+/// semantic actions
+///
+/// Reductions are written to the derivation file, so the derivation is the
+/// rightmost one in reverse. After a syntax error parsing resumes to report further
+/// errors but no AST is returned.
+pub fn parse_lalr(
+    lexer: &mut Lex<std::fs::File>,
+    table: &LalrTable,
+    output_config: &mut OutputConfig,
+) -> Option<ast::Node> {
+    let mut state_stack = vec![0];
+    let mut semantic_stack: Vec<ast::Node> = Vec::new();
+    let mut current_token = lexer.next();
+    let mut previous_token = current_token.clone();
+    let mut error = false;
+    // Whether the current token already caused an error, in which case recovery skips it
+    let mut recovering = false;
+
+    if let Some(token) = current_token.clone() {
+        warn_write(
+            &mut output_config.derivation_file,
+            &output_config.derivation_path,
+            &format!("Processing next token {}\n", token),
+        );
+    }
+
+    loop {
+        let state = *state_stack.last().unwrap();
+        let lookahead = Symbol::from_token(&current_token);
+        trace!("State {} on {}", state, lookahead);
+        match table.action(state, &lookahead) {
+            Some(LalrAction::Shift(next)) => {
+                state_stack.push(next);
+                previous_token = current_token;
+                current_token = lexer.next();
+                recovering = false;
+                if let Some(token) = current_token.clone() {
+                    warn_write(
+                        &mut output_config.derivation_file,
+                        &output_config.derivation_path,
+                        &format!("Processing next token {}\n", token),
+                    );
+                }
+            }
+            Some(LalrAction::Reduce(rule_index)) => {
+                let rule = table.rule(rule_index);
+                state_stack.truncate(state_stack.len() - rule.rhs.len());
+                if !error {
+                    for action in &rule.actions {
+                        action.execute(
+                            &mut semantic_stack,
+                            previous_token.clone().unwrap(),
+                            rule.lhs.clone(),
+                        );
+                    }
+                    trace!("Semantic Stack {:?}", semantic_stack);
+                }
+                state_stack.push(table.goto(*state_stack.last().unwrap(), &rule.lhs));
+                warn_write(
+                    &mut output_config.derivation_file,
+                    &output_config.derivation_path,
+                    &format!("{}\n", rule),
+                );
+            }
+            Some(LalrAction::Accept) => break,
+            None => {
+                if !recovering {
+                    report_error(&current_token, table, state, output_config);
+                }
+                error = true;
+                if let (false, Some(next)) = (recovering, single_shift(table, state)) {
+                    // Act as if the only acceptable token, typically a ';', was missing
+                    state_stack.push(next);
+                } else if !recover(
+                    lexer,
+                    &mut current_token,
+                    &mut state_stack,
+                    table,
+                    recovering,
+                ) {
+                    break;
+                }
+                recovering = true;
+            }
+        }
+    }
+
+    if error {
+        error!("Parsing encountered errors, see the associated outsyntaxerrors files");
+        return None;
+    }
+
+    let top = semantic_stack.pop()?;
+    top.dot_graph(&mut output_config.ast_file);
+    Some(top)
+}
+
+fn report_error(
+    current_token: &Option<Token>,
+    table: &LalrTable,
+    state: usize,
+    output_config: &mut OutputConfig,
+) {
+    match current_token {
+        Some(token) => warn_write(
+            &mut output_config.syntax_error_file,
+            &output_config.syntax_error_path,
+            &format!(
+                "Syntax error at line {}, col {}: encountered {}, but was expecting one of ",
+                token.line, token.column, token.lexeme
+            ),
+        ),
+        None => warn_write(
+            &mut output_config.syntax_error_file,
+            &output_config.syntax_error_path,
+            "Syntax error: unexpected end of file, but was expecting one of ",
+        ),
+    }
+    write_array(
+        &mut output_config.syntax_error_file,
+        &output_config.syntax_error_path,
+        &table.expected(state),
+    );
+}
+
+/// The state reached by shifting the only terminal a state accepts, if any
+fn single_shift(table: &LalrTable, state: usize) -> Option<usize> {
+    match table.expected(state).as_slice() {
+        [terminal] => match table.action(state, terminal) {
+            Some(LalrAction::Shift(next)) => Some(next),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Resume parsing at the topmost state having an action on the current token,
+/// skipping the token first if it already failed to parse after an earlier recovery
+///
+/// Tokens are skipped until some state on the stack accepts one, returns false if
+/// the end of file is reached without any.
+fn recover(
+    lexer: &mut Lex<std::fs::File>,
+    current_token: &mut Option<Token>,
+    state_stack: &mut Vec<usize>,
+    table: &LalrTable,
+    skip_token: bool,
+) -> bool {
+    if skip_token {
+        if current_token.is_none() {
+            return false;
+        }
+        *current_token = lexer.next();
+    }
+
+    loop {
+        let lookahead = Symbol::from_token(current_token);
+        if let Some(depth) = state_stack
+            .iter()
+            .rposition(|state| table.action(*state, &lookahead).is_some())
+        {
+            state_stack.truncate(depth + 1);
+            return true;
+        }
+        if current_token.is_none() {
+            return false;
+        }
+        *current_token = lexer.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Grammar, LalrGrammar, ParseTable};
+    use lexical_analyzer::{lexer::Lexer, lexical_rule::LexicalRule};
+    use std::fs::File;
+
+    #[test]
+    fn test_same_ast_as_ll1_parser() {
+        let rules = LexicalRule::from_file("../resources/lex_tokens.txt").unwrap();
+        let keywords = std::fs::read_to_string("../resources/keywords.txt")
+            .unwrap()
+            .lines()
+            .map(|keyword| keyword.to_string())
+            .collect();
+        let lexer = Lexer::new(rules, keywords);
+        let grammar =
+            Grammar::from_reader(File::open("../resources/LL1.paquet.grm").unwrap()).unwrap();
+        let parse_table = ParseTable::from_grammar(&grammar);
+        let table = LalrTable::from_grammar(
+            LalrGrammar::from_reader(File::open("../resources/LALR.paquet.grm").unwrap()).unwrap(),
+        );
+        assert!(table.conflicts().is_empty());
+
+        let output_folder = std::env::temp_dir().join("paquet_lalr_test");
+        let output_folder = output_folder.to_str().unwrap();
+        let mut compared = 0;
+        for entry in std::fs::read_dir("../test_sources").unwrap() {
+            let source = entry.unwrap().path().to_str().unwrap().to_string();
+            let mut ll1_output = OutputConfig::new(&source, output_folder);
+            let ll1_ast = parse(
+                &mut lexer.lex(&source, &ll1_output.lex_error_path),
+                &grammar,
+                &parse_table,
+                &mut ll1_output,
+            );
+            let ll1_errors = std::fs::read_to_string(&ll1_output.syntax_error_path).unwrap();
+
+            let mut lalr_output = OutputConfig::new(&source, output_folder);
+            let lalr_ast = parse_lalr(
+                &mut lexer.lex(&source, &lalr_output.lex_error_path),
+                &table,
+                &mut lalr_output,
+            );
+
+            if ll1_errors.is_empty() {
+                assert_eq!(lalr_ast, ll1_ast, "different AST for {}", source);
+                compared += 1;
+            } else {
+                assert_eq!(lalr_ast, None, "AST despite syntax errors in {}", source);
+            }
+        }
+        assert!(compared > 0);
+    }
+}
@@ -0,0 +1,667 @@
+//! LALR(1) parse table generation for grammars written in their natural, left
+//! recursive form
+//!
+//! The grammar file uses the notation of the LL(1) grammar files, optionally preceded
+//! by precedence declarations listed from lowest to highest precedence:
+//! ```text
+//! %left '+' '-'
+//! %left '*' '/'
+//! ```
+//! A production takes the precedence of its last terminal and shift/reduce conflicts
+//! between declared terminals and productions are resolved with it, like yacc does.
+//! Other conflicts are reported, shifting or reducing the earliest production.
+//!
+//! Semantic actions ending a production run when it is reduced. Actions anywhere else
+//! are moved to an empty marker production inserted in their place, so they run right
+//! after the preceding terminal is shifted and see the same previous token as they
+//! would in the LL(1) parser.
+use crate::semantic_action::Action;
+use crate::symbol::Symbol;
+use log::error;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssociative,
+}
+
+/// A production with its semantic actions separated from its grammar symbols
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub lhs: Symbol,
+    /// Terminals and non-terminals, empty for an epsilon production
+    pub rhs: Vec<Symbol>,
+    /// Executed in order when the rule is reduced
+    pub actions: Vec<Action>,
+    precedence: Option<(usize, Associativity)>,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rhs: Vec<String> = self.rhs.iter().map(|s| s.grm_notation()).collect();
+        if rhs.is_empty() {
+            rhs.push(Symbol::Epsilon.grm_notation());
+        }
+        rhs.extend(
+            self.actions
+                .iter()
+                .map(|a| format!("@{}@", a.grm_notation())),
+        );
+        write!(f, "{} ::= {}", self.lhs.grm_notation(), rhs.join(" "))
+    }
+}
+
+#[derive(Debug)]
+pub struct LalrGrammar {
+    /// The first rule is the augmented start production `<$accept> ::= <start>`
+    rules: Vec<Rule>,
+    precedence: HashMap<Symbol, (usize, Associativity)>,
+}
+
+impl LalrGrammar {
+    pub fn from_reader<R: Read>(stream: R) -> std::io::Result<Self> {
+        let mut precedence = HashMap::new();
+        let mut level = 0;
+        let mut productions = Vec::new();
+        let mut invalid_actions = false;
+
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('%') {
+                let mut fields = line.split_whitespace();
+                let associativity = match fields.next() {
+                    Some("%left") => Associativity::Left,
+                    Some("%right") => Associativity::Right,
+                    Some("%nonassoc") => Associativity::NonAssociative,
+                    Some(directive) => {
+                        error!("Unknown grammar directive \"{}\"", directive);
+                        panic!();
+                    }
+                    None => unreachable!(),
+                };
+                level += 1;
+                for terminal in fields {
+                    match terminal.parse::<Symbol>() {
+                        Ok(terminal @ Symbol::Terminal(_)) => {
+                            precedence.insert(terminal, (level, associativity));
+                        }
+                        _ => {
+                            error!(
+                                "Precedence can only be declared for terminals, found \"{}\"",
+                                terminal
+                            );
+                            panic!();
+                        }
+                    }
+                }
+            } else if let Some((lhs, rhs)) = line.split_once("::=") {
+                let lhs: Symbol = lhs.trim().parse().unwrap();
+                if !matches!(lhs, Symbol::NonTerminal(_)) {
+                    error!("Production left hand side {} is not a non-terminal", lhs);
+                    panic!();
+                }
+                let rhs = rhs
+                    .split_whitespace()
+                    .map(|s| s.parse())
+                    .collect::<Result<Vec<Symbol>, _>>();
+                match rhs {
+                    Ok(rhs) => productions.push((lhs, rhs)),
+                    Err(err) => {
+                        error!("Invalid semantic action in production {}: {}", line, err);
+                        invalid_actions = true;
+                    }
+                }
+            }
+        }
+
+        if invalid_actions {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the grammar contains invalid semantic actions",
+            ));
+        }
+        if productions.is_empty() {
+            error!("Grammar contains no productions");
+            panic!();
+        }
+        Ok(LalrGrammar::new(productions, precedence))
+    }
+
+    fn new(
+        productions: Vec<(Symbol, Vec<Symbol>)>,
+        precedence: HashMap<Symbol, (usize, Associativity)>,
+    ) -> Self {
+        let defined: HashSet<&Symbol> = productions.iter().map(|(lhs, _)| lhs).collect();
+        let mut undefined = false;
+        for (lhs, rhs) in &productions {
+            for symbol in rhs {
+                if matches!(symbol, Symbol::NonTerminal(_)) && !defined.contains(symbol) {
+                    error!(
+                        "Undefined non-terminal {} in a production of {}",
+                        symbol, lhs
+                    );
+                    undefined = true;
+                }
+            }
+        }
+        if undefined {
+            panic!();
+        }
+
+        let mut grammar = LalrGrammar {
+            rules: vec![],
+            precedence,
+        };
+        grammar.add_rule(
+            Symbol::NonTerminal("$accept".to_string()),
+            vec![productions[0].0.clone()],
+            vec![],
+        );
+
+        let mut marker_count = 0;
+        for (lhs, sentence) in productions {
+            let mut rhs = Vec::new();
+            let mut actions = Vec::new();
+            for symbol in sentence {
+                match symbol {
+                    Symbol::SemanticAction(action) => actions.push(action),
+                    Symbol::Epsilon => (),
+                    symbol => {
+                        if !actions.is_empty() {
+                            marker_count += 1;
+                            let marker = Symbol::NonTerminal(format!("$@{}", marker_count));
+                            grammar.add_rule(marker.clone(), vec![], actions.split_off(0));
+                            rhs.push(marker);
+                        }
+                        rhs.push(symbol);
+                    }
+                }
+            }
+            grammar.add_rule(lhs, rhs, actions);
+        }
+        grammar
+    }
+
+    fn add_rule(&mut self, lhs: Symbol, rhs: Vec<Symbol>, actions: Vec<Action>) {
+        let precedence = rhs
+            .iter()
+            .rev()
+            .find(|s| matches!(s, Symbol::Terminal(_)))
+            .and_then(|terminal| self.precedence.get(terminal).cloned());
+        self.rules.push(Rule {
+            lhs,
+            rhs,
+            actions,
+            precedence,
+        });
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LalrAction {
+    Shift(usize),
+    Reduce(usize),
+    Accept,
+}
+
+/// A conflict the precedence declarations did not resolve
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub state: usize,
+    pub lookahead: Symbol,
+    /// Descriptions of the action kept in the table and of the one dropped
+    pub kept: String,
+    pub dropped: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "state {} on {}: {} chosen over {}",
+            self.state,
+            self.lookahead.grm_notation(),
+            self.kept,
+            self.dropped
+        )
+    }
+}
+
+/// LR(0) item, a rule index and the position of the dot in its right hand side
+type Item = (usize, usize);
+
+/// The outgoing transitions of every state, on a grammar symbol to another state
+type Transitions = Vec<Vec<(Symbol, usize)>>;
+
+pub struct LalrTable {
+    grammar: LalrGrammar,
+    actions: Vec<HashMap<Symbol, LalrAction>>,
+    gotos: Vec<HashMap<Symbol, usize>>,
+    conflicts: Vec<Conflict>,
+}
+
+impl LalrTable {
+    pub fn from_grammar(grammar: LalrGrammar) -> Self {
+        let generator = Generator::new(&grammar);
+        let (kernels, transitions) = generator.lr0_automaton();
+        let lookaheads = generator.kernel_lookaheads(&kernels, &transitions);
+
+        let mut actions = vec![HashMap::new(); kernels.len()];
+        let mut gotos = vec![HashMap::new(); kernels.len()];
+        let mut conflicts = vec![];
+
+        for (state, state_transitions) in transitions.iter().enumerate() {
+            for (symbol, next) in state_transitions {
+                match symbol {
+                    Symbol::Terminal(_) => {
+                        actions[state].insert(symbol.clone(), LalrAction::Shift(*next));
+                    }
+                    _ => {
+                        gotos[state].insert(symbol.clone(), *next);
+                    }
+                }
+            }
+
+            let seeds = kernels[state]
+                .iter()
+                .map(|item| (*item, lookaheads[state][item].clone()))
+                .collect();
+            let mut reductions: Vec<(usize, Vec<Symbol>)> = generator
+                .lr1_closure(seeds)
+                .into_iter()
+                .filter(|((rule, dot), _)| *dot == grammar.rules[*rule].rhs.len())
+                .map(|((rule, _), lookaheads)| {
+                    let mut lookaheads: Vec<Symbol> = lookaheads.into_iter().collect();
+                    lookaheads.sort_by_key(|s| s.grm_notation());
+                    (rule, lookaheads)
+                })
+                .collect();
+            reductions.sort_by_key(|(rule, _)| *rule);
+
+            for (rule, lookaheads) in reductions {
+                let action = if rule == 0 {
+                    LalrAction::Accept
+                } else {
+                    LalrAction::Reduce(rule)
+                };
+                for lookahead in lookaheads {
+                    insert_reduction(
+                        &grammar,
+                        &mut actions[state],
+                        &mut conflicts,
+                        state,
+                        lookahead,
+                        action,
+                    );
+                }
+            }
+        }
+
+        LalrTable {
+            grammar,
+            actions,
+            gotos,
+            conflicts,
+        }
+    }
+
+    pub fn action(&self, state: usize, lookahead: &Symbol) -> Option<LalrAction> {
+        self.actions[state].get(lookahead).cloned()
+    }
+
+    /// The state reached after reducing to a non-terminal, always defined after a
+    /// reduction performed by the table
+    pub fn goto(&self, state: usize, non_terminal: &Symbol) -> usize {
+        self.gotos[state][non_terminal]
+    }
+
+    pub fn rule(&self, index: usize) -> &Rule {
+        &self.grammar.rules[index]
+    }
+
+    /// The terminals which have an action in a state, sorted
+    pub fn expected(&self, state: usize) -> Vec<&Symbol> {
+        let mut expected: Vec<&Symbol> = self.actions[state].keys().collect();
+        expected.sort_by_key(|s| s.grm_notation());
+        expected
+    }
+
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.actions.len()
+    }
+}
+
+/// Add a reduction to a state's actions, resolving a conflict with the action
+/// already there by precedence if possible
+fn insert_reduction(
+    grammar: &LalrGrammar,
+    actions: &mut HashMap<Symbol, LalrAction>,
+    conflicts: &mut Vec<Conflict>,
+    state: usize,
+    lookahead: Symbol,
+    action: LalrAction,
+) {
+    let describe = |action: LalrAction| match action {
+        LalrAction::Shift(_) => "shift".to_string(),
+        LalrAction::Reduce(rule) => format!("reduce {}", grammar.rules[rule]),
+        LalrAction::Accept => "accept".to_string(),
+    };
+
+    let existing = match actions.get(&lookahead) {
+        None => {
+            actions.insert(lookahead, action);
+            return;
+        }
+        Some(existing) => *existing,
+    };
+
+    let rule_precedence = match action {
+        LalrAction::Reduce(rule) => grammar.rules[rule].precedence,
+        _ => None,
+    };
+    let kept = match (
+        existing,
+        rule_precedence,
+        grammar.precedence.get(&lookahead),
+    ) {
+        (LalrAction::Shift(_), Some((rule_level, _)), Some((token_level, associativity))) => {
+            if rule_level > *token_level {
+                actions.insert(lookahead, action);
+            } else if rule_level == *token_level {
+                match associativity {
+                    Associativity::Left => {
+                        actions.insert(lookahead, action);
+                    }
+                    Associativity::Right => (),
+                    Associativity::NonAssociative => {
+                        actions.remove(&lookahead);
+                    }
+                }
+            }
+            return;
+        }
+        // Keep the shift, or the reduction of the earliest production
+        _ => existing,
+    };
+
+    conflicts.push(Conflict {
+        state,
+        lookahead,
+        kept: describe(kept),
+        dropped: describe(action),
+    });
+}
+
+/// FIRST sets and the LR automaton construction of a grammar
+struct Generator<'a> {
+    rules: &'a [Rule],
+    rules_by_lhs: HashMap<&'a Symbol, Vec<usize>>,
+    nullable: HashSet<&'a Symbol>,
+    first: HashMap<&'a Symbol, HashSet<Symbol>>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(grammar: &'a LalrGrammar) -> Self {
+        let rules = grammar.rules.as_slice();
+        let mut rules_by_lhs: HashMap<&Symbol, Vec<usize>> = HashMap::new();
+        for (index, rule) in rules.iter().enumerate() {
+            rules_by_lhs.entry(&rule.lhs).or_default().push(index);
+        }
+
+        let mut generator = Generator {
+            rules,
+            first: rules_by_lhs
+                .keys()
+                .map(|lhs| (*lhs, HashSet::new()))
+                .collect(),
+            rules_by_lhs,
+            nullable: HashSet::new(),
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for rule in rules {
+                let (first, nullable) = generator.sequence_first(&rule.rhs);
+                let lhs_first = generator.first.get_mut(&rule.lhs).unwrap();
+                let size = lhs_first.len();
+                lhs_first.extend(first);
+                changed |= lhs_first.len() != size;
+                if nullable {
+                    changed |= generator.nullable.insert(&rule.lhs);
+                }
+            }
+        }
+        generator
+    }
+
+    /// The terminals starting a sequence of symbols and whether it derives epsilon
+    fn sequence_first(&self, sequence: &[Symbol]) -> (HashSet<Symbol>, bool) {
+        let mut first = HashSet::new();
+        for symbol in sequence {
+            match symbol {
+                Symbol::NonTerminal(_) => {
+                    first.extend(self.first[symbol].iter().cloned());
+                    if !self.nullable.contains(symbol) {
+                        return (first, false);
+                    }
+                }
+                _ => {
+                    first.insert(symbol.clone());
+                    return (first, false);
+                }
+            }
+        }
+        (first, true)
+    }
+
+    fn closure(&self, kernel: &[Item]) -> Vec<Item> {
+        let mut items = kernel.to_vec();
+        let mut index = 0;
+        while index < items.len() {
+            let (rule, dot) = items[index];
+            if let Some(symbol @ Symbol::NonTerminal(_)) = self.rules[rule].rhs.get(dot) {
+                for next_rule in &self.rules_by_lhs[symbol] {
+                    if !items.contains(&(*next_rule, 0)) {
+                        items.push((*next_rule, 0));
+                    }
+                }
+            }
+            index += 1;
+        }
+        items
+    }
+
+    /// The kernel items of every LR(0) state and the transitions between states,
+    /// numbered in discovery order so the table is the same on every run
+    fn lr0_automaton(&self) -> (Vec<Vec<Item>>, Transitions) {
+        let mut kernels = vec![vec![(0, 0)]];
+        let mut state_index: HashMap<Vec<Item>, usize> = HashMap::new();
+        state_index.insert(kernels[0].clone(), 0);
+        let mut transitions = Vec::new();
+
+        let mut state = 0;
+        while state < kernels.len() {
+            let mut next_kernels: Vec<(Symbol, Vec<Item>)> = Vec::new();
+            for (rule, dot) in self.closure(&kernels[state]) {
+                if let Some(symbol) = self.rules[rule].rhs.get(dot) {
+                    match next_kernels.iter_mut().find(|(s, _)| s == symbol) {
+                        Some((_, kernel)) => kernel.push((rule, dot + 1)),
+                        None => next_kernels.push((symbol.clone(), vec![(rule, dot + 1)])),
+                    }
+                }
+            }
+
+            let mut state_transitions = Vec::new();
+            for (symbol, mut kernel) in next_kernels {
+                kernel.sort();
+                let next = match state_index.get(&kernel) {
+                    Some(next) => *next,
+                    None => {
+                        state_index.insert(kernel.clone(), kernels.len());
+                        kernels.push(kernel);
+                        kernels.len() - 1
+                    }
+                };
+                state_transitions.push((symbol, next));
+            }
+            transitions.push(state_transitions);
+            state += 1;
+        }
+        (kernels, transitions)
+    }
+
+    /// Closure of LR(1) items, items are kept in order of discovery
+    fn lr1_closure(&self, seeds: Vec<(Item, HashSet<Symbol>)>) -> Vec<(Item, HashSet<Symbol>)> {
+        let mut items = seeds;
+        let mut pending: Vec<usize> = (0..items.len()).collect();
+        while let Some(index) = pending.pop() {
+            let ((rule, dot), _) = items[index];
+            let symbol = match self.rules[rule].rhs.get(dot) {
+                Some(symbol @ Symbol::NonTerminal(_)) => symbol,
+                _ => continue,
+            };
+            let (mut lookaheads, nullable) = self.sequence_first(&self.rules[rule].rhs[dot + 1..]);
+            if nullable {
+                lookaheads.extend(items[index].1.iter().cloned());
+            }
+
+            for next_rule in &self.rules_by_lhs[symbol] {
+                match items.iter().position(|(item, _)| *item == (*next_rule, 0)) {
+                    Some(position) => {
+                        let size = items[position].1.len();
+                        items[position].1.extend(lookaheads.iter().cloned());
+                        if items[position].1.len() != size && !pending.contains(&position) {
+                            pending.push(position);
+                        }
+                    }
+                    None => {
+                        items.push(((*next_rule, 0), lookaheads.clone()));
+                        pending.push(items.len() - 1);
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    /// The lookaheads of the kernel items of every state, spontaneously generated
+    /// lookaheads are propagated along the automaton until none changes
+    fn kernel_lookaheads(
+        &self,
+        kernels: &[Vec<Item>],
+        transitions: &Transitions,
+    ) -> Vec<HashMap<Item, HashSet<Symbol>>> {
+        // Epsilon never appears in rules, it stands for the lookahead being propagated
+        let propagated = Symbol::Epsilon;
+        let mut lookaheads: Vec<HashMap<Item, HashSet<Symbol>>> = kernels
+            .iter()
+            .map(|kernel| kernel.iter().map(|item| (*item, HashSet::new())).collect())
+            .collect();
+        lookaheads[0].get_mut(&(0, 0)).unwrap().insert(Symbol::Eos);
+
+        let mut propagations = Vec::new();
+        for (state, kernel) in kernels.iter().enumerate() {
+            for kernel_item in kernel {
+                let seed = vec![(*kernel_item, vec![propagated.clone()].into_iter().collect())];
+                for ((rule, dot), item_lookaheads) in self.lr1_closure(seed) {
+                    let symbol = match self.rules[rule].rhs.get(dot) {
+                        Some(symbol) => symbol,
+                        None => continue,
+                    };
+                    let next = transitions[state]
+                        .iter()
+                        .find(|(s, _)| s == symbol)
+                        .unwrap()
+                        .1;
+                    for lookahead in item_lookaheads {
+                        if lookahead == propagated {
+                            propagations.push(((state, *kernel_item), (next, (rule, dot + 1))));
+                        } else {
+                            lookaheads[next]
+                                .get_mut(&(rule, dot + 1))
+                                .unwrap()
+                                .insert(lookahead);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for ((from_state, from_item), (to_state, to_item)) in &propagations {
+                let from = lookaheads[*from_state][from_item].clone();
+                let to = lookaheads[*to_state].get_mut(to_item).unwrap();
+                let size = to.len();
+                to.extend(from);
+                changed |= to.len() != size;
+            }
+        }
+        lookaheads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence_resolves_conflicts() {
+        let grammar = LalrGrammar::from_reader(
+            r#"
+            %left '+'
+            %left '*'
+            <E> ::= <E> '+' @makenode~string~+@ <E> @makefamily~3~addOp@
+            <E> ::= <E> '*' @makenode~string~*@ <E> @makefamily~3~mulOp@
+            <E> ::= 'id' @makenode~string~id@
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+        // Mid-rule actions become marker productions
+        assert_eq!(grammar.rules().len(), 6);
+        assert_eq!(
+            grammar.rules()[2].to_string(),
+            "<E> ::= <E> '+' <$@1> <E> @makefamily~3~addOp@"
+        );
+        assert_eq!(
+            grammar.rules()[1].to_string(),
+            "<$@1> ::= EPSILON @makenode~string~+@"
+        );
+
+        let table = LalrTable::from_grammar(grammar);
+        assert!(table.conflicts().is_empty());
+
+        // Without precedence every operator pair conflicts
+        let grammar = LalrGrammar::from_reader(
+            r#"
+            <E> ::= <E> '+' <E>
+            <E> ::= 'id'
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+        let table = LalrTable::from_grammar(grammar);
+        assert_eq!(table.conflicts().len(), 1);
+        assert_eq!(table.conflicts()[0].kept, "shift");
+    }
+}
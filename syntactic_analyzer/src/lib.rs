@@ -1,9 +1,24 @@
 mod grammar;
+mod grammar_check;
+mod grammar_export;
+mod lalr_parser;
+mod lalr_table;
 mod parse_table;
+mod parse_tree;
 mod parser;
+mod recursive_descent;
 mod semantic_action;
+mod stack_effect;
 mod symbol;
+mod table_cache;
 
 pub use grammar::*;
+pub use grammar_check::*;
+pub use grammar_export::*;
+pub use lalr_parser::parse_lalr;
+pub use lalr_table::*;
 pub use parse_table::*;
 pub use parser::parse;
+pub use recursive_descent::RecursiveDescentParser;
+pub use stack_effect::{non_terminal_stack_effects, sentence_stack_effect};
+pub use table_cache::*;
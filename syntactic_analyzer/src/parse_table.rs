@@ -1,6 +1,6 @@
 use crate::grammar::Grammar;
 use crate::symbol::Symbol;
-use log::trace;
+use log::{trace, warn};
 use std::collections::HashMap;
 
 #[derive(Debug)]
@@ -35,10 +35,7 @@ impl ParseTable {
                     .iter()
                     .filter(|x| matches!(x, Symbol::Terminal(_)) || matches!(x, Symbol::Eos))
                 {
-                    table
-                        .get_mut(&symbol)
-                        .unwrap()
-                        .insert(terminal.clone(), index);
+                    insert_entry(&mut table, symbol, terminal, index);
                 }
 
                 if first_set.contains(&Symbol::Epsilon) {
@@ -53,10 +50,7 @@ impl ParseTable {
                             terminal,
                             index
                         );
-                        table
-                            .get_mut(&symbol)
-                            .unwrap()
-                            .insert(terminal.clone(), index);
+                        insert_entry(&mut table, symbol, terminal, index);
                     }
                 }
             }
@@ -74,6 +68,27 @@ impl ParseTable {
     }
 }
 
+/// Set table[non_terminal, terminal], the last production inserted wins a conflict
+fn insert_entry(
+    table: &mut HashMap<Symbol, HashMap<Symbol, usize>>,
+    non_terminal: &Symbol,
+    terminal: &Symbol,
+    index: usize,
+) {
+    if let Some(previous) = table
+        .get_mut(non_terminal)
+        .unwrap()
+        .insert(terminal.clone(), index)
+    {
+        if previous != index {
+            warn!(
+                "Parse table conflict for [{}, {}] between productions {} and {}, run \"grammar check\" for details",
+                non_terminal, terminal, previous, index
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
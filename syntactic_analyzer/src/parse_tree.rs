@@ -0,0 +1,141 @@
+//! Concrete syntax tree recorded by the LL(1) parser, from which the leftmost
+//! derivation and the parse tree graph are written
+use crate::grammar::Sentence;
+use crate::symbol::Symbol;
+use output_manager::warn_write;
+use std::fs::File;
+
+struct TreeNode {
+    symbol: Symbol,
+    /// The lexeme of a matched terminal, None for a terminal inserted by error recovery
+    lexeme: Option<String>,
+    children: Vec<usize>,
+    /// The derivation step expanding the non-terminal, None if it was skipped
+    step: Option<usize>,
+}
+
+pub(crate) struct ParseTree {
+    nodes: Vec<TreeNode>,
+    /// Leaves still to be expanded or matched, the leftmost one on top
+    pending: Vec<usize>,
+    steps: usize,
+}
+
+impl ParseTree {
+    pub(crate) fn new(start: &Symbol) -> Self {
+        let mut tree = ParseTree {
+            nodes: Vec::new(),
+            pending: Vec::new(),
+            steps: 0,
+        };
+        let root = tree.add(start.clone());
+        tree.pending.push(root);
+        tree
+    }
+
+    fn add(&mut self, symbol: Symbol) -> usize {
+        self.nodes.push(TreeNode {
+            symbol,
+            lexeme: None,
+            children: Vec::new(),
+            step: None,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Expand the leftmost non-terminal with one of its productions
+    pub(crate) fn expand(&mut self, production: &Sentence) {
+        let parent = self.pending.pop().unwrap();
+        self.steps += 1;
+        self.nodes[parent].step = Some(self.steps);
+        let mut children = Vec::new();
+        for symbol in production
+            .iter()
+            .filter(|s| !matches!(s, Symbol::SemanticAction(_)))
+        {
+            let child = self.add(symbol.clone());
+            self.nodes[parent].children.push(child);
+            if *symbol != Symbol::Epsilon {
+                children.push(child);
+            }
+        }
+        self.pending.extend(children.into_iter().rev());
+    }
+
+    /// Match the leftmost terminal with a token, None if it was inserted
+    pub(crate) fn match_terminal(&mut self, lexeme: Option<&str>) {
+        let leaf = self.pending.pop().unwrap();
+        self.nodes[leaf].lexeme = lexeme.map(|lexeme| lexeme.to_string());
+    }
+
+    /// Leave the leftmost non-terminal unexpanded after a syntax error
+    pub(crate) fn skip(&mut self) {
+        self.pending.pop();
+    }
+
+    /// The sentential forms of the leftmost derivation, starting with the start symbol
+    ///
+    /// Non-terminals skipped by error recovery remain in every later form.
+    pub(crate) fn sentential_forms(&self) -> Vec<String> {
+        (0..=self.steps)
+            .map(|step| {
+                let mut form = Vec::new();
+                self.frontier(0, step, &mut form);
+                form.join(" ")
+            })
+            .collect()
+    }
+
+    fn frontier(&self, node: usize, step: usize, form: &mut Vec<String>) {
+        let tree_node = &self.nodes[node];
+        match (&tree_node.symbol, tree_node.step) {
+            (Symbol::Epsilon, _) => (),
+            (Symbol::NonTerminal(_), Some(expanded)) if expanded <= step => {
+                for child in &tree_node.children {
+                    self.frontier(*child, step, form);
+                }
+            }
+            (symbol, _) => form.push(symbol.to_string()),
+        }
+    }
+
+    /// Write the leftmost derivation, one sentential form per line
+    pub(crate) fn write_derivation(&self, file: &mut File, path: &str) {
+        for (index, form) in self.sentential_forms().iter().enumerate() {
+            let prefix = if index == 0 { "" } else { "=> " };
+            warn_write(file, path, &format!("{}{}\n", prefix, form));
+        }
+    }
+
+    /// Write the tree as a DOT graph, terminals are boxes showing their lexeme
+    ///
+    /// Inserted terminals and skipped non-terminals are dashed.
+    pub(crate) fn dot_graph(&self, file: &mut File, path: &str) {
+        warn_write(file, path, "digraph parse_tree {\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            let symbol = dot_escape(&node.symbol.to_string());
+            let attributes = match &node.symbol {
+                Symbol::Terminal(_) => match &node.lexeme {
+                    Some(lexeme) => {
+                        format!(r#"shape=box label="{}\n{}""#, symbol, dot_escape(lexeme))
+                    }
+                    None => format!(r#"shape=box style=dashed label="{}""#, symbol),
+                },
+                Symbol::Epsilon => format!(r#"shape=plaintext label="{}""#, symbol),
+                _ if node.step.is_none() => format!(r#"style=dashed label="{}""#, symbol),
+                _ => format!(r#"label="{}""#, symbol),
+            };
+            warn_write(file, path, &format!("n{} [{}]\n", index, attributes));
+        }
+        for (index, node) in self.nodes.iter().enumerate() {
+            for child in &node.children {
+                warn_write(file, path, &format!("n{} -> n{}\n", index, child));
+            }
+        }
+        warn_write(file, path, "}");
+    }
+}
+
+fn dot_escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
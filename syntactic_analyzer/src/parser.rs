@@ -1,16 +1,88 @@
 use crate::grammar::Grammar;
 use crate::parse_table::ParseTable;
+use crate::parse_tree::ParseTree;
+use crate::stack_effect::{non_terminal_stack_effects, push_error_nodes};
 use crate::symbol::Symbol;
 use lexical_analyzer::{Lex, Token};
 use log::{error, trace};
-use output_manager::{warn_write, write_array, write_list, OutputConfig};
+use output_manager::{warn_write, write_array, write_list, DerivationMode, OutputConfig};
+use std::fs::File;
 
+/// The tokens of a lexer with one token of lookahead past the current one
+pub(crate) struct TokenStream<'a, 'l> {
+    lexer: &'a mut Lex<'l, File>,
+    peeked: Option<Option<Token>>,
+}
+
+impl<'a, 'l> TokenStream<'a, 'l> {
+    pub(crate) fn new(lexer: &'a mut Lex<'l, File>) -> Self {
+        TokenStream {
+            lexer,
+            peeked: None,
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Option<Token> {
+        match self.peeked.take() {
+            Some(token) => token,
+            None => self.lexer.next(),
+        }
+    }
+
+    /// The token after the one last returned by `next`
+    pub(crate) fn peek(&mut self) -> &Option<Token> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lexer.next());
+        }
+        self.peeked.as_ref().unwrap()
+    }
+}
+
+/// The token standing for a missing terminal, at the position of the token found
+/// in its place or right after the previous token at the end of file
+///
+/// It is marked as an error token so nodes made from it are error nodes.
+pub(crate) fn inserted_token(
+    terminal: &str,
+    current_token: &Option<Token>,
+    previous_token: &Option<Token>,
+) -> Token {
+    let (line, column) = match (current_token, previous_token) {
+        (Some(token), _) => (token.line, token.column),
+        (None, Some(token)) => (token.line, token.column + token.lexeme.chars().count()),
+        (None, None) => (1, 1),
+    };
+    Token {
+        token_type: terminal.to_string(),
+        error_token: true,
+        lexeme: terminal.to_string(),
+        line,
+        column,
+    }
+}
+
+/// Table driven LL(1) parsing building the AST with the grammar's semantic actions
+///
+/// Syntax errors are repaired when a single token can be inserted or deleted: the
+/// unexpected token is deleted if the next one is acceptable and it may not follow
+/// the non-terminal being expanded, otherwise a missing terminal is inserted if the
+/// unexpected token may follow it. Non-terminals deriving the empty string do so on
+/// an unexpected token, leaving the repair to the next terminal. Otherwise tokens and
+/// non-terminals are skipped in panic mode, nodes which could not be built are
+/// replaced by error nodes to keep the AST well formed.
+///
+/// The derivation file either traces the parser or lists the sentential forms of
+/// the leftmost derivation, written along with the parse tree once parsing ends.
+///
+/// The AST is returned despite syntax errors, None only if nothing was parsed.
 pub fn parse(
     lexer: &mut Lex<std::fs::File>,
     grammar: &Grammar,
     parse_table: &ParseTable,
     output_config: &mut OutputConfig,
 ) -> Option<ast::Node> {
+    let stack_effects = non_terminal_stack_effects(grammar.productions());
+    let mut lexer = TokenStream::new(lexer);
     let eos_stack = vec![Symbol::Eos];
     let mut symbol_stack = vec![Symbol::Eos, grammar.start().clone()];
     let mut semantic_stack: Vec<ast::Node> = Vec::new();
@@ -18,8 +90,14 @@ pub fn parse(
     let mut previous_token = current_token.clone();
     let mut error = false;
     let mut previous_grammar_lhs = Symbol::Eos;
+    let trace = output_config.derivation_mode == DerivationMode::Trace;
+    let mut parse_tree = if !trace || output_config.parse_tree_file.is_some() {
+        Some(ParseTree::new(grammar.start()))
+    } else {
+        None
+    };
 
-    if let Some(token) = current_token.clone() {
+    if let (true, Some(token)) = (trace, current_token.clone()) {
         warn_write(
             &mut output_config.derivation_file,
             &output_config.derivation_path,
@@ -38,7 +116,10 @@ pub fn parse(
                     symbol_stack.pop();
                     previous_token = current_token;
                     current_token = lexer.next();
-                    if let Some(token) = current_token.clone() {
+                    if let Some(tree) = &mut parse_tree {
+                        tree.match_terminal(previous_token.as_ref().map(|t| t.lexeme.as_str()));
+                    }
+                    if let (true, Some(token)) = (trace, current_token.clone()) {
                         warn_write(
                             &mut output_config.derivation_file,
                             &output_config.derivation_path,
@@ -49,30 +130,90 @@ pub fn parse(
                     trace!("Stack: {:?}", symbol_stack);
                 } else {
                     error = true;
-                    skip_errors(
+                    let terminal_follow = grammar.terminal_follow(&symbol_stack_top);
+                    if current_token.is_some()
+                        && Symbol::from_token(lexer.peek()) == symbol_stack_top
+                    {
+                        report_deletion(&current_token, output_config);
+                        current_token = lexer.next();
+                    } else if current_token.is_none() || terminal_follow.contains(&token_symbol) {
+                        report_insertion(symbol, &current_token, output_config);
+                        previous_token =
+                            Some(inserted_token(symbol, &current_token, &previous_token));
+                        symbol_stack.pop();
+                        if let Some(tree) = &mut parse_tree {
+                            tree.match_terminal(None);
+                        }
+                    } else {
+                        // Neither repair applies, skip to a token the terminal or its
+                        // insertion accepts
+                        report_unexpected(symbol, &current_token, output_config);
+                        while current_token.is_some()
+                            && Symbol::from_token(&current_token) != symbol_stack_top
+                            && !terminal_follow.contains(&Symbol::from_token(&current_token))
+                        {
+                            current_token = lexer.next();
+                        }
+                    }
+                }
+            }
+            Symbol::NonTerminal(_) => {
+                let option_index = if parse_table.contains(&symbol_stack_top, &token_symbol) {
+                    Some(parse_table.get(&symbol_stack_top, &token_symbol))
+                } else if let Some(option_index) = grammar.epsilon_production(&symbol_stack_top) {
+                    // Leave the error to the next terminal, where it may be repaired
+                    Some(option_index)
+                } else {
+                    error = true;
+                    // A token which may follow the non-terminal is rather kept
+                    if current_token.is_some()
+                        && !grammar.follow(&symbol_stack_top).contains(&token_symbol)
+                        && parse_table
+                            .contains(&symbol_stack_top, &Symbol::from_token(lexer.peek()))
+                    {
+                        report_deletion(&current_token, output_config);
+                        current_token = lexer.next();
+                    } else if skip_errors(
                         grammar,
-                        lexer,
+                        &mut lexer,
                         &mut current_token,
                         &mut symbol_stack,
                         parse_table,
                         output_config,
-                    );
-                }
-            }
-            Symbol::NonTerminal(_) => {
-                if parse_table.contains(&symbol_stack_top, &token_symbol) {
-                    let option_index = parse_table.get(&symbol_stack_top, &token_symbol);
+                    ) {
+                        let (line, column) = match &current_token {
+                            Some(token) => (token.line, token.column),
+                            None => (0, 0),
+                        };
+                        if let Some(tree) = &mut parse_tree {
+                            tree.skip();
+                        }
+                        push_error_nodes(
+                            &mut semantic_stack,
+                            stack_effects.get(&symbol_stack_top).cloned().unwrap_or(0),
+                            line,
+                            column,
+                        );
+                    }
+                    None
+                };
+                if let Some(option_index) = option_index {
                     let production = grammar.production(&symbol_stack_top, option_index);
-                    warn_write(
-                        &mut output_config.derivation_file,
-                        &output_config.derivation_path,
-                        &format!("{} -> ", symbol_stack_top),
-                    );
-                    write_list(
-                        &mut output_config.derivation_file,
-                        &output_config.derivation_path,
-                        production,
-                    );
+                    if let Some(tree) = &mut parse_tree {
+                        tree.expand(production);
+                    }
+                    if trace {
+                        warn_write(
+                            &mut output_config.derivation_file,
+                            &output_config.derivation_path,
+                            &format!("{} -> ", symbol_stack_top),
+                        );
+                        write_list(
+                            &mut output_config.derivation_file,
+                            &output_config.derivation_path,
+                            production,
+                        );
+                    }
                     previous_grammar_lhs = symbol_stack_top.clone();
                     symbol_stack.pop();
                     symbol_stack.extend(
@@ -82,27 +223,19 @@ pub fn parse(
                             .rev()
                             .cloned(),
                     );
-                    warn_write(
-                        &mut output_config.derivation_file,
-                        &output_config.derivation_path,
-                        "Stack: ",
-                    );
-                    write_list(
-                        &mut output_config.derivation_file,
-                        &output_config.derivation_path,
-                        &symbol_stack,
-                    );
+                    if trace {
+                        warn_write(
+                            &mut output_config.derivation_file,
+                            &output_config.derivation_path,
+                            "Stack: ",
+                        );
+                        write_list(
+                            &mut output_config.derivation_file,
+                            &output_config.derivation_path,
+                            &symbol_stack,
+                        );
+                    }
                     trace!("Stack: {:?}", symbol_stack);
-                } else {
-                    error = true;
-                    skip_errors(
-                        grammar,
-                        lexer,
-                        &mut current_token,
-                        &mut symbol_stack,
-                        parse_table,
-                        output_config,
-                    );
                 }
             }
             Symbol::SemanticAction(action) => {
@@ -128,75 +261,115 @@ pub fn parse(
     // info!("Symbol stack: {:?}", symbol_stack);
     // info!("Current Token: {:?}", current_token);
 
-    if symbol_stack.last().is_none() {
-        // Ran out of productions before end of tokens
+    if let Some(token) = current_token {
+        // The AST of the program is kept, the tokens after its end are ignored
         warn_write(
             &mut output_config.syntax_error_file,
             &output_config.syntax_error_path,
             &format!(
-                "Syntax error: expected end of file, but got {}",
-                current_token.unwrap()
+                "Syntax error at line {}, col {}: unexpected token {}, but was expecting end of file\n",
+                token.line, token.column, token.lexeme
             ),
         );
         error!("Parsing encountered errors, see the associated output files");
-        return None;
-    } else if !current_token.is_none() || symbol_stack.last() != Some(&Symbol::Eos) {
-        // Expected end of file
-        warn_write(
-            &mut output_config.syntax_error_file,
-            &output_config.syntax_error_path,
-            &format!(
-                "Syntax error: unexpected token {}, but was expecting end of file",
-                current_token.unwrap()
-            ),
-        );
-        error!("Parsing encountered errors, see the associated output files");
-        return None;
-    } else if !semantic_stack.is_empty() {
-        let top = semantic_stack.pop().unwrap();
-        top.dot_graph(&mut output_config.ast_file);
-        return Some(top);
     }
-    return None;
+
+    if let Some(tree) = &parse_tree {
+        if !trace {
+            tree.write_derivation(
+                &mut output_config.derivation_file,
+                &output_config.derivation_path,
+            );
+        }
+        if let Some(file) = &mut output_config.parse_tree_file {
+            tree.dot_graph(file, &output_config.parse_tree_path);
+        }
+    }
+
+    let top = semantic_stack.pop()?;
+    top.dot_graph(&mut output_config.ast_file);
+    Some(top)
+}
+
+fn report_insertion(
+    terminal: &str,
+    current_token: &Option<Token>,
+    output_config: &mut OutputConfig,
+) {
+    let message = match current_token {
+        Some(token) => format!(
+            "Syntax error at line {}, col {}: inserted '{}' before '{}'\n",
+            token.line, token.column, terminal, token.lexeme
+        ),
+        None => format!(
+            "Syntax error: unexpected end of file, inserted '{}'\n",
+            terminal
+        ),
+    };
+    warn_write(
+        &mut output_config.syntax_error_file,
+        &output_config.syntax_error_path,
+        &message,
+    );
 }
 
+fn report_unexpected(
+    terminal: &str,
+    current_token: &Option<Token>,
+    output_config: &mut OutputConfig,
+) {
+    let token = current_token.as_ref().unwrap();
+    warn_write(
+        &mut output_config.syntax_error_file,
+        &output_config.syntax_error_path,
+        &format!(
+            "Syntax error at line {}, col {}: encountered {}, but was expecting '{}'\n",
+            token.line, token.column, token.lexeme, terminal
+        ),
+    );
+}
+
+fn report_deletion(current_token: &Option<Token>, output_config: &mut OutputConfig) {
+    let token = current_token.as_ref().unwrap();
+    warn_write(
+        &mut output_config.syntax_error_file,
+        &output_config.syntax_error_path,
+        &format!(
+            "Syntax error at line {}, col {}: deleted unexpected '{}'\n",
+            token.line, token.column, token.lexeme
+        ),
+    );
+}
+
+/// Panic mode recovery for a non-terminal without production for the lookahead,
+/// either skipping it or the tokens which cannot start it
+///
+/// Returns whether the non-terminal was skipped.
 fn skip_errors(
     grammar: &Grammar,
-    lexer: &mut Lex<std::fs::File>,
+    lexer: &mut TokenStream,
     current_token: &mut Option<Token>,
     symbol_stack: &mut Vec<Symbol>,
     parse_table: &ParseTable,
     output_config: &mut OutputConfig,
-) {
+) -> bool {
     let mut lookahead = Symbol::from_token(current_token);
+    let top = symbol_stack.last().unwrap().clone();
 
     match current_token.clone() {
         Some(lex_token) => {
-            match &symbol_stack.last().clone().unwrap() {
-                Symbol::Terminal(c) => {
-                    warn_write(&mut output_config.syntax_error_file, &output_config.syntax_error_path, &format!("Syntax error at line {}, col {}: encountered {}, but was expecting {}\n", lex_token.line, lex_token.column, lex_token.lexeme, c));
-                }
-                Symbol::NonTerminal(nt) => {
-                    warn_write(&mut output_config.syntax_error_file, &output_config.syntax_error_path, &format!("Syntax error at line {}, col {}: encountered {}, but was expecting a {} which begins with one of ", lex_token.line, lex_token.column, lex_token.lexeme, nt));
-                    write_array(
-                        &mut output_config.syntax_error_file,
-                        &output_config.syntax_error_path,
-                        &parse_table
-                            .table
-                            .get(symbol_stack.last().clone().unwrap())
-                            .unwrap()
-                            .iter()
-                            .map(|x| x.0)
-                            .collect(),
-                    );
-                    warn_write(
-                        &mut output_config.syntax_error_file,
-                        &output_config.syntax_error_path,
-                        "\n",
-                    );
-                }
-                _ => (),
-            }
+            warn_write(&mut output_config.syntax_error_file, &output_config.syntax_error_path, &format!("Syntax error at line {}, col {}: encountered {}, but was expecting a {} which begins with one of ", lex_token.line, lex_token.column, lex_token.lexeme, top));
+            write_array(
+                &mut output_config.syntax_error_file,
+                &output_config.syntax_error_path,
+                &parse_table
+                    .table
+                    .get(&top)
+                    .unwrap()
+                    .iter()
+                    .map(|x| x.0)
+                    .collect(),
+            );
         }
         None => {
             warn_write(
@@ -207,27 +380,158 @@ fn skip_errors(
         }
     }
 
-    let mut reconstructed_stack = symbol_stack.clone();
-    while reconstructed_stack.len() > 0
-        && !matches!(reconstructed_stack.last().unwrap(), Symbol::NonTerminal(_))
-    {
-        reconstructed_stack.pop();
-    }
-    *symbol_stack = reconstructed_stack;
-    let top = symbol_stack.last().unwrap();
-
     trace!("Stack: {:?}", symbol_stack);
-    if lookahead == Symbol::Eos || grammar.follow(top).contains(&lookahead) {
+    if lookahead == Symbol::Eos || grammar.follow(&top).contains(&lookahead) {
         symbol_stack.pop();
         trace!("Stack: {:?}", symbol_stack);
+        true
     } else {
-        while !(grammar.first(top).contains(&lookahead)
-            || grammar.first(top).contains(&Symbol::Epsilon)
-                && grammar.follow(top).contains(&lookahead))
+        while !(grammar.first(&top).contains(&lookahead)
+            || grammar.first(&top).contains(&Symbol::Epsilon)
+                && grammar.follow(&top).contains(&lookahead))
             && !current_token.is_none()
         {
             *current_token = lexer.next();
             lookahead = Symbol::from_token(current_token);
         }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ast::Data;
+    use lexical_analyzer::{lexer::Lexer, lexical_rule::LexicalRule};
+    use std::convert::TryFrom;
+
+    /// Parse a source file written to the temporary folder with the LL(1) grammar
+    fn parse_source(
+        name: &str,
+        contents: &str,
+        configure: impl FnOnce(&mut OutputConfig),
+    ) -> (Option<ast::Node>, OutputConfig) {
+        let rules = LexicalRule::from_file("../resources/lex_tokens.txt").unwrap();
+        let keywords = std::fs::read_to_string("../resources/keywords.txt")
+            .unwrap()
+            .lines()
+            .map(|keyword| keyword.to_string())
+            .collect();
+        let lexer = Lexer::new(rules, keywords);
+        let grammar =
+            Grammar::from_reader(File::open("../resources/LL1.paquet.grm").unwrap()).unwrap();
+        let parse_table = ParseTable::from_grammar(&grammar);
+
+        let output_folder = std::env::temp_dir().join("paquet_parser_test");
+        std::fs::create_dir_all(&output_folder).unwrap();
+        let source = output_folder.join(name);
+        std::fs::write(&source, contents).unwrap();
+        let source = source.to_str().unwrap();
+        let mut output_config = OutputConfig::new(source, output_folder.to_str().unwrap());
+        configure(&mut output_config);
+        let ast = parse(
+            &mut lexer.lex(source, &output_config.lex_error_path),
+            &grammar,
+            &parse_table,
+            &mut output_config,
+        );
+        (ast, output_config)
+    }
+
+    #[test]
+    fn test_repairs_keep_the_ast() {
+        let (ast, output_config) = parse_source(
+            "repair.src",
+            "main {\n  var {\n    integer x\n    integer ) y;\n  }\n  x = 1 + ;\n}\n",
+            |_| (),
+        );
+        let ast = ast.unwrap();
+
+        let errors = std::fs::read_to_string(&output_config.syntax_error_path).unwrap();
+        let errors: Vec<_> = errors.lines().collect();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[0],
+            "Syntax error at line 4, col 5: inserted ';' before 'integer'"
+        );
+        assert_eq!(
+            errors[1],
+            "Syntax error at line 4, col 13: deleted unexpected ')'"
+        );
+        assert_eq!(ast.dft().filter(|node| node.name() == "varDecl").count(), 2);
+        let error_nodes: Vec<_> = ast.dft().filter(|node| node.is_error()).collect();
+        assert_eq!(error_nodes.len(), 1);
+        assert_eq!((*error_nodes[0].line(), *error_nodes[0].column()), (6, 11));
+    }
+
+    #[test]
+    fn test_leftmost_derivation_and_parse_tree() {
+        let (_, output_config) =
+            parse_source("derivation.src", "main {\n  x = 1;\n}\n", |config| {
+                config.derivation_mode = DerivationMode::Leftmost;
+                config.enable_parse_tree();
+            });
+
+        let derivation = std::fs::read_to_string(&output_config.derivation_path).unwrap();
+        let forms: Vec<_> = derivation.lines().collect();
+        assert_eq!(forms[0], "<START>");
+        assert_eq!(forms[1], "=> <Prog>");
+        assert_eq!(forms[2], "=> <ClassDecl> <FuncDef> 'main' <FuncBody>");
+        assert_eq!(forms[3], "=> <FuncDef> 'main' <FuncBody>");
+        assert_eq!(
+            *forms.last().unwrap(),
+            "=> 'main' '{' 'id' 'assign' 'intnum' ';' '}'"
+        );
+
+        let parse_tree = std::fs::read_to_string(&output_config.parse_tree_path).unwrap();
+        assert!(parse_tree.starts_with("digraph parse_tree {\n"));
+        assert!(parse_tree.contains("[shape=box label=\"'intnum'\\n1\"]"));
+        assert!(parse_tree.contains("[shape=plaintext label=\"EPSILON\"]"));
+    }
+
+    #[test]
+    fn test_braceless_statement_blocks() {
+        let (ast, output_config) = parse_source(
+            "braceless.src",
+            "main {\n  if (x == 1) then x = 2; else x = 3;;\n  while (x < 5) x = x + 1;;\n}\n",
+            |_| (),
+        );
+        assert!(!output_config.has_syntax_errors());
+        let ast = ast.unwrap();
+
+        // Each block holds its single statement like a braced one
+        let names = |node: &ast::Node| match node.data() {
+            Data::Children(children) => children.iter().map(|c| c.name().clone()).collect(),
+            _ => Vec::new(),
+        };
+        let if_stat = ast.dft().find(|node| node.name() == "ifStat").unwrap();
+        assert_eq!(names(if_stat), vec!["relOp", "statBlock", "statBlock"]);
+        let while_stat = ast.dft().find(|node| node.name() == "whileStat").unwrap();
+        assert_eq!(names(while_stat), vec!["relOp", "statBlock"]);
+        for stat in &[if_stat, while_stat] {
+            if let Data::Children(children) = stat.data() {
+                for block in &children[1..] {
+                    assert_eq!(names(block), vec!["assignOp"]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_asts_convert_to_typed_ast() {
+        let mut converted = 0;
+        for entry in std::fs::read_dir("../test_sources").unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            let contents = std::fs::read_to_string(&path).unwrap();
+            let (ast, output_config) = parse_source(&name, &contents, |_| ());
+            if !output_config.has_syntax_errors() {
+                if let Err(err) = ast::typed::Program::try_from(&ast.unwrap()) {
+                    panic!("{} in {}", err, name);
+                }
+                converted += 1;
+            }
+        }
+        assert!(converted > 0);
     }
 }
@@ -0,0 +1,1157 @@
+//! Hand-written recursive descent parser for the language of `LL1.paquet.grm`
+//!
+//! Every non-terminal of the LL(1) grammar has a method executing the same semantic
+//! actions in the same order, so the AST is identical to the one `parse` builds.
+//! Error recovery also follows `parse`: an unexpected token is deleted if the next
+//! one is acceptable, otherwise a missing terminal is inserted and a non-terminal
+//! is skipped if the lookahead may follow it, or tokens are skipped until one may
+//! start it. Errors are therefore reported at the same positions, with messages
+//! naming the construct being parsed, and the same error nodes are built.
+use crate::grammar::Grammar;
+use crate::parse_table::ParseTable;
+use crate::parser::{inserted_token, TokenStream};
+use crate::semantic_action::Action;
+use crate::stack_effect::{non_terminal_stack_effects, push_error_nodes};
+use crate::symbol::Symbol;
+use ast::Node;
+use lexical_analyzer::{Lex, Token};
+use log::error;
+use output_manager::{warn_write, OutputConfig};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+
+/// The sets of a non-terminal used to decide if the lookahead is acceptable
+struct SyncSets {
+    /// FIRST set without epsilon
+    first: HashSet<Symbol>,
+    nullable: bool,
+    /// Whether a production derives the empty string, it is then taken on an
+    /// unexpected lookahead so that the error is detected on the next terminal
+    epsilon_production: bool,
+    follow: HashSet<Symbol>,
+    /// Net number of nodes left on the semantic stack, replaced by error nodes
+    /// when the non-terminal is skipped
+    stack_effect: isize,
+}
+
+pub struct RecursiveDescentParser {
+    sets: HashMap<String, SyncSets>,
+    /// The tokens which may follow each terminal, to decide if a missing terminal
+    /// can be inserted
+    terminal_follows: HashMap<String, HashSet<Symbol>>,
+    /// Decides which non-terminals apply to the lookahead exactly as `parse` does
+    table: ParseTable,
+}
+
+impl RecursiveDescentParser {
+    /// The grammar provides the FIRST and FOLLOW sets used for error recovery, it
+    /// must define the non-terminals of `LL1.paquet.grm`
+    pub fn new(grammar: &Grammar) -> Self {
+        let stack_effects = non_terminal_stack_effects(grammar.productions());
+        let sets = grammar
+            .productions()
+            .keys()
+            .filter_map(|non_terminal| match non_terminal {
+                Symbol::NonTerminal(name) => {
+                    let mut first = grammar.first(non_terminal);
+                    let nullable = first.remove(&Symbol::Epsilon);
+                    let follow = grammar.follow(non_terminal).clone();
+                    Some((
+                        name.clone(),
+                        SyncSets {
+                            first,
+                            nullable,
+                            epsilon_production: grammar.epsilon_production(non_terminal).is_some(),
+                            follow,
+                            stack_effect: stack_effects.get(non_terminal).cloned().unwrap_or(0),
+                        },
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        let terminal_follows = grammar
+            .productions()
+            .values()
+            .flatten()
+            .flatten()
+            .filter_map(|symbol| match symbol {
+                Symbol::Terminal(name) => Some((name.clone(), grammar.terminal_follow(symbol))),
+                _ => None,
+            })
+            .collect();
+        RecursiveDescentParser {
+            sets,
+            terminal_follows,
+            table: ParseTable::from_grammar(grammar),
+        }
+    }
+
+    /// Returns the AST despite syntax errors, like `parse`
+    pub fn parse(
+        &self,
+        lexer: &mut Lex<File>,
+        output_config: &mut OutputConfig,
+    ) -> Option<ast::Node> {
+        let mut lexer = TokenStream::new(lexer);
+        let current_token = lexer.next();
+        let mut parser = Parser {
+            sets: &self.sets,
+            terminal_follows: &self.terminal_follows,
+            table: &self.table,
+            lexer,
+            output_config,
+            previous_token: current_token.clone(),
+            current_token,
+            semantic_stack: Vec::new(),
+            error: false,
+        };
+        parser.start();
+
+        if parser.error {
+            error!("Parsing encountered errors, see the associated outsyntaxerrors files");
+        }
+
+        if let Some(token) = parser.current_token.clone() {
+            parser.report(format!(
+                "Syntax error at line {}, col {}: expected the end of file after the main function, found {}\n",
+                token.line,
+                token.column,
+                describe_token(&parser.current_token)
+            ));
+            error!("Parsing encountered errors, see the associated output files");
+        }
+
+        let top = parser.semantic_stack.pop()?;
+        top.dot_graph(&mut parser.output_config.ast_file);
+        Some(top)
+    }
+}
+
+struct Parser<'p, 'l> {
+    sets: &'p HashMap<String, SyncSets>,
+    terminal_follows: &'p HashMap<String, HashSet<Symbol>>,
+    table: &'p ParseTable,
+    lexer: TokenStream<'p, 'l>,
+    output_config: &'p mut OutputConfig,
+    current_token: Option<Token>,
+    previous_token: Option<Token>,
+    semantic_stack: Vec<Node>,
+    error: bool,
+}
+
+impl<'p, 'l> Parser<'p, 'l> {
+    // Parsing primitives =====================================================
+
+    fn lookahead(&self) -> Option<&str> {
+        self.current_token
+            .as_ref()
+            .map(|token| token.token_type.as_str())
+    }
+
+    fn sets(&self, non_terminal: &str) -> &'p SyncSets {
+        match self.sets.get(non_terminal) {
+            Some(sets) => sets,
+            None => {
+                error!(
+                    "The recursive descent parser requires the non-terminal <{}> in the grammar",
+                    non_terminal
+                );
+                panic!();
+            }
+        }
+    }
+
+    /// Whether the lookahead can start a non-terminal
+    fn starts(&self, non_terminal: &str) -> bool {
+        self.sets(non_terminal)
+            .first
+            .contains(&Symbol::from_token(&self.current_token))
+    }
+
+    /// Whether some production of a non-terminal applies to the lookahead
+    fn accepts(&self, non_terminal: &str) -> bool {
+        self.applies(non_terminal, Symbol::from_token(&self.current_token))
+    }
+
+    fn applies(&self, non_terminal: &str, lookahead: Symbol) -> bool {
+        self.table
+            .contains(&Symbol::NonTerminal(non_terminal.to_string()), &lookahead)
+    }
+
+    /// Whether panic mode recovery may stop skipping tokens for a non-terminal
+    fn synchronizes(&self, non_terminal: &str) -> bool {
+        let sets = self.sets(non_terminal);
+        let lookahead = Symbol::from_token(&self.current_token);
+        sets.first.contains(&lookahead) || sets.nullable && sets.follow.contains(&lookahead)
+    }
+
+    /// Prepare to parse a non-terminal, returns false if it must be skipped to recover
+    /// from an error
+    fn enter(&mut self, non_terminal: &str) -> bool {
+        while !self.accepts(non_terminal) {
+            if self.sets(non_terminal).epsilon_production {
+                return true;
+            }
+            self.error = true;
+            let next = Symbol::from_token(self.lexer.peek());
+            if self.current_token.is_some()
+                && !self
+                    .sets(non_terminal)
+                    .follow
+                    .contains(&Symbol::from_token(&self.current_token))
+                && self.applies(non_terminal, next)
+            {
+                self.delete();
+                continue;
+            }
+            self.report(format!(
+                "{}: expected {}, found {}\n",
+                self.error_location(),
+                describe_non_terminal(non_terminal),
+                describe_token(&self.current_token)
+            ));
+            if !self.resynchronize(non_terminal) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns false if the non-terminal must be skipped, leaving error nodes in
+    /// place of its nodes, otherwise skips tokens until one may start it
+    fn resynchronize(&mut self, non_terminal: &str) -> bool {
+        let sets = self.sets(non_terminal);
+        let lookahead = Symbol::from_token(&self.current_token);
+        if lookahead == Symbol::Eos || sets.follow.contains(&lookahead) {
+            let (line, column) = match &self.current_token {
+                Some(token) => (token.line, token.column),
+                None => (0, 0),
+            };
+            push_error_nodes(&mut self.semantic_stack, sets.stack_effect, line, column);
+            return false;
+        }
+        while self.current_token.is_some() && !self.synchronizes(non_terminal) {
+            self.current_token = self.lexer.next();
+        }
+        true
+    }
+
+    /// Consume a terminal, `context` completes the error message if it is missing
+    ///
+    /// An unexpected token is deleted if the terminal follows it, otherwise the
+    /// terminal is inserted.
+    fn expect(&mut self, terminal: &str, context: &str) {
+        if self.lookahead() != Some(terminal) {
+            self.error = true;
+            let next = self
+                .lexer
+                .peek()
+                .as_ref()
+                .map(|token| token.token_type.as_str());
+            let follow = &self.terminal_follows[terminal];
+            if self.current_token.is_some() && next == Some(terminal) {
+                self.delete();
+            } else if self.current_token.is_some()
+                && !follow.contains(&Symbol::from_token(&self.current_token))
+            {
+                // Neither repair applies, skip to a token the terminal or its
+                // insertion accepts
+                self.report(format!(
+                    "{}: expected {}{}, found {}\n",
+                    self.error_location(),
+                    describe_terminal(terminal),
+                    with_context(context),
+                    describe_token(&self.current_token)
+                ));
+                while self.current_token.is_some()
+                    && self.lookahead() != Some(terminal)
+                    && !follow.contains(&Symbol::from_token(&self.current_token))
+                {
+                    self.current_token = self.lexer.next();
+                }
+                return self.expect(terminal, context);
+            } else {
+                self.report(format!(
+                    "{}: inserted missing {}{} before {}\n",
+                    self.error_location(),
+                    describe_terminal(terminal),
+                    with_context(context),
+                    describe_token(&self.current_token)
+                ));
+                self.previous_token = Some(inserted_token(
+                    terminal,
+                    &self.current_token,
+                    &self.previous_token,
+                ));
+                return;
+            }
+        }
+        self.previous_token = self.current_token.take();
+        self.current_token = self.lexer.next();
+    }
+
+    /// Skip an unexpected token
+    fn delete(&mut self) {
+        self.report(format!(
+            "{}: deleted unexpected {}\n",
+            self.error_location(),
+            describe_token(&self.current_token)
+        ));
+        self.current_token = self.lexer.next();
+    }
+
+    fn execute(&mut self, action: Action) {
+        action.execute(
+            &mut self.semantic_stack,
+            self.previous_token.clone().unwrap(),
+            Symbol::Eos,
+        );
+    }
+
+    fn make_node(&mut self, data_type: &str, name: &str) {
+        self.execute(Action::MakeNode(data_type.to_string(), name.to_string()));
+    }
+
+    fn make_family(&mut self, size: usize, name: &str) {
+        self.execute(Action::MakeFamily(size, name.to_string()));
+    }
+
+    fn make_sibling(&mut self) {
+        self.execute(Action::MakeSibling);
+    }
+
+    fn error_location(&self) -> String {
+        match &self.current_token {
+            Some(token) => format!("Syntax error at line {}, col {}", token.line, token.column),
+            None => "Syntax error at end of file".to_string(),
+        }
+    }
+
+    fn report(&mut self, message: String) {
+        warn_write(
+            &mut self.output_config.syntax_error_file,
+            &self.output_config.syntax_error_path,
+            &message,
+        );
+    }
+
+    // Program structure ======================================================
+
+    fn start(&mut self) {
+        if self.enter("START") {
+            self.prog();
+        }
+    }
+
+    fn prog(&mut self) {
+        if !self.enter("Prog") {
+            return;
+        }
+        self.make_node("list", "classDeclList");
+        self.class_decl();
+        self.make_node("list", "funcDefList");
+        self.func_def();
+        self.expect("main", "after the function definitions");
+        self.func_body();
+        self.make_family(3, "prog");
+    }
+
+    fn class_decl(&mut self) {
+        while self.enter("ClassDecl") && self.lookahead() == Some("class") {
+            self.expect("class", "");
+            self.expect("id", "naming the class");
+            self.make_node("string", "id");
+            self.make_node("list", "inherList");
+            self.inherit();
+            self.expect("{", "to open the class body");
+            self.make_node("list", "membList");
+            self.class_decl_body();
+            self.expect("}", "to close the class body");
+            self.make_family(3, "classDecl");
+            self.make_sibling();
+            self.expect(";", "after the class declaration");
+        }
+    }
+
+    fn inherit(&mut self) {
+        if !self.enter("Inherit") || self.lookahead() != Some("inherits") {
+            return;
+        }
+        self.expect("inherits", "");
+        self.expect("id", "naming the inherited class");
+        self.make_node("string", "id");
+        self.make_sibling();
+        self.nested_id();
+    }
+
+    fn nested_id(&mut self) {
+        while self.enter("NestedId") && self.lookahead() == Some(",") {
+            self.expect(",", "");
+            self.expect("id", "naming the inherited class");
+            self.make_node("string", "id");
+            self.make_sibling();
+        }
+    }
+
+    fn class_decl_body(&mut self) {
+        while self.enter("ClassDeclBody") && self.starts("ClassDeclBody") {
+            self.visibility();
+            self.member_decl();
+            self.make_family(2, "classDeclBody");
+            self.make_sibling();
+        }
+    }
+
+    fn visibility(&mut self) {
+        if !self.enter("Visibility") {
+            return;
+        }
+        match self.lookahead() {
+            Some(keyword @ "public") | Some(keyword @ "private") => {
+                let keyword = keyword.to_string();
+                self.expect(&keyword, "");
+                self.make_node("string", "visibility");
+            }
+            _ => self.make_node("epsilon", "visibility"),
+        }
+    }
+
+    fn member_decl(&mut self) {
+        if !self.enter("MemberDecl") {
+            return;
+        }
+        if self.lookahead() == Some("func") {
+            self.func_decl();
+        } else {
+            self.var_decl();
+        }
+    }
+
+    fn func_decl(&mut self) {
+        if !self.enter("FuncDecl") {
+            return;
+        }
+        self.expect("func", "");
+        self.expect("id", "naming the member function");
+        self.make_node("string", "id");
+        self.expect("(", "to open the parameter list");
+        self.make_node("list", "fparamList");
+        self.fparams();
+        self.expect(")", "to close the parameter list");
+        self.expect(":", "before the return type");
+        self.func_decl_tail();
+        self.make_family(3, "funcDecl");
+        self.expect(";", "after the member function declaration");
+    }
+
+    fn func_decl_tail(&mut self) {
+        if !self.enter("FuncDeclTail") {
+            return;
+        }
+        if self.lookahead() == Some("void") {
+            self.expect("void", "");
+            self.make_node("epsilon", "type");
+        } else {
+            self.type_();
+        }
+    }
+
+    fn func_def(&mut self) {
+        while self.enter("FuncDef") && self.lookahead() == Some("func") {
+            self.function();
+            self.make_sibling();
+        }
+    }
+
+    fn function(&mut self) {
+        if !self.enter("Function") {
+            return;
+        }
+        self.func_head();
+        self.func_body();
+        self.make_family(5, "funcDef");
+    }
+
+    fn func_head(&mut self) {
+        if !self.enter("FuncHead") {
+            return;
+        }
+        self.expect("func", "");
+        self.expect("id", "naming the function");
+        self.make_node("string", "id");
+        self.class_method();
+        self.expect("(", "to open the parameter list");
+        self.make_node("list", "fparamList");
+        self.fparams();
+        self.expect(")", "to close the parameter list");
+        self.expect(":", "before the return type");
+        self.func_decl_tail();
+    }
+
+    fn class_method(&mut self) {
+        if !self.enter("ClassMethod") {
+            return;
+        }
+        if self.lookahead() == Some("sr") {
+            self.expect("sr", "");
+            self.expect("id", "naming the member function");
+            self.make_node("string", "scopeSpec");
+        } else {
+            self.make_node("epsilon", "scopeSpec");
+        }
+    }
+
+    fn fparams(&mut self) {
+        if !self.enter("FParams") || !self.starts("Type") {
+            return;
+        }
+        self.fparam();
+        self.fparams_tail();
+    }
+
+    fn fparams_tail(&mut self) {
+        while self.enter("FParamsTail") && self.lookahead() == Some(",") {
+            self.expect(",", "");
+            self.fparam();
+        }
+    }
+
+    /// Not a non-terminal of its own in the grammar, shared by `<FParams>` and `<FParamsTail>`
+    fn fparam(&mut self) {
+        self.type_();
+        self.expect("id", "naming the parameter");
+        self.make_node("string", "id");
+        self.make_node("list", "dimlist");
+        self.array_size_rept();
+        self.make_family(3, "fparam");
+        self.make_sibling();
+    }
+
+    fn array_size_rept(&mut self) {
+        while self.enter("ArraySizeRept") && self.lookahead() == Some("[") {
+            self.expect("[", "");
+            self.int_num();
+            self.expect("]", "to close the array dimension");
+        }
+    }
+
+    fn int_num(&mut self) {
+        if !self.enter("IntNum") {
+            return;
+        }
+        if self.lookahead() == Some("intnum") {
+            self.expect("intnum", "");
+            self.make_node("integer", "num");
+        } else {
+            self.make_node("epsilon", "num");
+        }
+        self.make_sibling();
+    }
+
+    fn type_(&mut self) {
+        if !self.enter("Type") {
+            return;
+        }
+        if let Some(terminal) = self.lookahead().map(|t| t.to_string()) {
+            self.expect(&terminal, "");
+            self.make_node("string", "type");
+        }
+    }
+
+    fn var_decl(&mut self) {
+        if !self.enter("VarDecl") {
+            return;
+        }
+        self.type_();
+        self.expect("id", "naming the variable");
+        self.make_node("string", "id");
+        self.make_node("list", "dimlist");
+        self.array_size_rept();
+        self.expect(";", "after the variable declaration");
+        self.make_family(3, "varDecl");
+    }
+
+    // Function bodies and statements =========================================
+
+    fn func_body(&mut self) {
+        if !self.enter("FuncBody") {
+            return;
+        }
+        self.expect("{", "to open the function body");
+        self.method_body_var();
+        self.make_node("list", "statBlock");
+        self.statement_list();
+        self.expect("}", "to close the function body");
+        self.make_family(2, "funcBody");
+    }
+
+    fn method_body_var(&mut self) {
+        if !self.enter("MethodBodyVar") {
+            return;
+        }
+        if self.lookahead() == Some("var") {
+            self.expect("var", "");
+            self.expect("{", "to open the variable declarations");
+            self.make_node("list", "varList");
+            self.var_decl_rep();
+            self.expect("}", "to close the variable declarations");
+        } else {
+            self.make_node("epsilon", "varList");
+        }
+    }
+
+    fn var_decl_rep(&mut self) {
+        while self.enter("VarDeclRep") && self.starts("VarDecl") {
+            self.var_decl();
+            self.make_sibling();
+        }
+    }
+
+    fn statement_list(&mut self) {
+        while self.enter("StatementList") && self.starts("Statement") {
+            self.statement();
+            self.make_sibling();
+        }
+    }
+
+    fn stat_block(&mut self) {
+        if !self.enter("StatBlock") {
+            return;
+        }
+        if self.lookahead() == Some("{") {
+            self.expect("{", "");
+            self.make_node("list", "statBlock");
+            self.statement_list();
+            self.expect("}", "to close the statement block");
+        } else if self.starts("Statement") {
+            self.make_node("list", "statBlock");
+            self.statement();
+            self.make_sibling();
+        } else {
+            self.make_node("epsilon", "statBlock");
+        }
+    }
+
+    fn statement(&mut self) {
+        if !self.enter("Statement") {
+            return;
+        }
+        match self.lookahead() {
+            Some("if") => {
+                self.expect("if", "");
+                self.expect("(", "before the condition");
+                self.expr();
+                self.expect(")", "after the condition");
+                self.expect("then", "after the if condition");
+                self.stat_block();
+                self.expect("else", "after the then block");
+                self.stat_block();
+                self.make_family(3, "ifStat");
+                self.expect(";", "after the if statement");
+            }
+            Some("while") => {
+                self.expect("while", "");
+                self.expect("(", "before the condition");
+                self.expr();
+                self.expect(")", "after the condition");
+                self.stat_block();
+                self.make_family(2, "whileStat");
+                self.expect(";", "after the while statement");
+            }
+            Some("read") => {
+                self.expect("read", "");
+                self.expect("(", "before the variable to read");
+                self.make_node("list", "var");
+                self.variable();
+                self.make_family(1, "readStat");
+                self.expect(")", "after the variable to read");
+                self.expect(";", "after the read statement");
+            }
+            Some(keyword @ "write") | Some(keyword @ "return") => {
+                let keyword = keyword.to_string();
+                self.expect(&keyword, "");
+                self.expect("(", "before the expression");
+                self.expr();
+                self.make_family(1, &format!("{}Stat", keyword));
+                self.expect(")", "after the expression");
+                self.expect(";", &format!("after the {} statement", keyword));
+            }
+            Some(keyword @ "break") | Some(keyword @ "continue") => {
+                let keyword = keyword.to_string();
+                self.expect(&keyword, "");
+                self.make_node("string", &keyword);
+                self.make_family(1, &format!("{}Stat", keyword));
+                self.expect(";", &format!("after {}", keyword));
+            }
+            _ => {
+                self.make_node("list", "var");
+                self.func_or_assign_stat();
+                self.expect(";", "after the statement");
+            }
+        }
+    }
+
+    fn func_or_assign_stat(&mut self) {
+        if !self.enter("FuncOrAssignStat") {
+            return;
+        }
+        self.expect("id", "");
+        self.make_node("string", "id");
+        self.func_or_assign_stat_idnest();
+    }
+
+    fn func_or_assign_stat_idnest(&mut self) {
+        if !self.enter("FuncOrAssignStatIdnest") {
+            return;
+        }
+        if self.lookahead() == Some("(") {
+            self.function_call();
+            self.func_or_assign_stat_idnest_func_tail();
+        } else {
+            self.data_member();
+            self.func_or_assign_stat_idnest_var_tail();
+        }
+    }
+
+    fn func_or_assign_stat_idnest_func_tail(&mut self) {
+        if !self.enter("FuncOrAssignStatIdnestFuncTail") || self.lookahead() != Some(".") {
+            return;
+        }
+        self.expect(".", "");
+        self.expect("id", "naming the member");
+        self.make_node("string", "id");
+        self.func_stat_tail();
+    }
+
+    fn func_stat_tail(&mut self) {
+        if !self.enter("FuncStatTail") {
+            return;
+        }
+        if self.lookahead() == Some("(") {
+            self.function_call();
+            self.func_stat_tail_idnest();
+        } else {
+            self.data_member();
+            self.expect(".", "to call a member function");
+            self.expect("id", "naming the member");
+            self.make_node("string", "id");
+            self.func_stat_tail();
+        }
+    }
+
+    fn func_stat_tail_idnest(&mut self) {
+        if !self.enter("FuncStatTailIdnest") || self.lookahead() != Some(".") {
+            return;
+        }
+        self.expect(".", "");
+        self.expect("id", "naming the member");
+        self.make_node("string", "id");
+        self.func_stat_tail();
+    }
+
+    fn func_or_assign_stat_idnest_var_tail(&mut self) {
+        if !self.enter("FuncOrAssignStatIdnestVarTail") {
+            return;
+        }
+        if self.lookahead() == Some(".") {
+            self.expect(".", "");
+            self.expect("id", "naming the member");
+            self.make_node("string", "id");
+            self.func_or_assign_stat_idnest();
+        } else {
+            self.assign_stat_tail();
+        }
+    }
+
+    fn assign_stat_tail(&mut self) {
+        if !self.enter("AssignStatTail") {
+            return;
+        }
+        self.assign_op();
+        self.expr();
+        self.make_family(2, "assignOp");
+    }
+
+    fn assign_op(&mut self) {
+        if self.enter("AssignOp") {
+            self.expect("assign", "");
+        }
+    }
+
+    fn variable(&mut self) {
+        if !self.enter("Variable") {
+            return;
+        }
+        self.expect("id", "");
+        self.make_node("string", "id");
+        self.variable_idnest();
+    }
+
+    fn variable_idnest(&mut self) {
+        if !self.enter("VariableIdnest") {
+            return;
+        }
+        self.data_member();
+        self.variable_idnest_tail();
+    }
+
+    fn variable_idnest_tail(&mut self) {
+        if !self.enter("VariableIdnestTail") || self.lookahead() != Some(".") {
+            return;
+        }
+        self.expect(".", "");
+        self.expect("id", "naming the member");
+        self.make_node("string", "id");
+        self.variable_idnest();
+    }
+
+    /// Indices following an identifier, not a non-terminal of its own in the grammar
+    fn data_member(&mut self) {
+        self.make_node("list", "indexList");
+        self.indice_rep();
+        self.make_family(2, "dataMember");
+        self.make_sibling();
+    }
+
+    /// Arguments following an identifier, not a non-terminal of its own in the grammar
+    fn function_call(&mut self) {
+        self.expect("(", "");
+        self.make_node("list", "aParams");
+        self.aparams();
+        self.expect(")", "to close the argument list");
+        self.make_family(2, "fCall");
+        self.make_sibling();
+    }
+
+    fn indice_rep(&mut self) {
+        while self.enter("IndiceRep") && self.lookahead() == Some("[") {
+            self.expect("[", "");
+            self.expr();
+            self.make_sibling();
+            self.expect("]", "to close the index");
+        }
+    }
+
+    fn aparams(&mut self) {
+        if !self.enter("AParams") || !self.starts("Expr") {
+            return;
+        }
+        self.expr();
+        self.make_sibling();
+        self.aparams_tail();
+    }
+
+    fn aparams_tail(&mut self) {
+        while self.enter("AParamsTail") && self.lookahead() == Some(",") {
+            self.expect(",", "");
+            self.expr();
+            self.make_sibling();
+        }
+    }
+
+    // Expressions ============================================================
+
+    fn expr(&mut self) {
+        if !self.enter("Expr") {
+            return;
+        }
+        self.arith_expr();
+        self.expr_tail();
+    }
+
+    fn expr_tail(&mut self) {
+        if !self.enter("ExprTail") || !self.starts("RelOp") {
+            return;
+        }
+        self.rel_op();
+        self.arith_expr();
+        self.make_family(3, "relOp");
+    }
+
+    fn arith_expr(&mut self) {
+        if !self.enter("ArithExpr") {
+            return;
+        }
+        self.term();
+        self.arith_expr_tail();
+    }
+
+    fn arith_expr_tail(&mut self) {
+        while self.enter("ArithExprTail") && self.starts("AddOp") {
+            self.add_op();
+            self.term();
+            self.make_family(3, "addOp");
+        }
+    }
+
+    fn term(&mut self) {
+        if !self.enter("Term") {
+            return;
+        }
+        self.factor();
+        self.term_tail();
+    }
+
+    fn term_tail(&mut self) {
+        while self.enter("TermTail") && self.starts("MultOp") {
+            self.mult_op();
+            self.factor();
+            self.make_family(3, "mulOp");
+        }
+    }
+
+    fn factor(&mut self) {
+        if !self.enter("Factor") {
+            return;
+        }
+        match self.lookahead() {
+            Some("intnum") => {
+                self.expect("intnum", "");
+                self.make_node("integer", "intfactor");
+            }
+            Some("floatnum") => {
+                self.expect("floatnum", "");
+                self.make_node("float", "floatfactor");
+            }
+            Some("stringlit") => {
+                self.expect("stringlit", "");
+                self.make_node("string", "stringfactor");
+            }
+            Some("(") => {
+                self.expect("(", "");
+                self.expr();
+                self.expect(")", "to close the parenthesized expression");
+            }
+            Some("not") => {
+                self.expect("not", "");
+                self.factor();
+                self.make_family(1, "not");
+            }
+            Some("+") | Some("-") => {
+                self.sign();
+                self.factor();
+                self.make_family(2, "sign");
+            }
+            Some("qm") => {
+                self.expect("qm", "");
+                self.expect("[", "to open the ternary operator");
+                self.expr();
+                self.expect(":", "after the ternary condition");
+                self.expr();
+                self.expect(":", "between the ternary alternatives");
+                self.expr();
+                self.expect("]", "to close the ternary operator");
+                self.make_family(3, "ternary");
+            }
+            _ => {
+                self.make_node("list", "var");
+                self.func_or_var();
+            }
+        }
+    }
+
+    fn func_or_var(&mut self) {
+        if !self.enter("FuncOrVar") {
+            return;
+        }
+        self.expect("id", "");
+        self.make_node("string", "id");
+        self.func_or_var_idnest();
+    }
+
+    fn func_or_var_idnest(&mut self) {
+        if !self.enter("FuncOrVarIdnest") {
+            return;
+        }
+        if self.lookahead() == Some("(") {
+            self.function_call();
+        } else {
+            self.data_member();
+        }
+        self.func_or_var_idnest_tail();
+    }
+
+    fn func_or_var_idnest_tail(&mut self) {
+        if !self.enter("FuncOrVarIdnestTail") || self.lookahead() != Some(".") {
+            return;
+        }
+        self.expect(".", "");
+        self.expect("id", "naming the member");
+        self.make_node("string", "id");
+        self.func_or_var_idnest();
+    }
+
+    fn add_op(&mut self) {
+        if !self.enter("AddOp") {
+            return;
+        }
+        if let Some(operator) = self.lookahead().map(|t| t.to_string()) {
+            self.expect(&operator, "");
+            self.make_node("string", &operator);
+        }
+    }
+
+    fn mult_op(&mut self) {
+        if !self.enter("MultOp") {
+            return;
+        }
+        if let Some(operator) = self.lookahead().map(|t| t.to_string()) {
+            self.expect(&operator, "");
+            self.make_node("string", &operator);
+        }
+    }
+
+    fn rel_op(&mut self) {
+        if !self.enter("RelOp") {
+            return;
+        }
+        if let Some(operator) = self.lookahead().map(|t| t.to_string()) {
+            self.expect(&operator, "");
+            self.make_node(
+                "string",
+                match operator.as_str() {
+                    "eq" => "releq",
+                    "neq" => "relneq",
+                    other => other,
+                },
+            );
+        }
+    }
+
+    fn sign(&mut self) {
+        if !self.enter("Sign") {
+            return;
+        }
+        if self.lookahead() == Some("+") {
+            self.expect("+", "");
+            self.make_node("string", "pos");
+        } else {
+            self.expect("-", "");
+            self.make_node("string", "neg");
+        }
+    }
+}
+
+fn describe_non_terminal(non_terminal: &str) -> &'static str {
+    match non_terminal {
+        "START" | "Prog" => "a class declaration, a function definition or the main function",
+        "ClassDecl" => "a class declaration",
+        "Inherit" | "NestedId" => "the inherited classes",
+        "ClassDeclBody" | "MemberDecl" | "Visibility" => "a member declaration",
+        "FuncDecl" => "a member function declaration",
+        "FuncDeclTail" => "a return type",
+        "FuncDef" | "Function" | "FuncHead" => "a function definition",
+        "ClassMethod" => "a parameter list",
+        "FParams" | "FParamsTail" => "a parameter",
+        "ArraySizeRept" | "IntNum" => "an array dimension",
+        "Type" => "a type",
+        "VarDecl" | "VarDeclRep" => "a variable declaration",
+        "FuncBody" => "a function body",
+        "MethodBodyVar" | "StatementList" => "a statement",
+        "StatBlock" => "a statement or a statement block",
+        "Statement" => "a statement",
+        "AssignStatTail" | "AssignOp" => "an assignment",
+        "Variable" | "VariableIdnest" | "VariableIdnestTail" => "a variable",
+        "IndiceRep" => "an index",
+        "AParams" | "AParamsTail" => "an argument",
+        "RelOp" => "a relational operator",
+        "AddOp" => "an additive operator",
+        "MultOp" => "a multiplicative operator",
+        "Sign" => "a sign",
+        _ => "an expression or a member access",
+    }
+}
+
+fn describe_terminal(terminal: &str) -> String {
+    match terminal {
+        "id" => "an identifier".to_string(),
+        "intnum" => "an integer literal".to_string(),
+        "floatnum" => "a float literal".to_string(),
+        "stringlit" => "a string literal".to_string(),
+        "sr" => "'::'".to_string(),
+        "qm" => "'?'".to_string(),
+        "assign" => "'='".to_string(),
+        "not" => "'!'".to_string(),
+        _ => format!("'{}'", terminal),
+    }
+}
+
+fn with_context(context: &str) -> String {
+    if context.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", context)
+    }
+}
+
+fn describe_token(token: &Option<Token>) -> String {
+    match token {
+        Some(token) => format!("'{}'", token.lexeme),
+        None => "the end of file".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, ParseTable};
+    use lexical_analyzer::{lexer::Lexer, lexical_rule::LexicalRule};
+    use regex::Regex;
+
+    /// The position of every syntax error, None standing for the end of file
+    fn error_positions(path: &str) -> Vec<Option<(usize, usize)>> {
+        let position = Regex::new(r"line (\d+), col (\d+)").unwrap();
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .filter(|line| line.starts_with("Syntax error"))
+            .map(|line| {
+                position
+                    .captures(line)
+                    .map(|captures| (captures[1].parse().unwrap(), captures[2].parse().unwrap()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_ast_and_errors_as_ll1_parser() {
+        let rules = LexicalRule::from_file("../resources/lex_tokens.txt").unwrap();
+        let keywords = std::fs::read_to_string("../resources/keywords.txt")
+            .unwrap()
+            .lines()
+            .map(|keyword| keyword.to_string())
+            .collect();
+        let lexer = Lexer::new(rules, keywords);
+        let grammar =
+            Grammar::from_reader(File::open("../resources/LL1.paquet.grm").unwrap()).unwrap();
+        let parse_table = ParseTable::from_grammar(&grammar);
+        let parser = RecursiveDescentParser::new(&grammar);
+
+        let output_folder = std::env::temp_dir().join("paquet_recursive_descent_test");
+        let output_folder = output_folder.to_str().unwrap();
+        let mut with_errors = 0;
+        for entry in std::fs::read_dir("../test_sources").unwrap() {
+            let source = entry.unwrap().path().to_str().unwrap().to_string();
+            let mut ll1_output = OutputConfig::new(&source, output_folder);
+            let ll1_ast = parse(
+                &mut lexer.lex(&source, &ll1_output.lex_error_path),
+                &grammar,
+                &parse_table,
+                &mut ll1_output,
+            );
+            let ll1_errors = error_positions(&ll1_output.syntax_error_path);
+
+            let mut output = OutputConfig::new(&source, output_folder);
+            let ast = parser.parse(&mut lexer.lex(&source, &output.lex_error_path), &mut output);
+            let errors = error_positions(&output.syntax_error_path);
+
+            assert_eq!(ast, ll1_ast, "different AST for {}", source);
+            assert_eq!(errors, ll1_errors, "different errors for {}", source);
+            if !errors.is_empty() {
+                with_errors += 1;
+            }
+        }
+        assert!(with_errors > 0);
+    }
+}
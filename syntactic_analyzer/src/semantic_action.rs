@@ -1,3 +1,16 @@
+//! Semantic actions building the AST, written between @ in grammar files
+//!
+//! - `makenode~<type>~<name>` pushes a node valued by the previous token, of type
+//!   `integer`, `float`, `string`, `list` or `epsilon`
+//! - `makefamily~<n>~<name>` pops n nodes and pushes a node having them as children
+//! - `makesibling` pops a node and adds it to the list underneath
+//! - `adoptchildren` pops a node and adds its children to the list underneath
+//! - `rename~<name>` renames the top node
+//! - `flatten` replaces the top node's children of the same name by their children
+//! - `setattribute~<key>~<value>` sets an attribute of the top node
+//! - `makeoptional` turns the top node into an epsilon node if it is an empty list
+//!
+//! Actions are validated when the grammar is read.
 use crate::symbol::Symbol;
 use ast::{Data, Node};
 use lazy_static::lazy_static;
@@ -10,6 +23,9 @@ use std::str::FromStr;
 #[derive(Debug, PartialEq, Hash, Clone)]
 pub enum Action {
     /// Create an AST node of a supplied type, with the name of the previous token
+    ///
+    /// An error node is created instead if the value of the node would come from a
+    /// token inserted to repair a syntax error, which marks it as an error token.
     MakeNode(String, String),
 
     /// Create an AST node by using n elements from the stack as children
@@ -17,19 +33,43 @@ pub enum Action {
 
     /// Push the top stack element to the list element underneath it
     MakeSibling,
+
+    /// Push the children of the top stack element to the list element underneath it
+    AdoptChildren,
+
+    /// Change the type of the top stack element
+    Rename(String),
+
+    /// Replace the children of the top stack element having its type by their own
+    /// children, undoing the nesting of recursive productions
+    Flatten,
+
+    /// Set an attribute of the top stack element
+    SetAttribute(String, String),
+
+    /// Change the top stack element into an epsilon node if it is a list without
+    /// elements
+    MakeOptional,
 }
 
 lazy_static! {
-    static ref MAKE_NODE_RE: Regex = Regex::new("makenode~(?P<type>.*)~(?P<name>.*)")
+    static ref MAKE_NODE_RE: Regex = Regex::new("^makenode~(?P<type>[^~]*)~(?P<name>[^~]+)$")
         .expect("Regular expression failed to compile");
-    static ref MAKE_FAMILY_RE: Regex = Regex::new("makefamily~(?P<size>.*)~(?P<name>.*)")
+    static ref MAKE_FAMILY_RE: Regex = Regex::new("^makefamily~(?P<size>[^~]*)~(?P<name>[^~]+)$")
         .expect("Regular expression failed to compile");
     static ref MAKE_SIBLING_RE: Regex =
-        Regex::new("makesibling").expect("Regular expression failed to compile");
+        Regex::new("^makesibling$").expect("Regular expression failed to compile");
     static ref ADOPT_CHILDREN_RE: Regex =
-        Regex::new("adoptchildren").expect("Regular expression failed to compile");
+        Regex::new("^adoptchildren$").expect("Regular expression failed to compile");
     static ref RENAME_RE: Regex =
-        Regex::new("rename~(?P<name>.*)").expect("Regular expression failed to compile");
+        Regex::new("^rename~(?P<name>[^~]+)$").expect("Regular expression failed to compile");
+    static ref FLATTEN_RE: Regex =
+        Regex::new("^flatten$").expect("Regular expression failed to compile");
+    static ref SET_ATTRIBUTE_RE: Regex =
+        Regex::new("^setattribute~(?P<key>[^~]+)~(?P<value>[^~]*)$")
+            .expect("Regular expression failed to compile");
+    static ref MAKE_OPTIONAL_RE: Regex =
+        Regex::new("^makeoptional$").expect("Regular expression failed to compile");
 }
 
 const INTEGER: &str = "integer";
@@ -38,25 +78,44 @@ const STRING: &str = "string";
 const LIST: &str = "list";
 const EPSILON: &str = "epsilon";
 
+const STRING_LITERAL: &str = "stringlit";
+
 impl FromStr for Action {
     type Err = String;
 
+    /// Parse an action written without its surrounding @, the error describes why
+    /// it is invalid
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if MAKE_NODE_RE.is_match(s) {
-            let captures = MAKE_NODE_RE.captures(s).unwrap();
+        if let Some(captures) = MAKE_NODE_RE.captures(s) {
             let data_type = captures["type"].to_string();
+            if ![INTEGER, FLOAT, STRING, LIST, EPSILON].contains(&data_type.as_str()) {
+                return Err(format!("unknown node value type \"{}\"", data_type));
+            }
             let name = captures["name"].to_string();
-            return Ok(Action::MakeNode(data_type, name));
-        } else if MAKE_FAMILY_RE.is_match(s) {
-            let captures = MAKE_FAMILY_RE.captures(s).unwrap();
-            let number = captures["size"].to_string().parse().unwrap();
+            Ok(Action::MakeNode(data_type, name))
+        } else if let Some(captures) = MAKE_FAMILY_RE.captures(s) {
+            let number = captures["size"]
+                .parse()
+                .map_err(|_| format!("\"{}\" is not a number of nodes", &captures["size"]))?;
             let name = captures["name"].to_string();
-            return Ok(Action::MakeFamily(number, name));
+            Ok(Action::MakeFamily(number, name))
         } else if MAKE_SIBLING_RE.is_match(s) {
-            return Ok(Action::MakeSibling);
+            Ok(Action::MakeSibling)
+        } else if ADOPT_CHILDREN_RE.is_match(s) {
+            Ok(Action::AdoptChildren)
+        } else if let Some(captures) = RENAME_RE.captures(s) {
+            Ok(Action::Rename(captures["name"].to_string()))
+        } else if FLATTEN_RE.is_match(s) {
+            Ok(Action::Flatten)
+        } else if let Some(captures) = SET_ATTRIBUTE_RE.captures(s) {
+            Ok(Action::SetAttribute(
+                captures["key"].to_string(),
+                captures["value"].to_string(),
+            ))
+        } else if MAKE_OPTIONAL_RE.is_match(s) {
+            Ok(Action::MakeOptional)
         } else {
-            error!("Unrecognized semantic action {}", s);
-            panic!();
+            Err(format!("unrecognized semantic action \"{}\"", s))
         }
     }
 }
@@ -71,9 +130,51 @@ impl Action {
                 self.make_family(semantic_stack, previous_token, *size, name)
             }
             Action::MakeSibling => self.make_sibling(semantic_stack),
+            Action::AdoptChildren => self.adopt_children(semantic_stack),
+            Action::Rename(name) => {
+                if let Some(top) = self.top(semantic_stack) {
+                    *top.name_mut() = name.clone();
+                }
+            }
+            Action::Flatten => {
+                if let Some(top) = self.top(semantic_stack) {
+                    let name = top.name().clone();
+                    if let Data::Children(children) = top.data_mut() {
+                        *children = std::mem::take(children)
+                            .into_iter()
+                            .flat_map(|child| match child.data() {
+                                Data::Children(grandchildren) if *child.name() == name => {
+                                    grandchildren.clone()
+                                }
+                                _ => vec![child],
+                            })
+                            .collect();
+                    }
+                }
+            }
+            Action::SetAttribute(key, value) => {
+                if let Some(top) = self.top(semantic_stack) {
+                    top.set_attribute(key, value);
+                }
+            }
+            Action::MakeOptional => {
+                if let Some(top) = self.top(semantic_stack) {
+                    if matches!(top.data(), Data::Children(children) if children.is_empty()) {
+                        *top.data_mut() = Data::Epsilon;
+                    }
+                }
+            }
         }
     }
 
+    fn top<'a>(&self, semantic_stack: &'a mut [Node]) -> Option<&'a mut Node> {
+        let top = semantic_stack.last_mut();
+        if top.is_none() {
+            error!("Expected a node on the stack for the {} action", self);
+        }
+        top
+    }
+
     fn make_node(
         &self,
         semantic_stack: &mut Vec<Node>,
@@ -81,6 +182,11 @@ impl Action {
         data_type: &str,
         name: &str,
     ) {
+        if previous_token.error_token && [INTEGER, FLOAT, STRING].contains(&data_type) {
+            semantic_stack.push(Node::error(previous_token.line, previous_token.column));
+            return;
+        }
+
         let data = match data_type {
             INTEGER => {
                 let int = previous_token.lexeme.parse::<i64>();
@@ -106,6 +212,18 @@ impl Action {
                     Data::Float(float.unwrap())
                 }
             }
+            STRING if previous_token.token_type == STRING_LITERAL => {
+                match previous_token.string_value() {
+                    Some(string) => Data::String(string),
+                    None => {
+                        error!(
+                            "Failed to decode lexeme {} as string literal",
+                            previous_token.lexeme
+                        );
+                        return;
+                    }
+                }
+            }
             STRING => Data::String(previous_token.lexeme.clone()),
             LIST => Data::Children(Vec::new()),
             EPSILON => Data::Epsilon,
@@ -183,11 +301,75 @@ impl Action {
         };
         if let Data::Children(sibling_list) = top.data_mut() {
             sibling_list.push(sibling);
+        } else if top.is_error() {
+            // The list was lost to a syntax error, keep its following elements
+            *top.data_mut() = Data::Children(vec![sibling]);
         } else {
             error!("Expected a sibling list after a make_sibling action");
             error!("Node was {:?}", top);
         }
     }
+
+    fn adopt_children(&self, semantic_stack: &mut Vec<Node>) {
+        let parent = match semantic_stack.pop() {
+            None => {
+                error!("Expected a node whose children to adopt for adopt_children action");
+                return;
+            }
+            Some(s) => s,
+        };
+
+        let top = match semantic_stack.last_mut() {
+            None => {
+                error!("Expected a list adopting the children after an adopt_children action");
+                return;
+            }
+            Some(s) => s,
+        };
+        let children = match parent.data() {
+            Data::Children(children) => children.clone(),
+            // An empty list made into an epsilon node has no children to give
+            _ => Vec::new(),
+        };
+        if let Data::Children(sibling_list) = top.data_mut() {
+            sibling_list.extend(children);
+        } else if top.is_error() {
+            *top.data_mut() = Data::Children(children);
+        } else {
+            error!("Expected a list adopting the children after an adopt_children action");
+            error!("Node was {:?}", top);
+        }
+    }
+}
+
+impl Action {
+    /// The number of nodes the action pops from the semantic stack and the number it
+    /// pushes, the list a sibling is added to counts as popped and pushed back
+    pub fn stack_effect(&self) -> (usize, usize) {
+        match self {
+            Action::MakeNode(_, _) => (0, 1),
+            Action::MakeFamily(size, _) => (*size, 1),
+            Action::MakeSibling | Action::AdoptChildren => (2, 1),
+            Action::Rename(_)
+            | Action::Flatten
+            | Action::SetAttribute(_, _)
+            | Action::MakeOptional => (1, 1),
+        }
+    }
+
+    /// The action as written between @ in a grammar file
+    pub fn grm_notation(&self) -> String {
+        match self {
+            Action::MakeNode(data_type, name) => format!("makenode~{}~{}", data_type, name),
+            Action::MakeFamily(size, name) => format!("makefamily~{}~{}", size, name),
+            Action::MakeSibling => "makesibling".to_string(),
+            Action::AdoptChildren => "adoptchildren".to_string(),
+            Action::Rename(name) => format!("rename~{}", name),
+            Action::Flatten => "flatten".to_string(),
+            Action::SetAttribute(key, value) => format!("setattribute~{}~{}", key, value),
+            Action::MakeOptional => "makeoptional".to_string(),
+        }
+    }
 }
 
 impl fmt::Display for Action {
@@ -196,6 +378,91 @@ impl fmt::Display for Action {
             Action::MakeNode(data_type, name) => write!(f, "make_node({}, {})", data_type, name),
             Action::MakeFamily(size, name) => write!(f, "make_family({}, {})", size, name),
             Action::MakeSibling => write!(f, "make_sibling"),
+            Action::AdoptChildren => write!(f, "adopt_children"),
+            Action::Rename(name) => write!(f, "rename({})", name),
+            Action::Flatten => write!(f, "flatten"),
+            Action::SetAttribute(key, value) => write!(f, "set_attribute({}, {})", key, value),
+            Action::MakeOptional => write!(f, "make_optional"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token() -> Token {
+        Token {
+            token_type: "id".to_string(),
+            error_token: false,
+            lexeme: "x".to_string(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn run(actions: &[&str]) -> Vec<Node> {
+        let mut semantic_stack = Vec::new();
+        for action in actions {
+            let action: Action = action.parse().unwrap();
+            action.execute(&mut semantic_stack, token(), Symbol::Eos);
+        }
+        semantic_stack
+    }
+
+    #[test]
+    fn test_parse_actions() {
+        for notation in &[
+            "makenode~list~statBlock",
+            "makefamily~2~assignOp",
+            "makesibling",
+            "adoptchildren",
+            "rename~block",
+            "flatten",
+            "setattribute~operator~+",
+            "makeoptional",
+        ] {
+            let action: Action = notation.parse().unwrap();
+            assert_eq!(action.grm_notation(), *notation);
+        }
+        assert!("makenode~tree~x".parse::<Action>().is_err());
+        assert!("makefamily~two~x".parse::<Action>().is_err());
+        assert!("makesiblings".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn test_tree_shaping_actions() {
+        let stack = run(&[
+            "makenode~list~list",
+            "makenode~list~list",
+            "makenode~string~id",
+            "makesibling",
+            "makesibling",
+            "makenode~string~id",
+            "makesibling",
+            "flatten",
+            "rename~ids",
+            "setattribute~kind~names",
+        ]);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack[0].name(), "ids");
+        assert_eq!(stack[0].attribute("kind").unwrap(), "names");
+        match stack[0].data() {
+            Data::Children(children) => assert_eq!(children.len(), 2),
+            _ => panic!("the flattened list lost its children"),
+        }
+
+        let stack = run(&[
+            "makenode~list~list",
+            "makenode~list~inner",
+            "makenode~string~id",
+            "makesibling",
+            "adoptchildren",
+            "makenode~list~empty",
+            "makeoptional",
+        ]);
+        assert_eq!(stack.len(), 2);
+        assert!(matches!(stack[0].data(), Data::Children(children) if children.len() == 1));
+        assert_eq!(stack[1].data(), &Data::Epsilon);
+    }
+}
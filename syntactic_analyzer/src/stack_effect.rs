@@ -0,0 +1,77 @@
+//! Effect of semantic actions on the semantic stack, used to keep the AST well
+//! formed when a syntax error makes the parser skip part of a production
+use crate::grammar::Sentence;
+use crate::symbol::Symbol;
+use ast::{Data, Node};
+use std::collections::HashMap;
+
+/// The net number of nodes a sentence leaves on the semantic stack, None if the
+/// effect of one of its non-terminals is unknown
+pub fn sentence_stack_effect(
+    sentence: &Sentence,
+    effects: &HashMap<Symbol, isize>,
+) -> Option<isize> {
+    let mut effect = 0;
+    for symbol in sentence {
+        match symbol {
+            Symbol::SemanticAction(action) => {
+                let (pops, pushes) = action.stack_effect();
+                effect += pushes as isize - pops as isize;
+            }
+            Symbol::NonTerminal(_) => effect += effects.get(symbol)?,
+            _ => (),
+        }
+    }
+    Some(effect)
+}
+
+/// The net number of nodes every non-terminal leaves on the semantic stack
+///
+/// The effect of a non-terminal is the one of its first production whose
+/// non-terminals have a known effect, starting from the productions without
+/// non-terminals. Non-terminals deriving no terminal string are left out.
+pub fn non_terminal_stack_effects(
+    productions: &HashMap<Symbol, Vec<Sentence>>,
+) -> HashMap<Symbol, isize> {
+    let mut effects = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (non_terminal, sentences) in productions {
+            if effects.contains_key(non_terminal) {
+                continue;
+            }
+            if let Some(effect) = sentences
+                .iter()
+                .find_map(|sentence| sentence_stack_effect(sentence, &effects))
+            {
+                effects.insert(non_terminal.clone(), effect);
+                changed = true;
+            }
+        }
+    }
+    effects
+}
+
+/// Stand in for the nodes a skipped non-terminal would have left on the stack
+///
+/// A positive effect pushes error nodes, a negative one gathers the nodes the
+/// non-terminal would have consumed under a single error node.
+pub(crate) fn push_error_nodes(
+    semantic_stack: &mut Vec<Node>,
+    effect: isize,
+    line: usize,
+    column: usize,
+) {
+    if effect >= 0 {
+        for _ in 0..effect {
+            semantic_stack.push(Node::error(line, column));
+        }
+    } else {
+        let count = ((1 - effect) as usize).min(semantic_stack.len());
+        let children = semantic_stack.split_off(semantic_stack.len() - count);
+        let mut node = Node::error(line, column);
+        *node.data_mut() = Data::Children(children);
+        semantic_stack.push(node);
+    }
+}
@@ -31,6 +31,15 @@ impl Symbol {
             None => return Symbol::Eos,
         }
     }
+
+    /// The symbol as written in a grammar file, the end of stream is written $
+    pub fn grm_notation(&self) -> String {
+        match self {
+            Symbol::SemanticAction(action) => format!("@{}@", action.grm_notation()),
+            Symbol::Eos => "$".to_string(),
+            _ => self.to_string(),
+        }
+    }
 }
 
 impl FromStr for Symbol {
@@ -58,9 +67,7 @@ impl FromStr for Symbol {
             return Ok(Symbol::Epsilon);
         } else if ACTION_RE.is_match(s) {
             let captures = ACTION_RE.captures(s).unwrap();
-            return Ok(Symbol::SemanticAction(
-                captures["action"].parse().expect("Failed to parse action"),
-            ));
+            return captures["action"].parse().map(Symbol::SemanticAction);
         } else {
             error!("Unexpected symbol in grammar {:?}", s);
             panic!();
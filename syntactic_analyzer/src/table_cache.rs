@@ -0,0 +1,192 @@
+//! Persistence of the follow sets and parse table computed from a grammar file
+//!
+//! The cache is a text file keyed by a hash of the grammar file's content and by
+//! the versions of the code computing the sets, so any edit to the grammar or to
+//! the analysis invalidates it. Productions are not cached since reading them
+//! from the grammar file is cheap compared to the set computations.
+//!
+//! Format, one entry per line with symbols in the grammar file notation:
+//! ```text
+//! paquet-table-cache <algorithm version> <crate version>
+//! hash <16 hexadecimal digits>
+//! follow <NonTerminal> 'terminal' ... $
+//! table <NonTerminal> 'terminal' <production index>
+//! ```
+use crate::grammar::{read_productions, Grammar};
+use crate::parse_table::ParseTable;
+use crate::symbol::Symbol;
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+
+/// Bumped whenever the computation of the follow sets or of the parse table changes
+pub const ALGORITHM_VERSION: u32 = 2;
+
+/// The first line of a cache, older analyses write a different one
+fn header() -> String {
+    format!(
+        "paquet-table-cache {} {}",
+        ALGORITHM_VERSION,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// 64 bit FNV-1a hash, stable across compiler versions unlike std's DefaultHasher
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Build the grammar and parse table of a grammar file, reusing the cache file
+/// if it was created from the same grammar content
+///
+/// A missing, stale or unreadable cache is rebuilt, failing to write it only warns.
+pub fn load_or_build(
+    grammar_path: &str,
+    cache_path: &str,
+) -> std::io::Result<(Grammar, ParseTable)> {
+    let content = std::fs::read(grammar_path)?;
+    let hash = content_hash(&content);
+    let (productions, start) = read_productions(content.as_slice())?;
+
+    if let Ok(cache) = std::fs::read_to_string(cache_path) {
+        match parse_cache(&cache, hash) {
+            Ok((follow_sets, table)) => {
+                info!("Loaded parse table from cache \"{}\"", cache_path);
+                let grammar = Grammar::with_follow_sets(&productions, &start, follow_sets);
+                return Ok((grammar, ParseTable { table }));
+            }
+            Err(reason) => info!("Ignoring parse table cache \"{}\": {}", cache_path, reason),
+        }
+    }
+
+    let grammar = Grammar::new(&productions, &start);
+    let parse_table = ParseTable::from_grammar(&grammar);
+    match std::fs::write(cache_path, serialize(&grammar, &parse_table, hash)) {
+        Ok(_) => info!("Wrote parse table cache \"{}\"", cache_path),
+        Err(err) => warn!(
+            "Failed to write parse table cache \"{}\": {}",
+            cache_path, err
+        ),
+    }
+    Ok((grammar, parse_table))
+}
+
+/// The cache file content for a grammar, lines are sorted to keep it stable
+pub fn serialize(grammar: &Grammar, parse_table: &ParseTable, hash: u64) -> String {
+    let mut lines = Vec::new();
+    for (non_terminal, follow_set) in grammar.follow_sets() {
+        let mut follow: Vec<String> = follow_set.iter().map(|s| s.grm_notation()).collect();
+        follow.sort();
+        lines.push(format!(
+            "follow {} {}",
+            non_terminal.grm_notation(),
+            follow.join(" ")
+        ));
+    }
+    for (non_terminal, row) in &parse_table.table {
+        for (terminal, option) in row {
+            lines.push(format!(
+                "table {} {} {}",
+                non_terminal.grm_notation(),
+                terminal.grm_notation(),
+                option
+            ));
+        }
+    }
+    lines.sort();
+    format!("{}\nhash {:016x}\n{}\n", header(), hash, lines.join("\n"))
+}
+
+type CachedSets = (
+    HashMap<Symbol, HashSet<Symbol>>,
+    HashMap<Symbol, HashMap<Symbol, usize>>,
+);
+
+fn parse_cache(cache: &str, expected_hash: u64) -> Result<CachedSets, String> {
+    let mut lines = cache.lines();
+    if lines.next() != Some(header().as_str()) {
+        return Err("written by another version of the analysis".to_string());
+    }
+    match lines.next().and_then(|line| line.strip_prefix("hash ")) {
+        Some(hash) if u64::from_str_radix(hash, 16) == Ok(expected_hash) => (),
+        Some(_) => return Err("grammar file changed".to_string()),
+        None => return Err("missing grammar hash".to_string()),
+    }
+
+    let mut follow_sets = HashMap::new();
+    let mut table: HashMap<Symbol, HashMap<Symbol, usize>> = HashMap::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["follow", non_terminal, follow @ ..] => {
+                let follow = follow
+                    .iter()
+                    .map(|s| parse_symbol(s))
+                    .collect::<Result<_, _>>()?;
+                follow_sets.insert(parse_symbol(non_terminal)?, follow);
+            }
+            ["table", non_terminal, terminal, option] => {
+                let option = option
+                    .parse()
+                    .map_err(|_| format!("invalid production index \"{}\"", option))?;
+                table
+                    .entry(parse_symbol(non_terminal)?)
+                    .or_default()
+                    .insert(parse_symbol(terminal)?, option);
+            }
+            _ => return Err(format!("malformed line \"{}\"", line)),
+        }
+    }
+
+    // Every non-terminal has a follow set and a table row, even if empty
+    for non_terminal in follow_sets.keys() {
+        table.entry(non_terminal.clone()).or_default();
+    }
+    Ok((follow_sets, table))
+}
+
+/// Symbols are restricted to those found in follow sets and the parse table
+fn parse_symbol(s: &str) -> Result<Symbol, String> {
+    let is_quoted =
+        |open: char, close: char| s.len() > 1 && s.starts_with(open) && s.ends_with(close);
+    if s == "$" {
+        Ok(Symbol::Eos)
+    } else if is_quoted('\'', '\'') {
+        Ok(Symbol::Terminal(s[1..s.len() - 1].to_string()))
+    } else if is_quoted('<', '>') {
+        Ok(Symbol::NonTerminal(s[1..s.len() - 1].to_string()))
+    } else {
+        Err(format!("unexpected symbol \"{}\"", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let source = r#"
+            <E> ::= <T> <E'>
+            <E'> ::= '+' <T> @makesibling@ <E'>
+            <E'> ::= EPSILON
+            <T> ::= 'id' @makenode~string~id@
+        "#;
+        let grammar = Grammar::from_reader(source.as_bytes()).unwrap();
+        let parse_table = ParseTable::from_grammar(&grammar);
+        let hash = content_hash(source.as_bytes());
+        let cache = serialize(&grammar, &parse_table, hash);
+
+        let (follow_sets, table) = parse_cache(&cache, hash).unwrap();
+        assert_eq!(&follow_sets, grammar.follow_sets());
+        assert_eq!(table, parse_table.table);
+        assert!(parse_cache(&cache, hash + 1).is_err());
+
+        // A cache written by another analysis is ignored
+        let stale = cache.replacen(&header(), "paquet-table-cache 1", 1);
+        assert!(parse_cache(&stale, hash).is_err());
+    }
+}